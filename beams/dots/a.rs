@@ -43,7 +43,7 @@ struct Model {
     egui: Egui,
 }
 
-fn rotate_points(nodes: &mut Vec<Node>, angles: &mut Angles) {
+fn rotate_points(nodes: &mut [Node], angles: &mut Angles) {
     let r: Rotation3<f32> = Rotation3::from_euler_angles(angles.roll, angles.pitch, angles.yaw);
 
     for n in nodes.iter_mut() {
@@ -104,7 +104,7 @@ fn update(_app: &App, model: &mut Model, _update: Update) {
         );
     });
 
-    if changed_axis.iter().any(|angle| angle.get() == true) {
+    if changed_axis.iter().any(|angle| angle.get()) {
         rotate_points(nodes, angles);
     }
 }
@@ -112,7 +112,7 @@ fn update(_app: &App, model: &mut Model, _update: Update) {
 fn view(app: &App, model: &Model, frame: Frame) {
     let draw = app.draw();
     draw.background().color(BLACK);
-    draw_model(&draw, &model);
+    draw_model(&draw, model);
     draw.to_frame(app, &frame).unwrap();
     model.egui.draw_to_frame(&frame).unwrap();
 }
@@ -144,7 +144,6 @@ fn raw_window_event(_app: &App, model: &mut Model, event: &nannou::winit::event:
 
 #[derive(Debug, Deserialize)]
 struct NodeReader {
-    id: usize,
     x: f32,
     y: f32,
     z: f32,
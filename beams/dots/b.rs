@@ -1,21 +1,83 @@
 use csv::Reader;
 use nalgebra::geometry::{Point3, Rotation3};
 use nannou::prelude::*;
-use nannou_egui::{self, egui, Egui};
+use proto::accum::AccumWindow;
+use proto::clock::Clock;
+use proto::offline::Offline;
+use proto::osc::OscListener;
+use proto::paint::Painter;
+use proto::palette::{Palette, Stop};
+use proto::preset::Preset;
+use proto::rng::{capture_name, seed_from_args, seeded_rng};
+use rand::rngs::StdRng;
 use rand::Rng;
-use serde::Deserialize;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
 
 const WINDOW_SIZE: u32 = 1200;
 const SCALE: f32 = 0.3;
 const SPHERE_SIZE: f32 = WINDOW_SIZE as f32 * SCALE;
-const N_BORBS: usize = 150;
-const BORB_SPEED: f32 = 0.02;
-const BREAK_COUNT: usize = 10;
-const PITCH_SPEED: f32 = 0.02;
+const GRAPH_POSITIONS: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/dots/50_node/graph_positions.csv"
+);
+const GRAPH_EDGES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/dots/50_node/graph_edges.csv");
+
+/// What a preset file or OSC can set, `N_BORBS`, `BREAK_COUNT`, `PITCH_SPEED`
+/// and `BORB_SPEED` used to be consts.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+struct Params {
+    n_borbs: usize,
+    break_count: usize,
+    pitch_speed: f32, // radians per second
+    borb_speed: f32,  // edge progress per second
+}
+
+impl Default for Params {
+    fn default() -> Params {
+        Params {
+            n_borbs: 150,
+            break_count: 10,
+            pitch_speed: 1.2,
+            borb_speed: 1.2,
+        }
+    }
+}
+
+impl Params {
+    /// A `break_count` of 0 from a preset or OSC would divide by zero, an edge
+    /// breaks after one hop at the earliest.
+    fn checked(self) -> Params {
+        Params {
+            break_count: self.break_count.max(1),
+            ..self
+        }
+    }
+}
+
+/// Teal edges redden as they wear towards breaking.
+fn hop_wear() -> Palette {
+    Palette::Gradient(vec![
+        Stop {
+            t: 0.0,
+            color: [0.0, 0.5, 0.5],
+        },
+        Stop {
+            t: 0.5,
+            color: [0.5, 0.0, 0.0],
+        },
+        Stop {
+            t: 1.0,
+            color: [1.0, 0.0, 0.0],
+        },
+    ])
+}
 
 struct Node {
     pos: Point3<f32>,
+    last: Point3<f32>,
 }
 
 impl Node {
@@ -35,6 +97,7 @@ struct Edge {
 
 struct Borb {
     pos: Point3<f32>,
+    last: Point3<f32>,
     dest_pos: Point3<f32>,
     src: usize,
     dest: usize,
@@ -43,26 +106,27 @@ struct Borb {
 }
 
 impl Borb {
-    fn spawn_random(nodes: &Vec<Node>, neighbors: &Vec<Vec<usize>>) -> Self {
-        let src = rand::thread_rng().gen_range(0, nodes.len());
+    fn spawn_random(nodes: &[Node], neighbors: &[Vec<usize>], rng: &mut StdRng) -> Self {
+        let src = rng.gen_range(0, nodes.len());
         let options = &neighbors[src];
-        let index: usize = rand::thread_rng().gen_range(0, options.len());
+        let index: usize = rng.gen_range(0, options.len());
         let dest = options[index];
 
         Self {
             pos: nodes[src].pos,
+            last: nodes[src].pos,
             dest_pos: nodes[dest].pos,
-            src: src,
-            dest: dest,
+            src,
+            dest,
             progress: 0.0,
             color: (1.0, 0.1, 0.1),
         }
     }
-    fn hop(&mut self, nodes: &Vec<Node>, options: &Vec<Vec<usize>>) {
-        if options[self.dest].len() > 0 {
+    fn hop(&mut self, nodes: &[Node], options: &[Vec<usize>], rng: &mut StdRng) {
+        if !options[self.dest].is_empty() {
             self.src = self.dest;
             self.pos = self.dest_pos;
-            let index = rand::thread_rng().gen_range(0, options[self.dest].len());
+            let index = rng.gen_range(0, options[self.dest].len());
             let dest = options[self.dest][index];
             self.dest = dest;
             self.dest_pos = nodes[dest].pos;
@@ -70,8 +134,8 @@ impl Borb {
         }
     }
 
-    fn step(&mut self) {
-        self.progress += BORB_SPEED;
+    fn step(&mut self, speed: f32, dt: f32) {
+        self.progress += speed * dt;
         self.pos = self.pos + self.progress * (self.dest_pos - self.pos);
     }
 
@@ -85,7 +149,13 @@ impl Borb {
 }
 
 fn main() {
-    nannou::app(model).update(update).run();
+    match Offline::from_args() {
+        Some(offline) => {
+            let mut model = new_model();
+            offline.run(model.seed, WINDOW_SIZE, &mut model, advance, paint);
+        }
+        None => nannou::app(model).update(update).run(),
+    }
 }
 
 struct Angles {
@@ -95,14 +165,22 @@ struct Angles {
 }
 struct Model {
     nodes: Vec<Node>,
-    edges: HashMap<(usize, usize), Edge>,
+    edges: BTreeMap<(usize, usize), Edge>,
     borbs: Vec<Borb>,
     angles: Angles,
+    seed: u64,
+    rng: StdRng,
     neighbors: Vec<Vec<usize>>,
+    clock: Clock,
+    params: Params,
+    preset: Preset,
+    osc: Option<OscListener>,
+    palette: Palette,                  // edge color by hops over break_count
+    hdr: Option<RefCell<AccumWindow>>, // `--trails` and friends in the window
 }
 
 impl Model {
-    fn new(nodes: Vec<Node>, edges: HashMap<(usize, usize), Edge>) -> Self {
+    fn new(nodes: Vec<Node>, edges: BTreeMap<(usize, usize), Edge>, seed: u64) -> Self {
         let angles = Angles {
             roll: 0.0,
             pitch: 0.0,
@@ -114,9 +192,12 @@ impl Model {
             neighbors[*dest].push(*src);
         }
 
+        let mut preset = Preset::from_args(seed);
+        let params = preset.load_or(Params::default()).checked();
+        let mut rng = seeded_rng(seed);
         let mut borbs: Vec<Borb> = Vec::new();
-        for _ in 0..N_BORBS {
-            borbs.push(Borb::spawn_random(&nodes, &neighbors));
+        for _ in 0..params.n_borbs {
+            borbs.push(Borb::spawn_random(&nodes, &neighbors, &mut rng));
         }
 
         Model {
@@ -125,34 +206,93 @@ impl Model {
             borbs,
             angles,
             neighbors,
+            seed,
+            rng,
+            clock: Clock::default(),
+            hdr: None,
+            params,
+            preset,
+            osc: OscListener::from_args(),
+            palette: Palette::from_args(hop_wear()),
+        }
+    }
+
+    /// Break count (1 at least) and pitch speed apply as is, borbs are added
+    /// or dropped.
+    fn set_params(&mut self, params: Params) {
+        self.borbs.truncate(params.n_borbs);
+        while self.borbs.len() < params.n_borbs {
+            let borb = Borb::spawn_random(&self.nodes, &self.neighbors, &mut self.rng);
+            self.borbs.push(borb);
+        }
+        self.params = params.checked();
+    }
+
+    /// Wears every edge out, they all break and reconnect on the next tick.
+    fn rewire(&mut self) {
+        for edge in self.edges.values_mut() {
+            edge.hop_count = self.params.break_count;
         }
     }
 }
 
 fn model(app: &App) -> Model {
-    let window_id = app
-        .new_window()
+    app.new_window()
         .size(WINDOW_SIZE, WINDOW_SIZE)
         .view(view)
+        .key_released(key_released)
         .build()
         .unwrap();
 
-    let pos_f: String = String::from("beams/graphs/50_node/graph_positions.csv");
-    let edge_f: String = String::from("beams/graphs/50_node/graph_edges.csv");
-    let (nodes, edges) = read_graph(pos_f, edge_f);
+    let mut model = new_model();
+    model.hdr = AccumWindow::from_args(app, WINDOW_SIZE).map(RefCell::new);
+    model
+}
+
+fn new_model() -> Model {
+    let (nodes, edges) = read_graph(GRAPH_POSITIONS, GRAPH_EDGES);
+    Model::new(nodes, edges, seed_from_args())
+}
 
-    let window = app.window(window_id).unwrap();
-    let egui = Egui::from_window(&window);
-    Model::new(nodes, edges)
+fn update(_app: &App, model: &mut Model, update: Update) {
+    if let Some(params) = model.preset.poll() {
+        model.set_params(params);
+    }
+    update_osc(model);
+    advance(model, update.since_last.as_secs_f64());
 }
 
-fn update(_app: &App, model: &mut Model, _update: Update) {
+fn update_osc(model: &mut Model) {
+    let mut params = model.params.clone();
+    let triggers = match &mut model.osc {
+        Some(osc) => osc.poll_params(&mut params),
+        None => return,
+    };
+    model.set_params(params);
+    for trigger in triggers {
+        match trigger.as_str() {
+            "rewire" => model.rewire(),
+            other => eprintln!("osc: no trigger {:?}", other),
+        }
+    }
+}
+
+fn advance(model: &mut Model, seconds: f64) {
+    for _ in 0..model.clock.advance(seconds) {
+        step(model);
+    }
+}
+
+fn step(model: &mut Model) {
+    let dt = model.clock.dt();
     let Model {
         ref mut nodes,
         ref mut edges,
         ref mut borbs,
         ref mut angles,
         ref mut neighbors,
+        ref mut rng,
+        ref params,
         ..
     } = *model;
 
@@ -160,7 +300,7 @@ fn update(_app: &App, model: &mut Model, _update: Update) {
 
     let mut broken_edges: Vec<(usize, usize)> = Vec::new();
     for (key, edge) in edges.iter() {
-        if edge.hop_count >= BREAK_COUNT {
+        if edge.hop_count >= params.break_count {
             broken_edges.push(*key);
         }
     }
@@ -174,7 +314,7 @@ fn update(_app: &App, model: &mut Model, _update: Update) {
             neighbors[dest].remove(pos);
         }
 
-        let new_dest = rand::thread_rng().gen_range(0, num_nodes);
+        let new_dest = rng.gen_range(0, num_nodes);
         let new_edge: Edge = Edge {
             src,
             dest: new_dest,
@@ -183,7 +323,7 @@ fn update(_app: &App, model: &mut Model, _update: Update) {
         };
         edges.insert((src, new_dest), new_edge);
         neighbors[src].push(new_dest);
-        let new_dest = rand::thread_rng().gen_range(0, num_nodes);
+        let new_dest = rng.gen_range(0, num_nodes);
         let new_edge: Edge = Edge {
             src: dest,
             dest: new_dest,
@@ -195,22 +335,27 @@ fn update(_app: &App, model: &mut Model, _update: Update) {
         edges.remove(&(src, dest));
     }
 
-    let r: Rotation3<f32> =
-        Rotation3::from_euler_angles(angles.roll, angles.pitch + PITCH_SPEED, angles.yaw);
+    let r: Rotation3<f32> = Rotation3::from_euler_angles(
+        angles.roll,
+        angles.pitch + params.pitch_speed * dt,
+        angles.yaw,
+    );
 
     for n in nodes.iter_mut() {
+        n.last = n.pos;
         n.pos = r * n.pos;
     }
     for b in borbs.iter_mut() {
+        b.last = b.pos;
         b.pos = r * b.pos;
         b.dest_pos = r * b.dest_pos;
     }
 
     // Step Objects
     for borb in borbs.iter_mut() {
-        borb.step();
+        borb.step(params.borb_speed, dt);
         if borb.progress >= 0.5 {
-            borb.hop(&model.nodes, &model.neighbors);
+            borb.hop(nodes, neighbors, rng);
             if let Some(e) = edges.get_mut(&(borb.src, borb.dest)) {
                 e.hop_count += 1;
             }
@@ -222,40 +367,70 @@ fn update(_app: &App, model: &mut Model, _update: Update) {
 }
 
 fn view(app: &App, model: &Model, frame: Frame) {
-    let draw = app.draw();
-    draw.background().rgba(0.0, 0.0, 0.0, 0.75);
-    draw_model(&draw, &model);
-    draw.to_frame(app, &frame).unwrap();
+    match &model.hdr {
+        Some(hdr) => hdr.borrow_mut().view(app, &frame, |accum| {
+            paint(accum, model, frame.nth(), app.time)
+        }),
+        None => {
+            let mut draw = app.draw();
+            paint(&mut draw, model, frame.nth(), app.time);
+            draw.to_frame(app, &frame).unwrap();
+        }
+    }
+}
+
+fn key_released(app: &App, model: &mut Model, key: Key) {
+    match key {
+        Key::S => {
+            let name = capture_name(&app.exe_name().unwrap(), model.seed, app.elapsed_frames());
+            app.main_window().capture_frame(name);
+        }
+        // write the running parameters to the preset file
+        Key::W => model.preset.save(&model.params),
+        _other_key => {}
+    }
 }
 
-fn draw_model(draw: &Draw, model: &Model) {
+fn paint<P: Painter>(p: &mut P, model: &Model, _nth: u64, _time: f32) {
+    p.background(Rgba::new(0.0, 0.0, 0.0, 0.75));
+    draw_model(p, model);
+}
+
+fn draw_model<P: Painter>(p: &mut P, model: &Model) {
+    let alpha = model.clock.alpha();
     // Drawing edges
     for ((src, dest), e) in model.edges.iter() {
         let n1 = &model.nodes[*src];
         let n2 = &model.nodes[*dest];
 
         let fade: f32 = (n1.fade() + n2.fade()) / 2.0;
-        let rc = e.hop_count as f32 / BREAK_COUNT as f32;
+        let rc = e.hop_count as f32 / model.params.break_count as f32;
         if !e.free {
-            draw.line()
-                .start(vec2(n1.pos.x, n1.pos.y))
-                .end(vec2(n2.pos.x, n2.pos.y))
-                .weight(3.0)
-                .rgba(rc, 0.5 - rc, 0.5 - rc, fade);
+            p.line(
+                lerp_xy(&n1.last, &n1.pos, alpha),
+                lerp_xy(&n2.last, &n2.pos, alpha),
+                3.0,
+                model.palette.rgba(rc, fade),
+            );
         }
     }
 
     for borb in model.borbs.iter() {
-        draw.ellipse()
-            .x_y_z(borb.pos.x, borb.pos.y, borb.pos.z)
-            .radius(borb.size())
-            .rgba(borb.color.0, borb.color.1, borb.color.2, borb.fade());
+        p.ellipse(
+            lerp_xy(&borb.last, &borb.pos, alpha),
+            borb.size(),
+            Rgba::new(borb.color.0, borb.color.1, borb.color.2, borb.fade()),
+        );
     }
 }
 
+/// Screen position `alpha` of the way from `last` to `pos`.
+fn lerp_xy(last: &Point3<f32>, pos: &Point3<f32>, alpha: f32) -> Vec2 {
+    vec2(last.x, last.y).lerp(vec2(pos.x, pos.y), alpha)
+}
+
 #[derive(Debug, Deserialize)]
 struct NodeReader {
-    id: usize,
     x: f32,
     y: f32,
     z: f32,
@@ -267,15 +442,14 @@ struct EdgeReader {
     dest: usize,
 }
 
-fn read_graph(pos_file: String, edge_file: String) -> (Vec<Node>, HashMap<(usize, usize), Edge>) {
+fn read_graph(pos_file: &str, edge_file: &str) -> (Vec<Node>, BTreeMap<(usize, usize), Edge>) {
     let mut nodes: Vec<Node> = Vec::new();
-    let mut edges: HashMap<(usize, usize), Edge> = HashMap::new();
+    let mut edges: BTreeMap<(usize, usize), Edge> = BTreeMap::new();
     let mut rdr = Reader::from_path(pos_file).unwrap();
     for result in rdr.deserialize() {
         let n: NodeReader = result.unwrap();
-        let node: Node = Node {
-            pos: SPHERE_SIZE * Point3::new(n.x, n.y, n.z),
-        };
+        let pos = SPHERE_SIZE * Point3::new(n.x, n.y, n.z);
+        let node: Node = Node { pos, last: pos };
         nodes.push(node);
     }
     let mut rdr = Reader::from_path(edge_file).unwrap();
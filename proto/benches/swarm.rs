@@ -0,0 +1,58 @@
+//! Circles stepped per second by `Swarm::step`, on one thread and on all of
+//! them: `cargo bench --bench swarm`.
+use proto::clock::TICK_RATE;
+use proto::ploom::PloomParams;
+use proto::swarm::Swarm;
+use rayon::ThreadPoolBuilder;
+use std::time::{Duration, Instant};
+
+// how long each size is timed for
+const BUDGET: Duration = Duration::from_secs(2);
+
+/// Mean seconds per tick of `circles` circles on `threads` threads.
+fn time(circles: usize, threads: usize) -> f64 {
+    let params = PloomParams {
+        num_plooms: circles / 3,
+        num_circles: 3,
+        ..PloomParams::default()
+    };
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .unwrap();
+    let mut swarm = Swarm::new(&params, 1);
+    let dt = 1.0 / TICK_RATE;
+    pool.install(|| {
+        for _ in 0..3 {
+            swarm.step(&params, dt); // warm up
+        }
+        let start = Instant::now();
+        let mut ticks = 0;
+        while start.elapsed() < BUDGET {
+            swarm.step(&params, dt);
+            ticks += 1;
+        }
+        start.elapsed().as_secs_f64() / ticks as f64
+    })
+}
+
+fn main() {
+    let threads = rayon::current_num_threads();
+    println!(
+        "{:>9} {:>14} {:>14} {:>9} {:>8}",
+        "circles", "1 thread/s", "parallel/s", "ms/tick", "speedup"
+    );
+    for circles in [10_000, 100_000, 1_000_000] {
+        let serial = time(circles, 1);
+        let parallel = time(circles, threads);
+        println!(
+            "{:>9} {:>14.3e} {:>14.3e} {:>9.3} {:>7.1}x",
+            circles,
+            circles as f64 / serial,
+            circles as f64 / parallel,
+            parallel * 1e3,
+            serial / parallel
+        );
+    }
+    println!("{} threads", threads);
+}
@@ -0,0 +1,35 @@
+//! Sends one OSC message to a sketch on this machine, to try a mapping
+//! without a controller:
+//!
+//! cargo run --example osc_send -- /ploom/decay 0.5
+//! cargo run --example osc_send -- --port 9001 /beams/rewire
+use proto::cli::arg;
+use proto::osc::{Arg, Message, OscSender, PORT};
+use std::env;
+
+fn parse(s: &str) -> Arg {
+    if let Ok(i) = s.parse() {
+        Arg::Int(i)
+    } else if let Ok(f) = s.parse() {
+        Arg::Float(f)
+    } else if let Ok(b) = s.parse() {
+        Arg::Bool(b)
+    } else {
+        Arg::Str(s.to_string())
+    }
+}
+
+fn main() {
+    let port = arg("--port").unwrap_or(PORT);
+    let args: Vec<String> = env::args().skip(1).collect();
+    let start = args
+        .iter()
+        .position(|a| a.starts_with('/'))
+        .expect("usage: osc_send [--port <port>] <address> [values...]");
+    let message = Message::new(
+        &args[start],
+        args[start + 1..].iter().map(|s| parse(s)).collect(),
+    );
+    OscSender::new(port).unwrap().send(&message).unwrap();
+    println!("sent {:?} to port {}", message, port);
+}
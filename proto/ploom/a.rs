@@ -1,5 +1,8 @@
 use nannou::prelude::*;
+use proto::rng::{capture_name, seed_from_args, seeded_rng};
 use rand::distributions::{Distribution, Normal};
+use rand::rngs::StdRng;
+use rand::Rng;
 
 fn main() {
     nannou::app(model).update(update).run();
@@ -7,6 +10,7 @@ fn main() {
 
 struct Model {
     circles: Vec<Circle>,
+    seed: u64,
 }
 
 struct Circle {
@@ -19,15 +23,12 @@ impl Circle {
         Circle { x, r }
     }
 
-    fn random_new() -> Circle {
+    fn random_new(rng: &mut StdRng) -> Circle {
         let s: f32 = 200.0;
         let r_max: f32 = 10.0;
         let normal = Normal::new(0.0, s as f64);
-        let x: Vec2 = vec2(
-            normal.sample(&mut rand::thread_rng()) as f32,
-            normal.sample(&mut rand::thread_rng()) as f32,
-        );
-        Circle::new(x, random_range(0.0, r_max))
+        let x: Vec2 = vec2(normal.sample(rng) as f32, normal.sample(rng) as f32);
+        Circle::new(x, rng.gen_range(0.0, r_max))
     }
 }
 
@@ -39,18 +40,18 @@ fn model(app: &App) -> Model {
         .build()
         .unwrap();
 
+    let seed = seed_from_args();
+    let mut rng = seeded_rng(seed);
     let num_circles: usize = 100;
-    let circles: Vec<Circle> = spawn_random_circles(num_circles);
-    Model { circles }
+    let circles: Vec<Circle> = spawn_random_circles(num_circles, &mut rng);
+    Model { circles, seed }
 }
 
-fn spawn_random_circles(num_circles: usize) -> Vec<Circle> {
-    (0..num_circles).map(|_| Circle::random_new()).collect()
+fn spawn_random_circles(num_circles: usize, rng: &mut StdRng) -> Vec<Circle> {
+    (0..num_circles).map(|_| Circle::random_new(rng)).collect()
 }
 
-fn update(_app: &App, _model: &mut Model, _update: Update) {
-    return;
-}
+fn update(_app: &App, _model: &mut Model, _update: Update) {}
 
 fn view(app: &App, model: &Model, frame: Frame) {
     let draw = app.draw();
@@ -73,11 +74,11 @@ fn view(app: &App, model: &Model, frame: Frame) {
     draw.to_frame(app, &frame).unwrap();
 }
 
-fn key_released(app: &App, _model: &mut Model, key: Key) {
+fn key_released(app: &App, model: &mut Model, key: Key) {
     match key {
         Key::S => {
-            app.main_window()
-                .capture_frame(app.exe_name().unwrap() + ".png");
+            let name = capture_name(&app.exe_name().unwrap(), model.seed, app.elapsed_frames());
+            app.main_window().capture_frame(name);
         }
         _other_key => {}
     }
@@ -1,58 +1,65 @@
 use nannou::prelude::*;
+use proto::clock::{per_tick, Clock};
+use proto::rng::{capture_name, seed_from_args, seeded_rng};
 use rand::distributions::{Distribution, Normal};
+use rand::rngs::StdRng;
+use rand::Rng;
 
 const NUM_CIRCLES: usize = 50;
 const MAX_RADIUS: f32 = 3.0;
 const SCALE: f32 = 100.0;
 const PLOOM: f32 = SCALE / 5.0; // or 4.0
+const DECAY: f32 = 0.3; // fraction of the variance left after a second
 
 fn main() {
     nannou::app(model).update(update).run();
 }
 
-fn rand_vec2(mu: f32, var: f32) -> Vec2 {
+fn rand_vec2(rng: &mut StdRng, mu: f32, var: f32) -> Vec2 {
     let normal = Normal::new(mu as f64, var as f64);
-    vec2(
-        normal.sample(&mut rand::thread_rng()) as f32,
-        normal.sample(&mut rand::thread_rng()) as f32,
-    )
+    vec2(normal.sample(rng) as f32, normal.sample(rng) as f32)
 }
 
 struct Model {
     circles: Vec<Circle>,
     focus: Vec2,
+    seed: u64,
+    rng: StdRng,
+    clock: Clock,
 }
 
 struct Circle {
     x: Vec2,
+    last: Vec2,
     r: f32,
     sigma: f32,
 }
 
 impl Circle {
-    fn random_new() -> Circle {
+    fn random_new(rng: &mut StdRng) -> Circle {
+        let x = rand_vec2(rng, 0.0, SCALE);
         Circle {
-            x: rand_vec2(0.0, SCALE),
-            r: random_range(1.0, MAX_RADIUS),
+            x,
+            last: x,
+            r: rng.gen_range(1.0, MAX_RADIUS),
             sigma: PLOOM,
         }
     }
 
-    fn update(&mut self, focus: Vec2) {
+    fn update(&mut self, focus: Vec2, dt: f32, rng: &mut StdRng) {
+        self.last = self.x;
         self.sigma = if self.sigma > 0.5 {
-            self.sigma * 0.98
+            self.sigma * per_tick(DECAY, dt)
         } else {
             PLOOM
         };
-        let mut delta: Vec2 = rand_vec2(0.0, self.sigma);
-        delta[0] /= self.r;
-        delta[1] /= self.r;
+        let delta: Vec2 = rand_vec2(rng, 0.0, self.sigma) / self.r;
         self.x += delta - (self.x - focus) * 0.01;
     }
 }
 
-fn spawn_random_circles(num_circles: usize) -> Vec<Circle> {
-    (0..num_circles).map(|_| Circle::random_new()).collect()
+fn spawn_random_circles(num_circles: usize, rng: &mut StdRng) -> Vec<Circle> {
+    (0..num_circles).map(|_| Circle::random_new(rng)).collect()
 }
 
 fn model(app: &App) -> Model {
@@ -63,14 +70,24 @@ fn model(app: &App) -> Model {
         .build()
         .unwrap();
 
-    let circles: Vec<Circle> = spawn_random_circles(NUM_CIRCLES);
-    let focus: Vec2 = rand_vec2(0.0, SCALE);
-    Model { circles, focus }
+    let seed = seed_from_args();
+    let mut rng = seeded_rng(seed);
+    let circles: Vec<Circle> = spawn_random_circles(NUM_CIRCLES, &mut rng);
+    let focus: Vec2 = rand_vec2(&mut rng, 0.0, SCALE);
+    Model {
+        circles,
+        focus,
+        seed,
+        rng,
+        clock: Clock::default(),
+    }
 }
 
-fn update(_app: &App, model: &mut Model, _update: Update) {
-    for i in 0..model.circles.len() {
-        model.circles[i].update(model.focus);
+fn update(_app: &App, model: &mut Model, update: Update) {
+    for _ in 0..model.clock.advance(update.since_last.as_secs_f64()) {
+        for circle in &mut model.circles {
+            circle.update(model.focus, model.clock.dt(), &mut model.rng);
+        }
     }
 }
 
@@ -85,10 +102,11 @@ fn view(app: &App, model: &Model, frame: Frame) {
             .rgba(0.18431373, 0.19215686, 0.29019608, 0.02);
     }
 
-    let colors = vec![LIGHTSTEELBLUE, VIOLET];
+    let colors = [LIGHTSTEELBLUE, VIOLET];
+    let alpha = model.clock.alpha();
     for i in 0..model.circles.len() {
         draw.ellipse()
-            .xy(model.circles[i].x)
+            .xy(model.circles[i].last.lerp(model.circles[i].x, alpha))
             .radius(model.circles[i].r)
             .color(colors[i % 2]);
     }
@@ -98,10 +116,10 @@ fn view(app: &App, model: &Model, frame: Frame) {
 
 fn key_released(app: &App, model: &mut Model, key: Key) {
     match key {
-        Key::R => model.focus = rand_vec2(0.0, SCALE),
+        Key::R => model.focus = rand_vec2(&mut model.rng, 0.0, SCALE),
         Key::S => {
-            app.main_window()
-                .capture_frame(app.exe_name().unwrap() + ".png");
+            let name = capture_name(&app.exe_name().unwrap(), model.seed, app.elapsed_frames());
+            app.main_window().capture_frame(name);
         }
         _other_key => {}
     }
@@ -1,150 +1,115 @@
 use nannou::prelude::*;
-use rand::distributions::{Distribution, Normal};
-
-const WINDOW_SIZE: u32 = 1000;
-const NUM_PLOOMS: usize = 200;
-const NUM_CIRCLES: usize = 5;
-const MAX_RADIUS: f32 = 3.0;
-const SCALE: f32 = 100.0;
-
-const DECAY: f32 = 0.99; // variance decay
-const THRESHOLD: f32 = 0.5; // min variance
-
-const PLOOM: f32 = SCALE / 5.0; // or 4.0, or 10
-const DAMPENING: f32 = 0.01;
-const ALPHA: f32 = 0.02;
-
-fn main() {
-    nannou::app(model).update(update).run();
-}
-
-fn rand_normal_vec2(mu: f32, var: f32) -> Vec2 {
-    let normal = Normal::new(mu as f64, var as f64);
-    vec2(
-        normal.sample(&mut rand::thread_rng()) as f32,
-        normal.sample(&mut rand::thread_rng()) as f32,
-    )
-}
-
-fn rand_uniform_vec2() -> Vec2 {
-    let bound = WINDOW_SIZE as f32 / 2.0;
-    vec2(random_range(-bound, bound), random_range(-bound, bound))
-}
-
-struct Circle {
-    x: Vec2,
-    r: f32,
-}
-
-struct Ploom {
-    circles: Vec<Circle>,
-    focus: Vec2,
-    sigma: f32,
-}
-
-impl Ploom {
-    fn new() -> Ploom {
-        Ploom {
-            circles: Circle::spawn_random_circles(NUM_CIRCLES),
-            focus: rand_uniform_vec2(),
-            sigma: PLOOM,
-        }
-    }
-
-    fn spawn_random_plooms(n: usize) -> Vec<Ploom> {
-        (0..n).map(|_| Ploom::new()).collect()
-    }
-
-    fn update(&mut self) -> bool {
-        if self.sigma > THRESHOLD {
-            self.sigma *= DECAY;
-            false
-        } else {
-            self.focus = rand_uniform_vec2();
-            self.sigma = PLOOM;
-            true
-        }
+use proto::burst::Bursts;
+use proto::clock::Clock;
+use proto::event::{Lifecycle, PloomEvent};
+use proto::ploom::{Ploom, PloomParams};
+use proto::rng::{capture_name, seed_from_args, seeded_rng};
+use rand::rngs::StdRng;
+
+fn params() -> PloomParams {
+    PloomParams {
+        num_plooms: 200,
+        num_circles: 5,
+        decay: 0.55,    // fraction of the variance left after a second
+        threshold: 3.9, // min variance
+        ploom: 155.0,
+        dampening: 0.45,
+        alpha: 0.02,
+        ..PloomParams::default()
     }
 }
 
-impl Circle {
-    fn new() -> Circle {
-        Circle {
-            x: rand_normal_vec2(0.0, PLOOM),
-            r: random_range(1.0, MAX_RADIUS),
-        }
-    }
-
-    fn spawn_random_circles(n: usize) -> Vec<Circle> {
-        (0..n).map(|_| Circle::new()).collect()
-    }
-
-    fn update(&mut self, focus: Vec2, sigma: f32) {
-        let mut dx: Vec2 = rand_normal_vec2(0.0, sigma);
-        dx[0] /= self.r;
-        dx[1] /= self.r;
-        self.x += dx - (self.x - focus) * DAMPENING;
-    }
+fn main() {
+    nannou::app(model).update(update).run();
 }
 
 struct Model {
+    params: PloomParams,
     plooms: Vec<Ploom>,
-    event_bool: bool,
+    bursts: Bursts,
+    seed: u64,
+    rng: StdRng,
+    clock: Clock,
 }
 
 fn model(app: &App) -> Model {
+    let params = params();
     app.new_window()
-        .size(WINDOW_SIZE, WINDOW_SIZE)
+        .size(params.window_size, params.window_size)
         .view(view)
         .key_released(key_released)
         .build()
         .unwrap();
 
+    let seed = seed_from_args();
+    let mut rng = seeded_rng(seed);
+    let mut plooms = Ploom::spawn_random_plooms(params.num_plooms, &params, &mut rng);
+    for ploom in plooms.iter_mut() {
+        ploom.speed = 0.0; // foci stay put until their ploom resets
+    }
     Model {
-        plooms: Ploom::spawn_random_plooms(NUM_PLOOMS),
-        event_bool: true,
+        params,
+        plooms,
+        bursts: Bursts::default(),
+        seed,
+        rng,
+        clock: Clock::default(),
     }
 }
 
-fn update(_app: &App, model: &mut Model, _update: Update) {
-    for i in 0..NUM_PLOOMS {
-        model.event_bool = model.plooms[i].update();
-        let focus = model.plooms[i].focus;
-        let sigma = model.plooms[i].sigma;
-        for j in 0..NUM_CIRCLES {
-            model.plooms[i].circles[j].update(focus, sigma)
+fn update(_app: &App, model: &mut Model, update: Update) {
+    for _ in 0..model.clock.advance(update.since_last.as_secs_f64()) {
+        let dt = model.clock.dt();
+        model.bursts.update(dt);
+        for (i, ploom) in model.plooms.iter_mut().enumerate() {
+            let at = ploom.focus;
+            if ploom.step(&model.params, dt, &mut model.rng) {
+                model.bursts.on_event(&PloomEvent {
+                    kind: Lifecycle::PloomReset,
+                    ploom: i,
+                    at,
+                });
+            }
         }
     }
 }
 
 fn view(app: &App, model: &Model, frame: Frame) {
-    let draw = app.draw();
+    let mut draw = app.draw();
 
-    if model.event_bool {
+    if frame.nth() == 0 {
         draw.background().color(WHITE);
     } else {
-        draw.rect()
-            .wh(app.window_rect().wh())
-            .rgba(0.18431373, 0.19215686, 0.29019608, ALPHA);
+        draw.rect().wh(app.window_rect().wh()).rgba(
+            0.18431373,
+            0.19215686,
+            0.29019608,
+            model.params.alpha,
+        );
     }
 
-    let colors = vec![LIGHTSTEELBLUE, VIOLET];
+    let colors = [LIGHTSTEELBLUE, VIOLET];
+    let alpha = model.clock.alpha();
     for ploom in &model.plooms {
-        for i in 0..ploom.circles.len() {
+        for (i, circle) in ploom.circles.iter().enumerate() {
             draw.ellipse()
-                .xy(ploom.circles[i].x)
-                .radius(ploom.circles[i].r)
+                .xy(circle.lerp(alpha))
+                .radius(circle.r)
                 .color(colors[i % 2]);
         }
     }
+    let color = Rgba::new(0.93333333, 0.50980392, 0.93333333, 0.6);
+    model
+        .bursts
+        .paint(&mut draw, color, alpha, model.clock.dt());
     draw.to_frame(app, &frame).unwrap();
 }
 
-fn key_released(app: &App, _model: &mut Model, key: Key) {
+fn key_released(app: &App, model: &mut Model, key: Key) {
     match key {
         Key::S => {
-            app.main_window()
-                .capture_frame(app.exe_name().unwrap() + ".png");
+            let name = capture_name(&app.exe_name().unwrap(), model.seed, app.elapsed_frames());
+            app.main_window().capture_frame(name);
         }
         _other_key => {}
     }
@@ -0,0 +1,202 @@
+use nannou::prelude::*;
+use nannou::winit::event::MouseScrollDelta;
+use proto::accum::AccumWindow;
+use proto::camera::Camera;
+use proto::clock::Clock;
+use proto::offline::Offline;
+use proto::paint::Painter;
+use proto::palette::Palette;
+use proto::ploom::PloomParams;
+use proto::ploom3::{noise3_from_args, Ploom3};
+use proto::preset::Preset;
+use proto::rng::{capture_name, seed_from_args, seeded_rng};
+use rand::rngs::StdRng;
+use std::cell::RefCell;
+
+// px a scrolled pixel counts as a line
+const PIXELS_PER_LINE: f32 = 50.0;
+
+fn params() -> PloomParams {
+    PloomParams {
+        num_plooms: 300,
+        num_circles: 5,
+        decay: 0.55, // per second
+        threshold: 3.9,
+        ploom: 155.0,
+        dampening: 0.45,
+        alpha: 0.05,
+        ..PloomParams::default()
+    }
+}
+
+fn main() {
+    match Offline::from_args() {
+        Some(offline) => {
+            let mut model = Model::new(seed_from_args());
+            let size = model.params.window_size;
+            offline.run(model.seed, size, &mut model, advance, paint);
+        }
+        None => nannou::app(model).update(update).run(),
+    }
+}
+
+struct Model {
+    params: PloomParams,
+    plooms: Vec<Ploom3>,
+    camera: Camera,
+    seed: u64,
+    rng: StdRng,
+    clock: Clock,
+    preset: Preset,
+    palette: Palette,
+    hdr: Option<RefCell<AccumWindow>>, // `--trails` and friends in the window
+}
+
+impl Model {
+    fn new(seed: u64) -> Model {
+        let mut preset = Preset::from_args(seed);
+        let params = preset.load_or(params()).with_args();
+        let mut rng = seeded_rng(seed);
+        let mut plooms = Ploom3::spawn_random_plooms(params.num_plooms, &params, &mut rng);
+        noise3_from_args(&mut plooms);
+        Model {
+            camera: Camera::from_args(params.window_size),
+            plooms,
+            params,
+            seed,
+            rng,
+            clock: Clock::default(),
+            preset,
+            palette: Palette::from_args(Palette::swatch(&[
+                Rgba::new(0.69019608, 0.76862745, 0.87058824, 1.0),
+                Rgba::new(0.93333333, 0.50980392, 0.93333333, 1.0),
+            ])),
+            hdr: None,
+        }
+    }
+}
+
+fn model(app: &App) -> Model {
+    let mut model = Model::new(seed_from_args());
+    app.new_window()
+        .size(model.params.window_size, model.params.window_size)
+        .view(view)
+        .key_released(key_released)
+        .mouse_pressed(mouse_pressed)
+        .mouse_moved(mouse_moved)
+        .mouse_released(mouse_released)
+        .mouse_wheel(mouse_wheel)
+        .build()
+        .unwrap();
+    model.hdr = AccumWindow::from_args(app, model.params.window_size).map(RefCell::new);
+    model
+}
+
+fn update(_app: &App, model: &mut Model, update: Update) {
+    if let Some(params) = model.preset.poll() {
+        // the window stays, the flags still win over the file
+        model.params = PloomParams {
+            window_size: model.params.window_size,
+            ..params
+        }
+        .with_args();
+        Ploom3::resize_plooms(&mut model.plooms, &model.params, &mut model.rng);
+    }
+    advance(model, update.since_last.as_secs_f64());
+}
+
+fn advance(model: &mut Model, seconds: f64) {
+    for _ in 0..model.clock.advance(seconds) {
+        let dt = model.clock.dt();
+        model.camera.update(dt);
+        for ploom in model.plooms.iter_mut() {
+            ploom.step(&model.params, dt, &mut model.rng);
+        }
+    }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+    match &model.hdr {
+        Some(hdr) => hdr.borrow_mut().view(app, &frame, |accum| {
+            paint(accum, model, frame.nth(), app.time)
+        }),
+        None => {
+            let mut draw = app.draw();
+            paint(&mut draw, model, frame.nth(), app.time);
+            draw.to_frame(app, &frame).unwrap();
+        }
+    }
+}
+
+fn paint<P: Painter>(p: &mut P, model: &Model, nth: u64, _time: f32) {
+    let size = model.params.window_size as f32;
+    if nth == 0 {
+        p.background(Rgba::new(0.18431373, 0.19215686, 0.29019608, 1.0));
+    } else {
+        p.fade(
+            vec2(size, size),
+            Rgba::new(0.18431373, 0.19215686, 0.29019608, model.params.alpha),
+        );
+    }
+    draw_cloud(p, model);
+}
+
+/// Every circle back to front, sized by its distance and faded by its depth.
+fn draw_cloud<P: Painter>(p: &mut P, model: &Model) {
+    let alpha = model.clock.alpha();
+    let camera = &model.camera;
+    let mut circles = Vec::new();
+    for ploom in &model.plooms {
+        let n = ploom.circles.len() as f32;
+        for (c, circle) in ploom.circles.iter().enumerate() {
+            if let Some(at) = camera.project(circle.lerp(alpha)) {
+                circles.push((at, circle.r, c as f32 / n));
+            }
+        }
+    }
+    circles.sort_by(|a, b| b.0.depth.total_cmp(&a.0.depth));
+    for (at, r, t) in circles {
+        let fade = camera.fade(at.depth);
+        p.ellipse(at.xy, r * at.scale, model.palette.rgba(t, 0.8 * fade));
+    }
+}
+
+fn key_released(app: &App, model: &mut Model, key: Key) {
+    match key {
+        Key::S => {
+            let name = capture_name(&app.exe_name().unwrap(), model.seed, app.elapsed_frames());
+            app.main_window().capture_frame(name);
+        }
+        // write the running parameters to the preset file
+        Key::W => model.preset.save(&model.params),
+        // perspective, orthographic
+        Key::P => model.camera.projection = model.camera.projection.next(),
+        // back to the starting view
+        Key::R => model.camera.reset(),
+        _other_key => {}
+    }
+}
+
+fn mouse_pressed(app: &App, model: &mut Model, button: MouseButton) {
+    if button == MouseButton::Left {
+        model.camera.press(app.mouse.position());
+    }
+}
+
+fn mouse_moved(_app: &App, model: &mut Model, at: Point2) {
+    model.camera.drag_to(at);
+}
+
+fn mouse_released(_app: &App, model: &mut Model, button: MouseButton) {
+    if button == MouseButton::Left {
+        model.camera.release();
+    }
+}
+
+fn mouse_wheel(_app: &App, model: &mut Model, delta: MouseScrollDelta, _phase: TouchPhase) {
+    let lines = match delta {
+        MouseScrollDelta::LineDelta(_, y) => y,
+        MouseScrollDelta::PixelDelta(d) => d.y as f32 / PIXELS_PER_LINE,
+    };
+    model.camera.zoom(lines);
+}
@@ -0,0 +1,271 @@
+use crate::canvas::{linear_to_srgb, Blend, Canvas};
+use crate::cli::arg;
+use crate::clock::TICK_RATE;
+use crate::paint::Painter;
+use nannou::color::IntoLinSrgba;
+use nannou::image::{self, RgbImage};
+use nannou::prelude::*;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+// linear value `ToneMap::Log` maps to white
+const LOG_WHITE: f32 = 16.0;
+
+/// How accumulated light, which can go past 1.0, becomes a displayable color.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ToneMap {
+    /// Everything past 1.0 is white, what the 8-bit targets do.
+    #[default]
+    Clamp,
+    /// `c / (1 + c)`, soft and never quite white.
+    Reinhard,
+    /// Narkowicz's fit of the ACES filmic curve, more contrast than Reinhard.
+    Aces,
+    /// `log(1 + c)`, `LOG_WHITE` and above are white, keeps faint trails visible.
+    Log,
+}
+
+impl ToneMap {
+    pub fn apply(self, c: f32) -> f32 {
+        let c = c.max(0.0);
+        match self {
+            ToneMap::Clamp => c,
+            ToneMap::Reinhard => c / (1.0 + c),
+            ToneMap::Aces => (c * (2.51 * c + 0.03)) / (c * (2.43 * c + 0.59) + 0.14),
+            ToneMap::Log => (1.0 + c).ln() / (1.0 + LOG_WHITE).ln(),
+        }
+    }
+}
+
+/// How an `Accum` keeps, blends and shows what was painted.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Trails {
+    /// Fraction of the light left after a second. `None` keeps the sketch's
+    /// own fade, made exact and frame rate independent.
+    pub decay: Option<f32>,
+    pub blend: Blend,
+    pub tone: ToneMap,
+    pub exposure: f32, // factor on the light before tone mapping
+}
+
+impl Trails {
+    /// `None` unless one of `--trails <decay per second>`, `--blend alpha|add`,
+    /// `--tone clamp|reinhard|aces|log` or `--exposure <factor>` was passed.
+    pub fn from_args() -> Option<Trails> {
+        let decay = arg("--trails");
+        let blend = arg("--blend");
+        let tone = arg("--tone");
+        let exposure = arg("--exposure");
+        if decay.is_none() && blend.is_none() && tone.is_none() && exposure.is_none() {
+            return None;
+        }
+        let default = Trails::default();
+        Some(Trails {
+            decay,
+            blend: blend.unwrap_or(default.blend),
+            tone: tone.unwrap_or(default.tone),
+            exposure: exposure.unwrap_or(default.exposure),
+        })
+    }
+}
+
+impl Default for Trails {
+    fn default() -> Trails {
+        Trails {
+            decay: None,
+            blend: Blend::Alpha,
+            tone: ToneMap::Clamp,
+            exposure: 1.0,
+        }
+    }
+}
+
+/// Floating point accumulation buffer. Trails decay exponentially towards
+/// the background instead of under translucent rects, so they reach it
+/// exactly rather than leaving 8-bit residue, and tone mapping happens once
+/// on the way out.
+pub struct Accum {
+    pub canvas: Canvas,
+    pub trails: Trails,
+    floor: [f32; 3], // what trails decay towards, the last background or fade color
+    dt: f32,         // seconds since the last frame
+    frames: u64,
+}
+
+impl Accum {
+    pub fn new(width: u32, height: u32, trails: Trails) -> Accum {
+        let mut canvas = Canvas::new(width, height);
+        canvas.blend = trails.blend;
+        Accum {
+            canvas,
+            trails,
+            floor: [0.0; 3],
+            dt: 1.0 / TICK_RATE,
+            frames: 0,
+        }
+    }
+
+    /// Starts a frame `dt` seconds after the last one, decaying the buffer
+    /// when `trails.decay` is set.
+    pub fn begin(&mut self, dt: f32) {
+        self.dt = dt;
+        if let Some(decay) = self.trails.decay {
+            if self.frames > 0 {
+                self.decay(decay.powf(dt));
+            }
+        }
+        self.frames += 1;
+    }
+
+    /// Moves every pixel towards `floor`, keeping `keep` of the difference.
+    fn decay(&mut self, keep: f32) {
+        let floor = self.floor;
+        for pixel in &mut self.canvas.pixels {
+            for (p, f) in pixel.iter_mut().zip(floor) {
+                *p = f + (*p - f) * keep;
+            }
+        }
+    }
+
+    /// Exposed, tone mapped and sRGB encoded, one `[r, g, b]` per pixel.
+    fn encoded(&self) -> impl Iterator<Item = [u8; 3]> + '_ {
+        let Trails { tone, exposure, .. } = self.trails;
+        self.canvas.pixels.iter().map(move |pixel| {
+            pixel.map(|c| (linear_to_srgb(tone.apply(c * exposure)) * 255.0).round() as u8)
+        })
+    }
+
+    pub fn to_image(&self) -> RgbImage {
+        let bytes = self.encoded().flatten().collect();
+        RgbImage::from_raw(self.canvas.width, self.canvas.height, bytes).unwrap()
+    }
+
+    /// RGBA bytes for an `Rgba8UnormSrgb` texture.
+    pub fn to_rgba(&self) -> Vec<u8> {
+        self.encoded()
+            .flat_map(|[r, g, b]| [r, g, b, 255])
+            .collect()
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> image::ImageResult<()> {
+        self.to_image().save(path)
+    }
+}
+
+impl Painter for Accum {
+    /// Clears, or with an explicit `decay` only sets what the trails decay
+    /// towards after the first frame.
+    fn background<C: IntoLinSrgba<f32>>(&mut self, color: C) {
+        let color = color.into_lin_srgba();
+        self.floor = [color.red, color.green, color.blue];
+        if self.trails.decay.is_none() || self.frames <= 1 {
+            self.canvas.background(color);
+        }
+    }
+
+    fn rect<C: IntoLinSrgba<f32>>(&mut self, xy: Vec2, wh: Vec2, color: C) {
+        self.canvas.rect(xy, wh, color);
+    }
+
+    fn ellipse<C: IntoLinSrgba<f32>>(&mut self, xy: Vec2, radius: f32, color: C) {
+        self.canvas.ellipse(xy, radius, color);
+    }
+
+    fn line<C: IntoLinSrgba<f32>>(&mut self, start: Vec2, end: Vec2, weight: f32, color: C) {
+        self.canvas.line(start, end, weight, color);
+    }
+
+    fn quad<C: IntoLinSrgba<f32>>(&mut self, points: [Vec2; 4], color: C) {
+        self.canvas.quad(points, color);
+    }
+
+    /// The sketch's per-frame `alpha` as an exact per-second decay towards
+    /// `color`, assuming it was tuned at `TICK_RATE` frames per second.
+    fn fade<C: IntoLinSrgba<f32>>(&mut self, _wh: Vec2, color: C) {
+        let color = color.into_lin_srgba();
+        self.floor = [color.red, color.green, color.blue];
+        if self.trails.decay.is_none() {
+            let keep = (1.0 - color.alpha.clamp(0.0, 1.0)).powf(TICK_RATE * self.dt);
+            self.decay(keep);
+        }
+    }
+}
+
+/// An `Accum` shown in a window through a texture.
+pub struct AccumWindow {
+    pub accum: Accum,
+    texture: wgpu::Texture,
+    last: Option<f32>, // app time of the last frame
+}
+
+impl AccumWindow {
+    /// A `size` square buffer for the main window when `Trails::from_args`
+    /// asks for one.
+    pub fn from_args(app: &App, size: u32) -> Option<AccumWindow> {
+        Trails::from_args().map(|trails| AccumWindow::new(app, size, trails))
+    }
+
+    pub fn new(app: &App, size: u32, trails: Trails) -> AccumWindow {
+        let window = app.main_window();
+        let texture = wgpu::TextureBuilder::new()
+            .size([size, size])
+            .format(wgpu::TextureFormat::Rgba8UnormSrgb)
+            .usage(wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::TEXTURE_BINDING)
+            .build(window.device());
+        AccumWindow {
+            accum: Accum::new(size, size, trails),
+            texture,
+            last: None,
+        }
+    }
+
+    /// Paints a frame into the buffer with `paint` and draws the tone mapped
+    /// result over the whole window.
+    pub fn view<F>(&mut self, app: &App, frame: &Frame, paint: F)
+    where
+        F: FnOnce(&mut Accum),
+    {
+        let dt = self.last.map_or(1.0 / TICK_RATE, |last| app.time - last);
+        self.last = Some(app.time);
+        self.accum.begin(dt);
+        paint(&mut self.accum);
+
+        let window = app.main_window();
+        let mut encoder = frame.command_encoder();
+        self.texture
+            .upload_data(window.device(), &mut encoder, &self.accum.to_rgba());
+        drop(encoder);
+        let draw = app.draw();
+        draw.texture(&self.texture);
+        draw.to_frame(app, frame).unwrap();
+    }
+}
+
+impl fmt::Display for ToneMap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ToneMap::Clamp => write!(f, "clamp"),
+            ToneMap::Reinhard => write!(f, "reinhard"),
+            ToneMap::Aces => write!(f, "aces"),
+            ToneMap::Log => write!(f, "log"),
+        }
+    }
+}
+
+impl FromStr for ToneMap {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<ToneMap, String> {
+        match s {
+            "clamp" => Ok(ToneMap::Clamp),
+            "reinhard" => Ok(ToneMap::Reinhard),
+            "aces" => Ok(ToneMap::Aces),
+            "log" => Ok(ToneMap::Log),
+            _ => Err(format!(
+                "expected clamp, reinhard, aces or log, got {:?}",
+                s
+            )),
+        }
+    }
+}
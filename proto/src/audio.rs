@@ -0,0 +1,230 @@
+use crate::cli::arg;
+use crate::clock::TICK_RATE;
+use crate::offline::fps_from_args;
+use crate::ploom::{Ploom, PloomParams};
+use hound::{SampleFormat, WavReader};
+use rustfft::num_complex::Complex;
+use rustfft::FftPlanner;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+use std::path::Path;
+
+/// Samples per FFT window, ~46ms at 44.1kHz.
+pub const FFT_SIZE: usize = 2048;
+
+/// Edges of the FFT bands in Hz: bass, low mids, high mids, treble.
+pub const BAND_EDGES: [f32; BANDS + 1] = [20.0, 150.0, 600.0, 2500.0, 12000.0];
+pub const BANDS: usize = 4;
+
+/// A WAV file mixed down to mono, samples in `[-1, 1]`.
+pub struct Track {
+    pub rate: u32, // samples per second
+    pub samples: Vec<f32>,
+}
+
+impl Track {
+    pub fn load(path: &Path) -> Result<Track, String> {
+        let reader = WavReader::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let spec = reader.spec();
+        let interleaved: Vec<f32> = match spec.sample_format {
+            SampleFormat::Float => reader.into_samples::<f32>().collect::<Result<_, _>>(),
+            SampleFormat::Int => {
+                let scale = (1i64 << (spec.bits_per_sample - 1)) as f32;
+                reader
+                    .into_samples::<i32>()
+                    .map(|s| s.map(|s| s as f32 / scale))
+                    .collect::<Result<_, _>>()
+            }
+        }
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+        let channels = spec.channels.max(1) as usize;
+        let samples = interleaved
+            .chunks(channels)
+            .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+            .collect();
+        Ok(Track {
+            rate: spec.sample_rate,
+            samples,
+        })
+    }
+
+    pub fn seconds(&self) -> f32 {
+        self.samples.len() as f32 / self.rate as f32
+    }
+
+    /// Samples `[start, end)` of frame `nth` at `fps` frames per second.
+    pub fn span(&self, nth: usize, fps: f32) -> (usize, usize) {
+        let at = |n: usize| {
+            ((n as f64 * self.rate as f64 / fps as f64).round() as usize).min(self.samples.len())
+        };
+        (at(nth), at(nth + 1))
+    }
+}
+
+/// What a frame of audio sounds like, every value in `[0, 1]` relative to
+/// the loudest frame of the track.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Features {
+    pub rms: f32,
+    pub bands: [f32; BANDS],
+}
+
+/// Features for every frame of a track at a fixed frame rate, so frame `n`
+/// of an offline render at `fps` gets the audio from `n / fps` seconds on.
+pub struct Analysis {
+    pub fps: f32,
+    pub frames: Vec<Features>,
+}
+
+impl Analysis {
+    pub fn new(track: &Track, fps: f32) -> Analysis {
+        let n = (track.seconds() * fps).ceil() as usize;
+        let fft = FftPlanner::new().plan_fft_forward(FFT_SIZE);
+        let hann: Vec<f32> = (0..FFT_SIZE)
+            .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / FFT_SIZE as f32).cos())
+            .collect();
+        let hz = track.rate as f32 / FFT_SIZE as f32; // per bin
+        let mut frames: Vec<Features> = (0..n)
+            .map(|nth| {
+                let (start, end) = track.span(nth, fps);
+                let span = &track.samples[start..end];
+                let rms =
+                    (span.iter().map(|s| s * s).sum::<f32>() / span.len().max(1) as f32).sqrt();
+
+                // window centered on the frame, zero past either end of the track
+                let center = (start + end) / 2;
+                let mut buffer: Vec<Complex<f32>> = (0..FFT_SIZE)
+                    .map(|i| {
+                        let s = (center + i)
+                            .checked_sub(FFT_SIZE / 2)
+                            .and_then(|k| track.samples.get(k))
+                            .copied()
+                            .unwrap_or(0.0);
+                        Complex::new(s * hann[i], 0.0)
+                    })
+                    .collect();
+                fft.process(&mut buffer);
+                let mut bands = [0.0; BANDS];
+                for (b, band) in bands.iter_mut().enumerate() {
+                    let lo = (BAND_EDGES[b] / hz).ceil() as usize;
+                    let hi = ((BAND_EDGES[b + 1] / hz).ceil() as usize).min(FFT_SIZE / 2);
+                    let power: f32 = buffer[lo.min(hi)..hi].iter().map(|x| x.norm_sqr()).sum();
+                    *band = power.sqrt();
+                }
+                Features { rms, bands }
+            })
+            .collect();
+
+        // loudest frame is 1 for every feature
+        let mut max = Features::default();
+        for f in &frames {
+            max.rms = max.rms.max(f.rms);
+            for b in 0..BANDS {
+                max.bands[b] = max.bands[b].max(f.bands[b]);
+            }
+        }
+        let norm = |x: f32, max: f32| if max > 0.0 { x / max } else { 0.0 };
+        for f in &mut frames {
+            f.rms = norm(f.rms, max.rms);
+            for b in 0..BANDS {
+                f.bands[b] = norm(f.bands[b], max.bands[b]);
+            }
+        }
+        Analysis { fps, frames }
+    }
+
+    /// Features of frame `nth`, silence past the end of the track.
+    pub fn frame(&self, nth: usize) -> Features {
+        self.frames.get(nth).copied().unwrap_or_default()
+    }
+}
+
+/// How strongly the audio drives a ploom sketch, 0 switches a mapping off.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioMap {
+    pub kick: f32,   // bass onsets push sigma up by `kick * ploom`
+    pub speed: f32,  // loudness speeds the foci up to `1 + speed` times
+    pub radius: f32, // low mids grow circles up to `1 + radius` times
+    pub alpha: f32,  // treble brightens lines up to `1 + alpha` times
+}
+
+impl Default for AudioMap {
+    fn default() -> AudioMap {
+        AudioMap {
+            kick: 1.0,
+            speed: 2.0,
+            radius: 1.5,
+            alpha: 1.0,
+        }
+    }
+}
+
+/// A track played through a sketch tick by tick. Each tick takes the
+/// features of the frame it starts in, so offline renders at `--fps` line
+/// up with the audio frame for frame.
+pub struct Audio {
+    pub analysis: Analysis,
+    pub now: Features,
+    last: Features,
+    ticks: u64,
+    frame: Option<usize>,
+}
+
+impl Audio {
+    pub fn new(analysis: Analysis) -> Audio {
+        Audio {
+            analysis,
+            now: Features::default(),
+            last: Features::default(),
+            ticks: 0,
+            frame: None,
+        }
+    }
+
+    /// `--audio <wav>`, analysed at `--fps` (the tick rate in the window).
+    pub fn from_args() -> Option<Audio> {
+        let path: String = arg("--audio")?;
+        let track = Track::load(Path::new(&path)).unwrap_or_else(|e| panic!("{}", e));
+        let fps = fps_from_args().unwrap_or(TICK_RATE);
+        Some(Audio::new(Analysis::new(&track, fps)))
+    }
+
+    /// Moves on by a tick of `dt` seconds, returns the frame it falls in.
+    pub fn tick(&mut self, dt: f32) -> usize {
+        let rate = (1.0 / dt as f64).round(); // ticks per second
+        let nth = (self.ticks as f64 * self.analysis.fps as f64 / rate + 1e-9).floor() as usize;
+        self.ticks += 1;
+        if self.frame != Some(nth) {
+            if self.frame.is_none() {
+                self.last = self.analysis.frame(nth);
+            }
+            self.now = self.analysis.frame(nth);
+            self.frame = Some(nth);
+        }
+        nth
+    }
+
+    /// Kicks `sigma` when the bass comes in and sets the focus speed, call
+    /// after `tick` and before stepping the plooms.
+    pub fn modulate(&mut self, plooms: &mut [Ploom], params: &PloomParams) {
+        let map = &params.audio;
+        let onset = (self.now.bands[0] - self.last.bands[0]).max(0.0);
+        // a frame's onset kicks once, whatever the ticks per frame
+        self.last = self.now;
+        for ploom in plooms.iter_mut() {
+            ploom.sigma += map.kick * onset * params.ploom;
+            ploom.speed = 1.0 + map.speed * self.now.rms;
+        }
+    }
+
+    /// Factor on circle radii.
+    pub fn radius(&self, map: &AudioMap) -> f32 {
+        1.0 + map.radius * self.now.bands[1]
+    }
+
+    /// Factor on line alpha.
+    pub fn alpha(&self, map: &AudioMap) -> f32 {
+        1.0 + map.alpha * self.now.bands[3]
+    }
+}
@@ -0,0 +1,186 @@
+use crate::event::Lifecycle;
+use crate::focus::FocusPath;
+use crate::ploom::{rand_normal_vec2, rand_uniform_vec2, Ploom, PloomParams};
+use nannou::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+// `attract` without a rate, radians per second
+const ATTRACT: f32 = 0.5;
+
+/// What keeps foci and circles on the canvas. The window rect is
+/// `±window_size / 2` on both axes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Boundary {
+    /// Nothing, foci drift off until their ploom resets.
+    #[default]
+    Open,
+    /// Toroidal, a focus leaving one side comes in on the other with its
+    /// plume in tow.
+    Wrap,
+    /// Elastic, foci and circles reflect off the window rect.
+    Bounce,
+    /// Soft central potential of angular frequency `k` (radians per second):
+    /// drifting foci orbit the center, random walks are pulled towards it.
+    Attract(f32),
+    /// A focus leaving the rect resets its ploom somewhere else, a circle
+    /// leaving it is put back into its plume.
+    Respawn,
+}
+
+impl Boundary {
+    /// Applies the policy to a focus that just moved, returns true when the
+    /// ploom respawned.
+    pub fn focus<R: Rng + ?Sized>(
+        &self,
+        ploom: &mut Ploom,
+        params: &PloomParams,
+        dt: f32,
+        rng: &mut R,
+    ) -> bool {
+        let half = params.window_size as f32 / 2.0;
+        match self {
+            Boundary::Open => {}
+            Boundary::Wrap => {
+                let offset = wrap(ploom.focus, half) - ploom.focus;
+                if offset != Vec2::ZERO {
+                    translate(ploom, offset);
+                    ploom.emit(Lifecycle::FocusSpawn);
+                }
+            }
+            Boundary::Bounce => {
+                let (focus, flip) = reflect(ploom.focus, half);
+                ploom.focus = focus;
+                ploom.v *= flip;
+            }
+            Boundary::Attract(k) => match ploom.path {
+                FocusPath::Drift => ploom.v -= ploom.focus * (k * k * dt),
+                FocusPath::RandomWalk { .. } => ploom.focus *= 1.0 - k * dt,
+                // scripted paths are laid out on the canvas already
+                _ => {}
+            },
+            Boundary::Respawn => {
+                if !inside(ploom.focus, half) {
+                    ploom.emit(Lifecycle::PloomReset);
+                    let offset = rand_uniform_vec2(rng, params.window_size) - ploom.focus;
+                    translate(ploom, offset);
+                    ploom.sigma = params.ploom;
+                    ploom.emit(Lifecycle::FocusSpawn);
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// Applies the policy to circles that just moved.
+    pub fn circles<R: Rng + ?Sized>(&self, ploom: &mut Ploom, params: &PloomParams, rng: &mut R) {
+        let half = params.window_size as f32 / 2.0;
+        match self {
+            // circles follow their focus
+            Boundary::Open | Boundary::Wrap | Boundary::Attract(_) => {}
+            Boundary::Bounce => {
+                for circle in &mut ploom.circles {
+                    circle.x = reflect(circle.x, half).0;
+                }
+            }
+            Boundary::Respawn => {
+                for circle in &mut ploom.circles {
+                    if !inside(circle.x, half) {
+                        let x = ploom.focus + rand_normal_vec2(rng, 0.0, ploom.sigma);
+                        circle.x = reflect(x, half).0;
+                        circle.last = circle.x;
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn inside(p: Vec2, half: f32) -> bool {
+    p.x.abs() <= half && p.y.abs() <= half
+}
+
+/// `p` folded into `[-half, half)` on both axes.
+pub fn wrap(p: Vec2, half: f32) -> Vec2 {
+    let fold = |x: f32| (x + half).rem_euclid(2.0 * half) - half;
+    vec2(fold(p.x), fold(p.y))
+}
+
+/// `p` mirrored back into the rect, and the sign flip for a velocity along
+/// each axis it crossed.
+pub fn reflect(p: Vec2, half: f32) -> (Vec2, Vec2) {
+    let fold = |x: f32| {
+        // period 4 half widths, the way back runs mirrored
+        let u = (x + half).rem_euclid(4.0 * half);
+        if u < 2.0 * half {
+            (u - half, 1.0)
+        } else {
+            (3.0 * half - u, -1.0)
+        }
+    };
+    let ((x, fx), (y, fy)) = (fold(p.x), fold(p.y));
+    (vec2(x, y), vec2(fx, fy))
+}
+
+/// Moves a ploom and its circles without a streak between ticks.
+fn translate(ploom: &mut Ploom, offset: Vec2) {
+    ploom.focus += offset;
+    ploom.last_focus = ploom.focus;
+    for circle in &mut ploom.circles {
+        circle.x += offset;
+        circle.last = circle.x;
+    }
+}
+
+impl fmt::Display for Boundary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Boundary::Open => write!(f, "open"),
+            Boundary::Wrap => write!(f, "wrap"),
+            Boundary::Bounce => write!(f, "bounce"),
+            Boundary::Attract(k) => write!(f, "attract:{}", k),
+            Boundary::Respawn => write!(f, "respawn"),
+        }
+    }
+}
+
+impl FromStr for Boundary {
+    type Err = String;
+
+    /// `open`, `wrap`, `bounce`, `attract[:<radians per second>]` or `respawn`.
+    fn from_str(s: &str) -> Result<Boundary, String> {
+        match s.split_once(':') {
+            None if s == "open" => Ok(Boundary::Open),
+            None if s == "wrap" => Ok(Boundary::Wrap),
+            None if s == "bounce" => Ok(Boundary::Bounce),
+            None if s == "attract" => Ok(Boundary::Attract(ATTRACT)),
+            None if s == "respawn" => Ok(Boundary::Respawn),
+            Some(("attract", k)) => k
+                .parse()
+                .map(Boundary::Attract)
+                .map_err(|e| format!("{}", e)),
+            _ => Err(format!(
+                "expected open, wrap, bounce, attract[:<k>] or respawn, got {:?}",
+                s
+            )),
+        }
+    }
+}
+
+impl TryFrom<String> for Boundary {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Boundary, String> {
+        s.parse()
+    }
+}
+
+impl From<Boundary> for String {
+    fn from(boundary: Boundary) -> String {
+        boundary.to_string()
+    }
+}
@@ -0,0 +1,70 @@
+use crate::event::{Lifecycle, PloomEvent};
+use crate::paint::Painter;
+use nannou::prelude::*;
+
+// segments per ring outline
+const SEGMENTS: usize = 48;
+
+pub struct Ring {
+    pub at: Vec2,
+    pub age: f32, // seconds
+}
+
+/// Rings expanding from where plooms reset, the local replacement for
+/// wiping the whole canvas.
+pub struct Bursts {
+    pub rings: Vec<Ring>,
+    pub on: Lifecycle,
+    pub speed: f32,    // px per second
+    pub lifetime: f32, // seconds
+    pub weight: f32,
+}
+
+impl Bursts {
+    pub fn on_event(&mut self, event: &PloomEvent) {
+        if event.kind == self.on {
+            self.rings.push(Ring {
+                at: event.at,
+                age: 0.0,
+            });
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        for ring in &mut self.rings {
+            ring.age += dt;
+        }
+        let lifetime = self.lifetime;
+        self.rings.retain(|ring| ring.age < lifetime);
+    }
+
+    /// Outlines every ring, fading `color` out over its lifetime. `alpha` is
+    /// the clock's interpolation between ticks.
+    pub fn paint<P: Painter>(&self, p: &mut P, color: Rgba, alpha: f32, dt: f32) {
+        for ring in &self.rings {
+            let age = ring.age + alpha * dt;
+            let r = self.speed * age;
+            let mut fade = color;
+            fade.alpha *= (1.0 - age / self.lifetime).max(0.0);
+            let point = |k: usize| {
+                let angle = TAU * k as f32 / SEGMENTS as f32;
+                ring.at + vec2(angle.cos(), angle.sin()) * r
+            };
+            for k in 0..SEGMENTS {
+                p.line(point(k), point(k + 1), self.weight, fade);
+            }
+        }
+    }
+}
+
+impl Default for Bursts {
+    fn default() -> Bursts {
+        Bursts {
+            rings: Vec::new(),
+            on: Lifecycle::PloomReset,
+            speed: 120.0,
+            lifetime: 1.0,
+            weight: 1.5,
+        }
+    }
+}
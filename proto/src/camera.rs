@@ -0,0 +1,193 @@
+use crate::cli::arg;
+use nannou::prelude::*;
+use std::fmt;
+use std::str::FromStr;
+
+// radians of orbit per px dragged
+const ORBIT: f32 = 0.01;
+// distance factor per line scrolled
+const ZOOM: f32 = 1.1;
+// the eye starts this many focal lengths from the target
+const DISTANCE: f32 = 2.0;
+// nothing closer than this is drawn in perspective
+const NEAR: f32 = 1.0;
+// how close to straight up or down the eye may get
+const MAX_PITCH: f32 = PI / 2.0 - 0.01;
+const FOV: f32 = 45.0; // degrees
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Projection {
+    /// Far things shrink, `focal / depth` px per unit.
+    #[default]
+    Perspective,
+    /// Everything at the scale of the target, `focal / distance`.
+    Orthographic,
+}
+
+impl Projection {
+    pub fn next(self) -> Projection {
+        match self {
+            Projection::Perspective => Projection::Orthographic,
+            Projection::Orthographic => Projection::Perspective,
+        }
+    }
+}
+
+/// A point as the camera sees it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Projected {
+    pub xy: Vec2,   // window coordinates
+    pub depth: f32, // along the view direction from the eye
+    pub scale: f32, // px per unit there
+}
+
+/// An eye orbiting `target` at `distance`, turned by `yaw` about the y axis
+/// and tilted up by `pitch`. At zero both it looks down -z with x right and
+/// y up, like the window.
+pub struct Camera {
+    pub projection: Projection,
+    pub target: Vec3,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub distance: f32,
+    pub fov: f32,         // vertical, degrees
+    pub depth_range: f32, // depth over which things fade from front to back
+    pub spin: f32,        // radians per second of orbit on its own
+    half: f32,            // half the window, px
+    drag: Option<Vec2>,
+}
+
+impl Camera {
+    /// Framing a cube `window_size` across around the origin.
+    pub fn new(window_size: u32) -> Camera {
+        let mut camera = Camera {
+            projection: Projection::Perspective,
+            target: Vec3::ZERO,
+            yaw: 0.0,
+            pitch: 0.0,
+            distance: 0.0,
+            fov: FOV,
+            depth_range: window_size as f32,
+            spin: 0.0,
+            half: window_size as f32 / 2.0,
+            drag: None,
+        };
+        camera.distance = DISTANCE * camera.focal();
+        camera
+    }
+
+    /// `--camera perspective|orthographic`, `--fov <degrees>` and
+    /// `--spin <radians per second>`.
+    pub fn from_args(window_size: u32) -> Camera {
+        let mut camera = Camera::new(window_size);
+        if let Some(fov) = arg("--fov") {
+            camera.fov = fov;
+            camera.distance = DISTANCE * camera.focal();
+        }
+        Camera {
+            projection: arg("--camera").unwrap_or_default(),
+            spin: arg("--spin").unwrap_or(0.0),
+            ..camera
+        }
+    }
+
+    /// Px per unit at a depth of 1, from the field of view.
+    pub fn focal(&self) -> f32 {
+        self.half / (self.fov.to_radians() / 2.0).tan()
+    }
+
+    pub fn eye(&self) -> Vec3 {
+        let (sin_yaw, cos_yaw) = self.yaw.sin_cos();
+        let (sin_pitch, cos_pitch) = self.pitch.sin_cos();
+        self.target + vec3(cos_pitch * sin_yaw, sin_pitch, cos_pitch * cos_yaw) * self.distance
+    }
+
+    /// Right, up and forward unit vectors of the view.
+    pub fn basis(&self) -> (Vec3, Vec3, Vec3) {
+        let forward = (self.target - self.eye()).normalize();
+        let right = forward.cross(Vec3::Y).normalize();
+        (right, right.cross(forward), forward)
+    }
+
+    /// Where `p` lands in the window, `None` behind the near plane in
+    /// perspective.
+    pub fn project(&self, p: Vec3) -> Option<Projected> {
+        let (right, up, forward) = self.basis();
+        let d = p - self.eye();
+        let depth = d.dot(forward);
+        let scale = match self.projection {
+            Projection::Perspective if depth < NEAR => return None,
+            Projection::Perspective => self.focal() / depth,
+            Projection::Orthographic => self.focal() / self.distance,
+        };
+        Some(Projected {
+            xy: vec2(d.dot(right), d.dot(up)) * scale,
+            depth,
+            scale,
+        })
+    }
+
+    /// 1 at the front of `depth_range` around the target, 0.5 at the target
+    /// and 0 at the back, like `Node::fade` in beams.
+    pub fn fade(&self, depth: f32) -> f32 {
+        (0.5 - (depth - self.distance) / self.depth_range).clamp(0.0, 1.0)
+    }
+
+    /// Orbits on its own by `spin` over `dt` seconds.
+    pub fn update(&mut self, dt: f32) {
+        self.yaw += self.spin * dt;
+    }
+
+    /// Starts orbiting with the cursor at `at`.
+    pub fn press(&mut self, at: Vec2) {
+        self.drag = Some(at);
+    }
+
+    /// Orbits by how far the cursor moved since the last call, while pressed.
+    pub fn drag_to(&mut self, at: Vec2) {
+        if let Some(from) = self.drag.replace(at) {
+            let d = at - from;
+            self.yaw -= d.x * ORBIT;
+            self.pitch = (self.pitch - d.y * ORBIT).clamp(-MAX_PITCH, MAX_PITCH);
+        }
+    }
+
+    pub fn release(&mut self) {
+        self.drag = None;
+    }
+
+    /// Moves in by `lines` scrolled, out for negative ones.
+    pub fn zoom(&mut self, lines: f32) {
+        let focal = self.focal();
+        self.distance = (self.distance * ZOOM.powf(-lines)).clamp(focal / 4.0, focal * 20.0);
+    }
+
+    /// Back to the framing of `new`, keeping projection, fov and spin.
+    pub fn reset(&mut self) {
+        self.target = Vec3::ZERO;
+        self.yaw = 0.0;
+        self.pitch = 0.0;
+        self.distance = DISTANCE * self.focal();
+    }
+}
+
+impl FromStr for Projection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Projection, String> {
+        match s {
+            "perspective" => Ok(Projection::Perspective),
+            "orthographic" => Ok(Projection::Orthographic),
+            _ => Err(format!("expected perspective or orthographic, got {:?}", s)),
+        }
+    }
+}
+
+impl fmt::Display for Projection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Projection::Perspective => write!(f, "perspective"),
+            Projection::Orthographic => write!(f, "orthographic"),
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use crate::paint::Painter;
+use nannou::color::{IntoLinSrgba, LinSrgba};
+use nannou::image::{self, RgbImage};
+use nannou::prelude::*;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+/// How shapes combine with what is already on the canvas.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Blend {
+    /// Over, the way the window blends, never brighter than the brightest shape.
+    #[default]
+    Alpha,
+    /// Light adds up, overlapping shapes can go past 1.0.
+    Add,
+}
+
+/// CPU raster target with the same coordinate system as a nannou window:
+/// origin at the center, y pointing up. Pixels are stored in linear light and
+/// blended the way the GPU blends the window texture unless `blend` says
+/// otherwise.
+pub struct Canvas {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<[f32; 3]>,
+    pub blend: Blend,
+}
+
+impl Canvas {
+    pub fn new(width: u32, height: u32) -> Canvas {
+        Canvas {
+            width,
+            height,
+            pixels: vec![[0.0; 3]; (width * height) as usize],
+            blend: Blend::Alpha,
+        }
+    }
+
+    /// Center of pixel `(px, py)` in window coordinates.
+    fn to_window(&self, px: u32, py: u32) -> Vec2 {
+        vec2(
+            px as f32 + 0.5 - self.width as f32 / 2.0,
+            self.height as f32 / 2.0 - (py as f32 + 0.5),
+        )
+    }
+
+    /// Pixel range covering the window space box `[min, max]`, clamped to the canvas.
+    fn pixel_bounds(&self, min: Vec2, max: Vec2) -> Option<(u32, u32, u32, u32)> {
+        let x0 = (min.x + self.width as f32 / 2.0 - 1.0).floor().max(0.0);
+        let x1 = (max.x + self.width as f32 / 2.0 + 1.0)
+            .ceil()
+            .min(self.width as f32);
+        let y0 = (self.height as f32 / 2.0 - max.y - 1.0).floor().max(0.0);
+        let y1 = (self.height as f32 / 2.0 - min.y + 1.0)
+            .ceil()
+            .min(self.height as f32);
+        if x0 >= x1 || y0 >= y1 {
+            None
+        } else {
+            Some((x0 as u32, x1 as u32, y0 as u32, y1 as u32))
+        }
+    }
+
+    /// Blends `color` into every pixel in the box, scaled by `coverage`.
+    fn fill<F>(&mut self, min: Vec2, max: Vec2, color: LinSrgba, coverage: F)
+    where
+        F: Fn(Vec2) -> f32,
+    {
+        if let Some((x0, x1, y0, y1)) = self.pixel_bounds(min, max) {
+            for py in y0..y1 {
+                for px in x0..x1 {
+                    let a =
+                        (color.alpha * coverage(self.to_window(px, py)).clamp(0.0, 1.0)).min(1.0);
+                    if a > 0.0 {
+                        let pixel = &mut self.pixels[(py * self.width + px) as usize];
+                        let rgb = [color.red, color.green, color.blue];
+                        for (p, c) in pixel.iter_mut().zip(rgb) {
+                            match self.blend {
+                                Blend::Alpha => *p += (c - *p) * a,
+                                Blend::Add => *p += c * a,
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Encodes the canvas as an 8-bit sRGB image.
+    pub fn to_image(&self) -> RgbImage {
+        let mut bytes: Vec<u8> = Vec::with_capacity(self.pixels.len() * 3);
+        for pixel in &self.pixels {
+            for c in pixel {
+                bytes.push((linear_to_srgb(*c) * 255.0).round() as u8);
+            }
+        }
+        RgbImage::from_raw(self.width, self.height, bytes).unwrap()
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> image::ImageResult<()> {
+        self.to_image().save(path)
+    }
+}
+
+pub fn linear_to_srgb(c: f32) -> f32 {
+    let c = c.clamp(0.0, 1.0);
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+impl Painter for Canvas {
+    fn background<C: IntoLinSrgba<f32>>(&mut self, color: C) {
+        let color = color.into_lin_srgba();
+        for pixel in &mut self.pixels {
+            *pixel = [color.red, color.green, color.blue];
+        }
+    }
+
+    fn rect<C: IntoLinSrgba<f32>>(&mut self, xy: Vec2, wh: Vec2, color: C) {
+        let half = wh.abs() / 2.0;
+        let (min, max) = (xy - half, xy + half);
+        self.fill(min, max, color.into_lin_srgba(), |p| {
+            let inside = half - (p - xy).abs();
+            inside.x.min(inside.y) + 0.5
+        });
+    }
+
+    fn ellipse<C: IntoLinSrgba<f32>>(&mut self, xy: Vec2, radius: f32, color: C) {
+        if radius <= 0.0 {
+            return;
+        }
+        let r = vec2(radius, radius);
+        self.fill(xy - r, xy + r, color.into_lin_srgba(), |p| {
+            radius - p.distance(xy) + 0.5
+        });
+    }
+
+    fn line<C: IntoLinSrgba<f32>>(&mut self, start: Vec2, end: Vec2, weight: f32, color: C) {
+        let length = start.distance(end);
+        if length == 0.0 || weight <= 0.0 {
+            return;
+        }
+        let dir = (end - start) / length;
+        let half = weight / 2.0;
+        let pad = vec2(half, half);
+        self.fill(
+            start.min(end) - pad,
+            start.max(end) + pad,
+            color.into_lin_srgba(),
+            |p| {
+                let along = (p - start).dot(dir);
+                let across = (p - start).perp_dot(dir).abs();
+                let cover_along = (along.min(length - along) + 0.5).clamp(0.0, 1.0);
+                (half - across + 0.5).clamp(0.0, 1.0) * cover_along
+            },
+        );
+    }
+
+    fn quad<C: IntoLinSrgba<f32>>(&mut self, points: [Vec2; 4], color: C) {
+        // signed area tells us which side of each edge is inside
+        let mut area = 0.0;
+        for i in 0..4 {
+            area += points[i].perp_dot(points[(i + 1) % 4]);
+        }
+        let sign = if area < 0.0 { -1.0 } else { 1.0 };
+        let min = points.iter().fold(points[0], |m, p| m.min(*p));
+        let max = points.iter().fold(points[0], |m, p| m.max(*p));
+        self.fill(min, max, color.into_lin_srgba(), |p| {
+            let mut inside = f32::MAX;
+            for i in 0..4 {
+                let (a, b) = (points[i], points[(i + 1) % 4]);
+                let edge = (b - a).normalize_or_zero();
+                inside = inside.min(sign * edge.perp_dot(p - a));
+            }
+            inside + 0.5
+        });
+    }
+}
+
+impl fmt::Display for Blend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Blend::Alpha => write!(f, "alpha"),
+            Blend::Add => write!(f, "add"),
+        }
+    }
+}
+
+impl FromStr for Blend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Blend, String> {
+        match s {
+            "alpha" => Ok(Blend::Alpha),
+            "add" => Ok(Blend::Add),
+            _ => Err(format!("expected alpha or add, got {:?}", s)),
+        }
+    }
+}
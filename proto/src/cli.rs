@@ -0,0 +1,27 @@
+use std::env;
+use std::fmt::Debug;
+use std::str::FromStr;
+
+/// Value following `name` on the command line, e.g. `arg::<u64>("--seed")`.
+pub fn arg<T>(name: &str) -> Option<T>
+where
+    T: FromStr,
+    T::Err: Debug,
+{
+    let args: Vec<String> = env::args().collect();
+    args.iter()
+        .position(|a| a == name)
+        .and_then(|i| args.get(i + 1))
+        .map(|s| {
+            s.parse()
+                .unwrap_or_else(|e| panic!("bad {} {:?}: {:?}", name, s, e))
+        })
+}
+
+/// Name of the running example, e.g. `ploom_d`.
+pub fn exe_name() -> String {
+    env::current_exe()
+        .ok()
+        .and_then(|p| p.file_stem().map(|s| s.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| String::from("nomos"))
+}
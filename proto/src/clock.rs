@@ -0,0 +1,58 @@
+/// Rate every sketch simulates at, independent of the monitor refresh rate.
+pub const TICK_RATE: f32 = 60.0;
+
+/// Fixed timestep accumulator. Real time comes in through `advance` (from
+/// `Update::since_last` in the window, from the frame rate offline) and goes
+/// out as a whole number of ticks of `dt` seconds.
+pub struct Clock {
+    dt: f64,
+    pub max_ticks: u32, // per advance, so a stall doesn't snowball
+    accumulator: f64,
+}
+
+impl Clock {
+    pub fn new(rate: f32) -> Clock {
+        Clock {
+            dt: 1.0 / rate as f64,
+            max_ticks: 8,
+            accumulator: 0.0,
+        }
+    }
+
+    /// Adds `seconds` of real time, returns how many ticks to simulate.
+    pub fn advance(&mut self, seconds: f64) -> u32 {
+        self.accumulator += seconds;
+        let mut ticks = 0;
+        while self.accumulator >= self.dt {
+            self.accumulator -= self.dt;
+            ticks += 1;
+            if ticks == self.max_ticks {
+                self.accumulator = self.accumulator.min(self.dt);
+                break;
+            }
+        }
+        ticks
+    }
+
+    /// How far the frame being drawn is past the last tick, in `[0, 1]`.
+    /// Draw `last.lerp(current, alpha)` to interpolate between ticks.
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / self.dt).min(1.0) as f32
+    }
+
+    /// Seconds per tick as `f32`, for scaling per-second rates.
+    pub fn dt(&self) -> f32 {
+        self.dt as f32
+    }
+}
+
+impl Default for Clock {
+    fn default() -> Clock {
+        Clock::new(TICK_RATE)
+    }
+}
+
+/// Per-tick factor of something that keeps `per_second` of itself each second.
+pub fn per_tick(per_second: f32, dt: f32) -> f32 {
+    per_second.powf(dt)
+}
@@ -0,0 +1,114 @@
+use crate::ploom::rand_normal_vec2;
+use nannou::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Symmetric 2x2 covariance `[[xx, xy], [xy, yy]]`, N(mu, sigma*I) is the
+/// special case `isotropic(sigma)`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Covariance {
+    pub xx: f32,
+    pub xy: f32,
+    pub yy: f32,
+}
+
+impl Covariance {
+    pub fn isotropic(sigma: f32) -> Covariance {
+        Covariance {
+            xx: sigma * sigma,
+            xy: 0.0,
+            yy: sigma * sigma,
+        }
+    }
+
+    /// `sigma` stretched by `stretch` along `angle` and squeezed by the same
+    /// factor across it, so the plume keeps the area of the isotropic one.
+    pub fn rotated(sigma: f32, stretch: f32, angle: f32) -> Covariance {
+        let (major, minor) = ((sigma * stretch).powi(2), (sigma / stretch).powi(2));
+        let (sin, cos) = angle.sin_cos();
+        Covariance {
+            xx: major * cos * cos + minor * sin * sin,
+            xy: (major - minor) * sin * cos,
+            yy: major * sin * sin + minor * cos * cos,
+        }
+    }
+
+    pub fn scaled(&self, k: f32) -> Covariance {
+        Covariance {
+            xx: self.xx * k,
+            xy: self.xy * k,
+            yy: self.yy * k,
+        }
+    }
+
+    /// Lower triangular `L` with `L Lᵀ = self`, as `(l11, l21, l22)`.
+    pub fn cholesky(&self) -> (f32, f32, f32) {
+        let l11 = self.xx.max(0.0).sqrt();
+        let l21 = if l11 > 0.0 { self.xy / l11 } else { 0.0 };
+        let l22 = (self.yy - l21 * l21).max(0.0).sqrt();
+        (l11, l21, l22)
+    }
+
+    /// Draws from N(0, self) as `L z` with `z` standard normal.
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec2 {
+        let (l11, l21, l22) = self.cholesky();
+        let z = rand_normal_vec2(rng, 0.0, 1.0);
+        vec2(l11 * z.x, l21 * z.x + l22 * z.y)
+    }
+}
+
+/// How the major axis of an anisotropic plume turns over time. Presets
+/// store it in the same `spin:<rate>` syntax as `--align`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Align {
+    /// Keeps whatever angle the ploom spawned with.
+    Fixed,
+    /// Follows the direction the focus moved in last step, streaks.
+    Velocity,
+    /// Turns at a fixed rate (radians per second), fans.
+    Spin(f32),
+}
+
+impl FromStr for Align {
+    type Err = String;
+
+    /// `fixed`, `velocity` or `spin:<radians per second>`.
+    fn from_str(s: &str) -> Result<Align, String> {
+        match s.split_once(':') {
+            None if s == "fixed" => Ok(Align::Fixed),
+            None if s == "velocity" => Ok(Align::Velocity),
+            Some(("spin", rate)) => rate.parse().map(Align::Spin).map_err(|e| format!("{}", e)),
+            _ => Err(format!(
+                "expected fixed, velocity or spin:<rate>, got {:?}",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Align {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Align::Fixed => write!(f, "fixed"),
+            Align::Velocity => write!(f, "velocity"),
+            Align::Spin(rate) => write!(f, "spin:{}", rate),
+        }
+    }
+}
+
+impl TryFrom<String> for Align {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Align, String> {
+        s.parse()
+    }
+}
+
+impl From<Align> for String {
+    fn from(align: Align) -> String {
+        align.to_string()
+    }
+}
@@ -0,0 +1,66 @@
+use crate::ploom::Ploom;
+use nannou::prelude::*;
+use std::vec::Drain;
+
+/// What happened to a ploom.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Lifecycle {
+    /// `sigma` decayed to `threshold`, the ploom resets next tick.
+    SigmaThresholdCrossed,
+    /// `sigma` jumped back to `ploom`.
+    PloomReset,
+    /// The focus appeared without travelling there: a new ploom, a teleport
+    /// on reset, or a wrap or respawn at the boundary.
+    FocusSpawn,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PloomEvent {
+    pub kind: Lifecycle,
+    pub ploom: usize, // index into the sketch's plooms
+    pub at: Vec2,     // focus when it happened
+}
+
+/// Every ploom's events in the order they happened. Plooms buffer their own,
+/// `collect` after each tick gathers them so the renderer, the plotter or
+/// anything else can go through them.
+#[derive(Debug, Default)]
+pub struct Events {
+    queue: Vec<PloomEvent>,
+}
+
+impl Events {
+    pub fn collect(&mut self, plooms: &mut [Ploom]) {
+        for (i, ploom) in plooms.iter_mut().enumerate() {
+            for (kind, at) in ploom.events.drain(..) {
+                self.queue.push(PloomEvent { kind, ploom: i, at });
+            }
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &PloomEvent> {
+        self.queue.iter()
+    }
+
+    /// Whether ploom `i` had a `kind` event since the last drain.
+    pub fn has(&self, i: usize, kind: Lifecycle) -> bool {
+        self.queue.iter().any(|e| e.ploom == i && e.kind == kind)
+    }
+
+    /// Forgets ploom `i`'s events and moves the later plooms' down by one,
+    /// for when it's removed.
+    pub fn remove(&mut self, i: usize) {
+        self.queue.retain(|e| e.ploom != i);
+        for event in self.queue.iter_mut().filter(|e| e.ploom > i) {
+            event.ploom -= 1;
+        }
+    }
+
+    pub fn drain(&mut self) -> Drain<'_, PloomEvent> {
+        self.queue.drain(..)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+}
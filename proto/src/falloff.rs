@@ -0,0 +1,135 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+// exponent of `power` without one
+const POWER: f32 = 2.0;
+
+/// How an edge weakens from full strength at `min_distance` to nothing at
+/// `max_distance`, as a function of `t` in `[0, 1]` between them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Falloff {
+    /// Full strength all the way, then nothing: the old hard cutoff.
+    Step,
+    /// `1 - t`.
+    Linear,
+    /// Smoothstep, flat at both ends so edges neither pop nor linger.
+    #[default]
+    Smooth,
+    /// `(1 - t)^p`, short edges dominate for `p > 1`.
+    Power(f32),
+}
+
+impl Falloff {
+    /// Strength at `t`, 1 at or below 0 and 0 at or above 1.
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Falloff::Step => (t < 1.0) as u8 as f32,
+            Falloff::Linear => 1.0 - t,
+            Falloff::Smooth => 1.0 - t * t * (3.0 - 2.0 * t),
+            Falloff::Power(p) => (1.0 - t).powf(p),
+        }
+    }
+}
+
+/// How ploom focus edges are drawn: weight, alpha and palette position are
+/// continuous in distance, and everything comes up over `fade_in` seconds
+/// from the start.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EdgeStyle {
+    pub weight: f32, // px at full strength
+    pub thin: f32,   // px just before an edge vanishes
+    pub alpha: f32,  // at full strength
+    pub falloff: Falloff,
+    pub fade_in: f32, // seconds from nothing to full alpha
+}
+
+impl Default for EdgeStyle {
+    fn default() -> EdgeStyle {
+        EdgeStyle {
+            weight: 2.0,
+            thin: 0.5,
+            alpha: 0.1,
+            falloff: Falloff::Smooth,
+            fade_in: 30.0,
+        }
+    }
+}
+
+impl EdgeStyle {
+    /// Where `distance` sits between `min` and `max`, clamped to `[0, 1]`,
+    /// also what edge palettes are looked up with.
+    pub fn t(distance: f32, min: f32, max: f32) -> f32 {
+        if max > min {
+            ((distance - min) / (max - min)).clamp(0.0, 1.0)
+        } else {
+            (distance >= max) as u8 as f32
+        }
+    }
+
+    /// Falloff strength of an edge `distance` long.
+    pub fn strength(&self, distance: f32, min: f32, max: f32) -> f32 {
+        self.falloff.apply(EdgeStyle::t(distance, min, max))
+    }
+
+    /// Line weight at `strength`.
+    pub fn weight(&self, strength: f32) -> f32 {
+        self.thin + (self.weight - self.thin) * strength
+    }
+
+    /// Smoothstepped ramp from 0 at `time` 0 to 1 at `fade_in` seconds.
+    pub fn envelope(&self, time: f32) -> f32 {
+        if self.fade_in <= 0.0 {
+            return 1.0;
+        }
+        let t = (time / self.fade_in).clamp(0.0, 1.0);
+        t * t * (3.0 - 2.0 * t)
+    }
+}
+
+impl fmt::Display for Falloff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Falloff::Step => write!(f, "step"),
+            Falloff::Linear => write!(f, "linear"),
+            Falloff::Smooth => write!(f, "smooth"),
+            Falloff::Power(p) => write!(f, "power:{}", p),
+        }
+    }
+}
+
+impl FromStr for Falloff {
+    type Err = String;
+
+    /// `step`, `linear`, `smooth` or `power[:<exponent>]`.
+    fn from_str(s: &str) -> Result<Falloff, String> {
+        match s.split_once(':') {
+            None if s == "step" => Ok(Falloff::Step),
+            None if s == "linear" => Ok(Falloff::Linear),
+            None if s == "smooth" => Ok(Falloff::Smooth),
+            None if s == "power" => Ok(Falloff::Power(POWER)),
+            Some(("power", p)) => p.parse().map(Falloff::Power).map_err(|e| format!("{}", e)),
+            _ => Err(format!(
+                "expected step, linear, smooth or power[:<p>], got {:?}",
+                s
+            )),
+        }
+    }
+}
+
+impl TryFrom<String> for Falloff {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Falloff, String> {
+        s.parse()
+    }
+}
+
+impl From<Falloff> for String {
+    fn from(falloff: Falloff) -> String {
+        falloff.to_string()
+    }
+}
@@ -0,0 +1,233 @@
+use crate::cli::arg;
+use crate::ploom::{rand_normal_vec2, rand_uniform_vec2, Ploom};
+use nannou::prelude::*;
+use rand::Rng;
+
+/// How a ploom focus moves. `t` is the age of the ploom and periods are in
+/// seconds. Everything but `Drift` and `RandomWalk` is a closed curve that
+/// the focus keeps following through resets instead of teleporting.
+#[derive(Clone, Debug, Default)]
+pub enum FocusPath {
+    /// The original behaviour, `focus += v dt`.
+    #[default]
+    Drift,
+    /// Brownian focus, each step adds N(0, step² dt).
+    RandomWalk { step: f32 },
+    Circle {
+        center: Vec2,
+        radius: f32,
+        period: f32,
+        phase: f32,
+    },
+    /// `center + amplitude * sin(2π frequency t / period + phase)` per axis.
+    Lissajous {
+        center: Vec2,
+        amplitude: Vec2,
+        frequency: Vec2,
+        phase: Vec2,
+        period: f32,
+    },
+    /// Closed Catmull-Rom spline through every waypoint.
+    Spline { waypoints: Vec<Vec2>, period: f32 },
+    /// Cubic Bezier travelled there and back.
+    Bezier { control: [Vec2; 4], period: f32 },
+    /// Mouse gesture sampled every `interval` seconds, looped.
+    Recorded { points: Vec<Vec2>, interval: f32 },
+}
+
+impl FocusPath {
+    /// Random path of the given kind (`drift`, `walk`, `circle`, `lissajous`,
+    /// `spline`, `bezier`) inside a centered square window.
+    pub fn random<R: Rng + ?Sized>(kind: &str, window_size: u32, rng: &mut R) -> FocusPath {
+        let bound = window_size as f32 / 2.0;
+        match kind {
+            "drift" => FocusPath::Drift,
+            "walk" => FocusPath::RandomWalk { step: 8.0 },
+            "circle" => FocusPath::Circle {
+                center: rand_uniform_vec2(rng, window_size) / 2.0,
+                radius: rng.gen_range(bound / 8.0, bound / 2.0),
+                period: rng.gen_range(5.0, 20.0),
+                phase: rng.gen_range(0.0, TAU),
+            },
+            "lissajous" => FocusPath::Lissajous {
+                center: Vec2::ZERO,
+                amplitude: vec2(rng.gen_range(0.3, 0.9), rng.gen_range(0.3, 0.9)) * bound,
+                frequency: vec2(rng.gen_range(1, 5) as f32, rng.gen_range(1, 5) as f32),
+                phase: vec2(rng.gen_range(0.0, TAU), rng.gen_range(0.0, TAU)),
+                period: rng.gen_range(20.0, 60.0),
+            },
+            "spline" => FocusPath::Spline {
+                waypoints: (0..rng.gen_range(4, 8))
+                    .map(|_| rand_uniform_vec2(rng, window_size))
+                    .collect(),
+                period: rng.gen_range(20.0, 60.0),
+            },
+            "bezier" => FocusPath::Bezier {
+                control: [
+                    rand_uniform_vec2(rng, window_size),
+                    rand_uniform_vec2(rng, window_size),
+                    rand_uniform_vec2(rng, window_size),
+                    rand_uniform_vec2(rng, window_size),
+                ],
+                period: rng.gen_range(10.0, 30.0),
+            },
+            other => panic!(
+                "unknown focus path {:?}, expected drift, walk, circle, lissajous, spline or bezier",
+                other
+            ),
+        }
+    }
+
+    /// False for paths that are a fixed curve, those don't teleport on reset.
+    pub fn teleports(&self) -> bool {
+        matches!(self, FocusPath::Drift | FocusPath::RandomWalk { .. })
+    }
+
+    /// Seconds it takes to go once around the path.
+    pub fn period(&self) -> Option<f32> {
+        match self {
+            FocusPath::Drift | FocusPath::RandomWalk { .. } => None,
+            FocusPath::Circle { period, .. }
+            | FocusPath::Lissajous { period, .. }
+            | FocusPath::Spline { period, .. }
+            | FocusPath::Bezier { period, .. } => Some(*period),
+            FocusPath::Recorded { points, interval } => Some(points.len() as f32 * interval),
+        }
+    }
+
+    /// Focus position after a step of `dt` seconds to age `t`, drifting at
+    /// `v` px per second.
+    pub fn next<R: Rng + ?Sized>(
+        &self,
+        focus: Vec2,
+        v: Vec2,
+        t: f32,
+        dt: f32,
+        rng: &mut R,
+    ) -> Vec2 {
+        match self {
+            FocusPath::Drift => focus + v * dt,
+            FocusPath::RandomWalk { step } => focus + rand_normal_vec2(rng, 0.0, step * dt.sqrt()),
+            _ => self.position(t).unwrap(),
+        }
+    }
+
+    /// Position at age `t` for the scripted paths, `None` for `Drift` and
+    /// `RandomWalk` which depend on where the focus already is.
+    pub fn position(&self, t: f32) -> Option<Vec2> {
+        match self {
+            FocusPath::Drift | FocusPath::RandomWalk { .. } => None,
+            FocusPath::Circle {
+                center,
+                radius,
+                period,
+                phase,
+            } => {
+                let angle = TAU * t / period + phase;
+                Some(*center + vec2(angle.cos(), angle.sin()) * *radius)
+            }
+            FocusPath::Lissajous {
+                center,
+                amplitude,
+                frequency,
+                phase,
+                period,
+            } => {
+                let angle = *frequency * (TAU * t / period) + *phase;
+                Some(*center + *amplitude * vec2(angle.x.sin(), angle.y.sin()))
+            }
+            FocusPath::Spline { waypoints, period } => {
+                let n = waypoints.len();
+                let u = (t / period).rem_euclid(1.0) * n as f32;
+                let i = (u.floor() as usize).min(n - 1);
+                let p = |k: usize| waypoints[(i + n + k - 1) % n];
+                Some(catmull_rom(p(0), p(1), p(2), p(3), u - i as f32))
+            }
+            FocusPath::Bezier { control, period } => {
+                // triangle wave so the way back retraces the way out
+                let s = 1.0 - (2.0 * (t / period).rem_euclid(1.0) - 1.0).abs();
+                Some(bezier(control, s))
+            }
+            FocusPath::Recorded { points, interval } => {
+                let u = (t / interval).rem_euclid(points.len() as f32);
+                let i = u.floor() as usize % points.len();
+                let j = (i + 1) % points.len();
+                Some(points[i].lerp(points[j], u.fract()))
+            }
+        }
+    }
+}
+
+/// Uniform Catmull-Rom segment between `p1` and `p2`.
+pub fn catmull_rom(p0: Vec2, p1: Vec2, p2: Vec2, p3: Vec2, s: f32) -> Vec2 {
+    let (s2, s3) = (s * s, s * s * s);
+    0.5 * (2.0 * p1
+        + (p2 - p0) * s
+        + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * s2
+        + (3.0 * p1 - p0 - 3.0 * p2 + p3) * s3)
+}
+
+pub fn bezier(control: &[Vec2; 4], s: f32) -> Vec2 {
+    let r = 1.0 - s;
+    control[0] * (r * r * r)
+        + control[1] * (3.0 * r * r * s)
+        + control[2] * (3.0 * r * s * s)
+        + control[3] * (s * s * s)
+}
+
+/// Cursor positions sampled once per tick while recording, toggled from a key.
+#[derive(Default)]
+pub struct Gesture {
+    pub points: Vec<Vec2>,
+    pub recording: bool,
+}
+
+impl Gesture {
+    pub fn sample(&mut self, cursor: Vec2) {
+        if self.recording {
+            self.points.push(cursor);
+        }
+    }
+
+    /// Starts a new recording, or stops the current one and returns it as a
+    /// path if it has at least two samples, `dt` seconds apart.
+    pub fn toggle(&mut self, dt: f32) -> Option<FocusPath> {
+        self.recording = !self.recording;
+        if self.recording {
+            self.points.clear();
+            None
+        } else if self.points.len() > 1 {
+            Some(FocusPath::Recorded {
+                points: std::mem::take(&mut self.points),
+                interval: dt,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+/// Puts every ploom on `path`, spreading them evenly along it.
+pub fn follow(plooms: &mut [Ploom], path: &FocusPath) {
+    let period = path.period().unwrap_or(0.0);
+    let n = plooms.len() as f32;
+    for (i, ploom) in plooms.iter_mut().enumerate() {
+        ploom.path = path.clone();
+        ploom.t = period * i as f32 / n;
+        if let Some(focus) = path.position(ploom.t) {
+            ploom.focus = focus;
+        }
+    }
+}
+
+/// Gives each ploom its own random path of the kind passed as `--path <kind>`.
+pub fn paths_from_args<R: Rng + ?Sized>(plooms: &mut [Ploom], window_size: u32, rng: &mut R) {
+    if let Some(kind) = arg::<String>("--path") {
+        for ploom in plooms {
+            ploom.path = FocusPath::random(&kind, window_size, rng);
+            if let Some(focus) = ploom.path.position(ploom.t) {
+                ploom.focus = focus;
+            }
+        }
+    }
+}
@@ -0,0 +1,285 @@
+use crate::cli::arg;
+use crate::spatial::{pairs_within, Grid};
+use nannou::prelude::*;
+use serde::{Deserialize, Serialize};
+use spade::{DelaunayTriangulation, Point2, Triangulation};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::str::FromStr;
+
+// neighbours per point for `knn` without a count
+const KNN: usize = 3;
+// graph edges fade out at this many times their longest edge
+const REACH: f32 = 2.0;
+
+/// An undirected edge `(i, j, distance)` between points `i < j`.
+pub type Edge = (usize, usize, f32);
+
+/// Which foci get connected by lines.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum EdgeMode {
+    /// Every pair closer than `max_distance`.
+    #[default]
+    Radius,
+    /// Delaunay triangulation, no edges cross and nothing is left alone.
+    Delaunay,
+    /// Euclidean minimum spanning tree, the shortest lines that connect
+    /// everything.
+    Mst,
+    /// Each point to its `k` nearest neighbours.
+    Knn(usize),
+    /// Relative neighborhood graph, two points are connected unless a third
+    /// is closer to both of them than they are to each other.
+    Relative,
+}
+
+impl EdgeMode {
+    /// `--edges radius|delaunay|mst|knn[:<k>]|rng`.
+    pub fn from_args() -> Option<EdgeMode> {
+        arg("--edges")
+    }
+
+    /// Radius, Delaunay, MST, kNN, RNG, radius...
+    pub fn next(self) -> EdgeMode {
+        match self {
+            EdgeMode::Radius => EdgeMode::Delaunay,
+            EdgeMode::Delaunay => EdgeMode::Mst,
+            EdgeMode::Mst => EdgeMode::Knn(KNN),
+            EdgeMode::Knn(_) => EdgeMode::Relative,
+            EdgeMode::Relative => EdgeMode::Radius,
+        }
+    }
+
+    /// Edges between `points` sorted by `(i, j)`, `max_distance` only bounds
+    /// `Radius`.
+    pub fn edges(self, points: &[Vec2], max_distance: f32) -> Vec<Edge> {
+        match self {
+            EdgeMode::Radius => pairs_within(points, max_distance),
+            EdgeMode::Delaunay => delaunay(points),
+            EdgeMode::Mst => mst(points),
+            EdgeMode::Knn(k) => knn(points, k),
+            EdgeMode::Relative => relative(points),
+        }
+    }
+
+    /// How long an edge is when it has faded out completely. `Radius` drops
+    /// edges at `max_distance` anyway, the graphs keep every edge they make
+    /// however long, so theirs fade out past the `longest` one.
+    pub fn reach(self, max_distance: f32, longest: f32) -> f32 {
+        match self {
+            EdgeMode::Radius => max_distance,
+            _ => max_distance.max(longest * REACH),
+        }
+    }
+}
+
+/// Delaunay edges of `points`. Duplicates of an earlier point and points
+/// that aren't finite get none.
+pub fn delaunay(points: &[Vec2]) -> Vec<Edge> {
+    let mut triangulation: DelaunayTriangulation<Point2<f64>> = DelaunayTriangulation::new();
+    let mut index = Vec::new(); // point of every vertex
+    for (i, p) in points.iter().enumerate() {
+        if let Ok(vertex) = triangulation.insert(Point2::new(p.x as f64, p.y as f64)) {
+            // a duplicate comes back as the vertex it landed on
+            if vertex.index() == index.len() {
+                index.push(i);
+            }
+        }
+    }
+    let mut edges: Vec<Edge> = triangulation
+        .undirected_edges()
+        .map(|edge| {
+            let [a, b] = edge.vertices().map(|v| index[v.fix().index()]);
+            (a.min(b), a.max(b), points[a].distance(points[b]))
+        })
+        .collect();
+    sort(&mut edges);
+    edges
+}
+
+/// Euclidean minimum spanning tree, Kruskal over the Delaunay edges which
+/// always contain it. `n - 1` edges for `n` distinct points.
+pub fn mst(points: &[Vec2]) -> Vec<Edge> {
+    let mut candidates = delaunay(points);
+    candidates.sort_by(|a, b| a.2.total_cmp(&b.2));
+    let mut parent: Vec<usize> = (0..points.len()).collect();
+    let mut edges: Vec<Edge> = candidates
+        .into_iter()
+        .filter(|&(i, j, _)| {
+            let (a, b) = (root(&mut parent, i), root(&mut parent, j));
+            parent[a] = b;
+            a != b
+        })
+        .collect();
+    sort(&mut edges);
+    edges
+}
+
+fn root(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+/// Every point to its `k` nearest others, an edge found from both ends is
+/// kept once.
+pub fn knn(points: &[Vec2], k: usize) -> Vec<Edge> {
+    let k = k.min(points.len().saturating_sub(1));
+    if k == 0 {
+        return Vec::new();
+    }
+    let mut pairs = BTreeSet::new();
+    let mut others: Vec<(f32, usize)> = Vec::with_capacity(points.len());
+    for (i, p) in points.iter().enumerate() {
+        others.clear();
+        others.extend(
+            points
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .map(|(j, q)| (p.distance(*q), j)),
+        );
+        others.select_nth_unstable_by(k - 1, |a, b| a.0.total_cmp(&b.0));
+        for &(_, j) in &others[..k] {
+            pairs.insert((i.min(j), i.max(j)));
+        }
+    }
+    pairs
+        .into_iter()
+        .map(|(i, j)| (i, j, points[i].distance(points[j])))
+        .collect()
+}
+
+/// Relative neighborhood graph, the Delaunay edges without a point strictly
+/// closer to both ends than they are to each other. Contains the MST.
+pub fn relative(points: &[Vec2]) -> Vec<Edge> {
+    let mut edges = delaunay(points);
+    let mean = edges.iter().map(|e| e.2).sum::<f32>() / edges.len().max(1) as f32;
+    if mean <= 0.0 {
+        return edges;
+    }
+    let grid = Grid::new(points, mean);
+    edges.retain(|&(i, j, distance)| {
+        grid.within(points[i], distance)
+            .into_iter()
+            .all(|k| points[j].distance(points[k]) >= distance)
+    });
+    edges
+}
+
+fn sort(edges: &mut [Edge]) {
+    edges.sort_unstable_by_key(|&(i, j, _)| (i, j));
+}
+
+/// Edge weights in `[0, 1]` that follow a changing topology: edges fade in
+/// when they appear and out when they're gone instead of popping, e.g. when
+/// two foci trade neighbours or the mode changes.
+#[derive(Clone, Debug, Default)]
+pub struct EdgeFade {
+    weights: BTreeMap<(usize, usize), f32>, // linear, eased on the way out
+}
+
+impl EdgeFade {
+    /// Moves every weight a tick of `dt` seconds towards 1 for `edges` and
+    /// 0 for the rest, taking `fade` seconds from one to the other.
+    pub fn update(&mut self, edges: &[Edge], fade: f32, dt: f32) {
+        let step = if fade > 0.0 { dt / fade } else { 1.0 };
+        let current: BTreeSet<(usize, usize)> = edges.iter().map(|&(i, j, _)| (i, j)).collect();
+        for (pair, weight) in self.weights.iter_mut() {
+            if !current.contains(pair) {
+                *weight -= step;
+            }
+        }
+        for pair in current {
+            let weight = self.weights.entry(pair).or_insert(0.0);
+            *weight = (*weight + step).min(1.0);
+        }
+        self.weights.retain(|_, weight| *weight > 0.0);
+    }
+
+    /// Smoothstepped weight of `(i, j)`, 0 for edges that aren't there.
+    pub fn weight(&self, i: usize, j: usize) -> f32 {
+        self.weights
+            .get(&(i.min(j), i.max(j)))
+            .map_or(0.0, |&w| smoothstep(w))
+    }
+
+    /// `(i, j, weight)` for every edge still visible, in `(i, j)` order.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize, f32)> + '_ {
+        self.weights
+            .iter()
+            .map(|(&(i, j), &w)| (i, j, smoothstep(w)))
+    }
+
+    /// Drops the edges of point `i` and moves the points above it down by
+    /// one, for when it's removed.
+    pub fn remove(&mut self, i: usize) {
+        let down = |k: usize| if k > i { k - 1 } else { k };
+        self.weights = std::mem::take(&mut self.weights)
+            .into_iter()
+            .filter(|&((a, b), _)| a != i && b != i)
+            .map(|((a, b), w)| ((down(a), down(b)), w))
+            .collect();
+    }
+
+    pub fn len(&self) -> usize {
+        self.weights.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.weights.is_empty()
+    }
+}
+
+fn smoothstep(t: f32) -> f32 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+impl fmt::Display for EdgeMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EdgeMode::Radius => write!(f, "radius"),
+            EdgeMode::Delaunay => write!(f, "delaunay"),
+            EdgeMode::Mst => write!(f, "mst"),
+            EdgeMode::Knn(k) => write!(f, "knn:{}", k),
+            EdgeMode::Relative => write!(f, "rng"),
+        }
+    }
+}
+
+impl FromStr for EdgeMode {
+    type Err = String;
+
+    /// `radius`, `delaunay`, `mst`, `knn[:<k>]` or `rng`.
+    fn from_str(s: &str) -> Result<EdgeMode, String> {
+        match s.split_once(':') {
+            None if s == "radius" => Ok(EdgeMode::Radius),
+            None if s == "delaunay" => Ok(EdgeMode::Delaunay),
+            None if s == "mst" => Ok(EdgeMode::Mst),
+            None if s == "knn" => Ok(EdgeMode::Knn(KNN)),
+            None if s == "rng" => Ok(EdgeMode::Relative),
+            Some(("knn", k)) => k.parse().map(EdgeMode::Knn).map_err(|e| format!("{}", e)),
+            _ => Err(format!(
+                "expected radius, delaunay, mst, knn[:<k>] or rng, got {:?}",
+                s
+            )),
+        }
+    }
+}
+
+impl TryFrom<String> for EdgeMode {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<EdgeMode, String> {
+        s.parse()
+    }
+}
+
+impl From<EdgeMode> for String {
+    fn from(mode: EdgeMode) -> String {
+        mode.to_string()
+    }
+}
@@ -0,0 +1,434 @@
+use crate::accum::AccumWindow;
+use crate::audio::Audio;
+use crate::burst::Bursts;
+use crate::clock::Clock;
+use crate::event::{Events, Lifecycle};
+use crate::focus::{follow, paths_from_args, Gesture};
+use crate::graph::EdgeFade;
+use crate::mouse::{nearest, spawn_at, steer_nearest, Mouse, Stroke};
+use crate::noise::noise_from_args;
+use crate::offline::Offline;
+use crate::osc::OscListener;
+use crate::paint::Painter;
+use crate::palette::{ColorBy, Palette};
+use crate::panel::{ploom_panel, randomize, PanelAction};
+use crate::ploom::{Ploom, PloomParams};
+use crate::plotter::PlotExport;
+use crate::preset::Preset;
+use crate::record::{hold_plooms, restore_plooms, PloomState, Recorder, Replay, Snapshot};
+use crate::rng::{capture_name, seed_from_args, seeded_rng};
+use crate::svg::SvgExport;
+use crate::swarm::SwarmRun;
+use nannou::prelude::*;
+use nannou_egui::Egui;
+use rand::rngs::StdRng;
+use std::cell::RefCell;
+use std::time::Duration;
+
+/// The defaults of ploom_d.
+pub fn ploom_d() -> PloomParams {
+    PloomParams {
+        num_plooms: 100,
+        num_circles: 3,
+        decay: 0.3, // per second
+        threshold: 1.55,
+        ploom: 155.0, // or 31, or 77
+        dampening: 0.7,
+        alpha: 0.02,
+        max_distance: 100.0,
+        ..PloomParams::default()
+    }
+}
+
+/// The defaults of ploom_e.
+pub fn ploom_e() -> PloomParams {
+    PloomParams {
+        num_plooms: 150,
+        num_circles: 2,
+        decay: 0.4, // per second
+        threshold: 2.32,
+        ploom: 387.0,
+        dampening: 0.84,
+        alpha: 0.02,
+        max_distance: 100.0,
+        ..PloomParams::default()
+    }
+}
+
+/// The defaults of ploom_f.
+pub fn ploom_f() -> PloomParams {
+    PloomParams {
+        num_plooms: 150,
+        num_circles: 2,
+        decay: 0.55, // per second
+        threshold: 0.77,
+        ploom: 31.0,
+        dampening: 0.91,
+        alpha: 0.1,
+        min_distance: 100.0,
+        max_distance: 150.0,
+        ..PloomParams::default()
+    }
+}
+
+/// What sets one of the ploom sketches apart: its defaults, its colors and
+/// how it paints a frame. Running, recording, the panel, OSC and the mouse
+/// are all the `Harness`'s.
+pub trait Sketch: Default + Sized + 'static {
+    /// Defaults a preset file is loaded over and the panel resets to.
+    fn params() -> PloomParams;
+
+    /// The palette when there's no `--palette`.
+    fn palette() -> Palette;
+
+    fn paint<P: Painter>(p: &mut P, model: &Harness<Self>, nth: u64, time: f32);
+}
+
+/// The model of a ploom sketch.
+pub struct Harness<S> {
+    pub sketch: S,
+    pub params: PloomParams,
+    pub plooms: Vec<Ploom>,
+    pub events: Events,
+    pub bursts: Bursts,
+    pub links: EdgeFade, // foci connected by `params.edges`
+    pub audio: Option<Audio>,
+    pub osc: Option<OscListener>,
+    pub record: Option<Recorder>,
+    pub replay: Option<Replay>, // drives the plooms instead of the simulation
+    pub swarm: Option<SwarmRun>, // steps the plooms in parallel instead
+    pub mouse: Mouse,
+    pub seed: u64,
+    pub rng: StdRng,
+    pub svg: Option<SvgExport>,
+    pub plot: Option<PlotExport>,
+    pub gesture: Gesture,
+    pub clock: Clock,
+    pub egui: Option<Egui>,
+    pub preset: Preset,
+    pub palette: Palette,
+    pub color_by: ColorBy,
+    pub hdr: Option<RefCell<AccumWindow>>, // `--trails` and friends in the window
+}
+
+/// Runs sketch `S` in a window, or headless with `--offline`.
+pub fn run<S: Sketch>() {
+    match Offline::from_args() {
+        Some(offline) => {
+            let mut model = Harness::<S>::new(seed_from_args());
+            let size = model.params.window_size;
+            offline.run(
+                model.seed,
+                size,
+                &mut model,
+                Harness::advance,
+                |canvas, model, nth, time| {
+                    S::paint(canvas, model, nth, time);
+                    model.record_svg(nth, time);
+                },
+            );
+        }
+        None => nannou::app(model::<S>).update(update::<S>).run(),
+    }
+}
+
+impl<S: Sketch> Harness<S> {
+    pub fn new(seed: u64) -> Harness<S> {
+        let mut preset = Preset::from_args(seed);
+        let params = preset.load_or(S::params()).with_args();
+        let mut rng = seeded_rng(seed);
+        let svg = SvgExport::from_args(seed, params.window_size);
+        let plot = PlotExport::from_args(seed, params.window_size);
+        let plooms = spawn_plooms(&params, seed, &mut rng);
+        let swarm = SwarmRun::from_args(seed, &params);
+        Harness {
+            sketch: S::default(),
+            plooms,
+            params,
+            events: Events::default(),
+            bursts: Bursts::default(),
+            links: EdgeFade::default(),
+            audio: Audio::from_args(),
+            osc: OscListener::from_args(),
+            record: Recorder::from_args(),
+            replay: Replay::from_args(),
+            swarm,
+            mouse: Mouse::from_args(),
+            seed,
+            rng,
+            svg,
+            plot,
+            gesture: Gesture::default(),
+            clock: Clock::default(),
+            hdr: None,
+            egui: None,
+            preset,
+            palette: Palette::from_args(S::palette()),
+            color_by: ColorBy::from_args(),
+        }
+    }
+
+    /// Runs as many ticks as fit in `seconds`.
+    pub fn advance(&mut self, seconds: f64) {
+        for _ in 0..self.clock.advance(seconds) {
+            self.step();
+        }
+    }
+
+    /// One tick: replay or simulate, then everything that follows the foci.
+    pub fn step(&mut self) {
+        let dt = self.clock.dt();
+        match &mut self.replay {
+            Some(replay) => match replay.advance(dt) {
+                Some(snapshot) => {
+                    let (plooms, params) = (&mut self.plooms, &mut self.params);
+                    restore_plooms(plooms, &snapshot.plooms, params, &mut self.rng);
+                }
+                None => hold_plooms(&mut self.plooms),
+            },
+            None => self.simulate(dt),
+        }
+        self.events.collect(&mut self.plooms);
+        let foci: Vec<Vec2> = self.plooms.iter().map(|p| p.focus).collect();
+        let edges = self.params.edges.edges(&foci, self.params.max_distance);
+        self.links.update(&edges, self.params.edge_fade, dt);
+        if let Some(plot) = &mut self.plot {
+            for (i, ploom) in self.plooms.iter().enumerate() {
+                let jumped = self.events.has(i, Lifecycle::FocusSpawn);
+                plot.trace(i, ploom.focus, jumped);
+            }
+            plot.end_tick();
+        }
+        self.bursts.update(dt);
+        for event in self.events.drain() {
+            self.bursts.on_event(&event);
+        }
+        if let Some(record) = &mut self.record {
+            let plooms = &self.plooms;
+            let snapshot = || Snapshot {
+                plooms: plooms.iter().map(PloomState::of).collect(),
+                ..Snapshot::default()
+            };
+            if let Err(e) = record.record(dt, snapshot) {
+                eprintln!("record: {}", e);
+            }
+        }
+    }
+
+    /// Drops ploom `i`, along with its edges, events and plotter trail, and
+    /// moves what's keyed by the later plooms' indices down with them.
+    pub fn remove_ploom(&mut self, i: usize) -> Ploom {
+        let ploom = self.plooms.remove(i);
+        self.params.num_plooms = self.plooms.len();
+        self.links.remove(i);
+        self.events.remove(i);
+        if let Some(plot) = &mut self.plot {
+            plot.remove(i);
+        }
+        ploom
+    }
+
+    /// Where the visible edges between `foci` have faded out, see
+    /// `EdgeMode::reach`.
+    pub fn edge_reach(&self, foci: &[Vec2]) -> f32 {
+        let longest = self
+            .links
+            .iter()
+            .filter_map(|(i, j, _)| Some(foci.get(i)?.distance(*foci.get(j)?)))
+            .fold(0.0, f32::max);
+        self.params.edges.reach(self.params.max_distance, longest)
+    }
+
+    fn simulate(&mut self, dt: f32) {
+        if let Some(audio) = &mut self.audio {
+            audio.tick(dt);
+            audio.modulate(&mut self.plooms, &self.params);
+        }
+        let swarmed = match &mut self.swarm {
+            Some(swarm) => swarm
+                .step(&mut self.plooms, &self.params, dt)
+                // back to stepping one ploom at a time
+                .map_err(|e| eprintln!("{}, stepping the plooms one by one", e))
+                .is_ok(),
+            None => false,
+        };
+        if !swarmed {
+            self.swarm = None;
+            for ploom in self.plooms.iter_mut() {
+                ploom.step(&self.params, dt, &mut self.rng);
+            }
+        }
+        self.mouse.pull_circles(&mut self.plooms, dt);
+    }
+
+    fn update_osc(&mut self) {
+        let triggers = match &mut self.osc {
+            Some(osc) => osc.poll_params(&mut self.params),
+            None => return,
+        };
+        for trigger in triggers {
+            match trigger.as_str() {
+                // every ploom resets on its next tick
+                "reset" => self.plooms.iter_mut().for_each(|p| p.sigma = 0.0),
+                "respawn" => self.plooms = spawn_plooms(&self.params, self.seed, &mut self.rng),
+                "randomize" => randomize(&mut self.params, &mut self.rng),
+                other => eprintln!("osc: no trigger {:?}", other),
+            }
+        }
+    }
+
+    fn update_panel(&mut self, since_start: Duration) {
+        let action = match &mut self.egui {
+            Some(egui) => {
+                egui.set_elapsed_time(since_start);
+                let ctx = egui.begin_frame();
+                ploom_panel(&ctx, &mut self.params)
+            }
+            None => return,
+        };
+        match action {
+            Some(PanelAction::Randomize) => randomize(&mut self.params, &mut self.rng),
+            Some(PanelAction::Reset) => {
+                // a preset that doesn't parse leaves the sketch as it is
+                if let Some(params) = self.preset.reload_or(S::params()) {
+                    self.params = params.with_args();
+                    self.plooms = spawn_plooms(&self.params, self.seed, &mut self.rng);
+                }
+            }
+            None => {}
+        }
+    }
+
+    fn record_svg(&self, nth: u64, time: f32) {
+        if let Some(svg) = &self.svg {
+            svg.record(nth, |recorder| S::paint(recorder, self, nth, time));
+        }
+    }
+}
+
+fn spawn_plooms(params: &PloomParams, seed: u64, rng: &mut StdRng) -> Vec<Ploom> {
+    let mut plooms = Ploom::spawn_random_plooms(params.num_plooms, params, rng);
+    paths_from_args(&mut plooms, params.window_size, rng);
+    noise_from_args(&mut plooms, seed);
+    plooms
+}
+
+fn model<S: Sketch>(app: &App) -> Harness<S> {
+    let mut model = Harness::new(seed_from_args());
+    let window_id = app
+        .new_window()
+        .size(model.params.window_size, model.params.window_size)
+        .view(view::<S>)
+        .raw_event(raw_window_event::<S>)
+        .key_released(key_released::<S>)
+        .mouse_pressed(mouse_pressed::<S>)
+        .mouse_released(mouse_released::<S>)
+        .build()
+        .unwrap();
+    model.egui = Some(Egui::from_window(&app.window(window_id).unwrap()));
+    model.hdr = AccumWindow::from_args(app, model.params.window_size).map(RefCell::new);
+    model
+}
+
+fn update<S: Sketch>(app: &App, model: &mut Harness<S>, update: Update) {
+    if let Some(params) = model.preset.poll() {
+        // the window stays, the flags still win over the file
+        model.params = PloomParams {
+            window_size: model.params.window_size,
+            ..params
+        }
+        .with_args();
+    }
+    model.update_osc();
+    model.update_panel(update.since_start);
+    Ploom::resize_plooms(&mut model.plooms, &model.params, &mut model.rng);
+    let cursor = app.mouse.position();
+    model.mouse.at = app.mouse.window.map(|_| cursor);
+    for _ in 0..model.clock.advance(update.since_last.as_secs_f64()) {
+        model.gesture.sample(cursor);
+        model.step();
+    }
+}
+
+fn view<S: Sketch>(app: &App, model: &Harness<S>, frame: Frame) {
+    match &model.hdr {
+        Some(hdr) => hdr.borrow_mut().view(app, &frame, |accum| {
+            S::paint(accum, model, frame.nth(), app.time)
+        }),
+        None => {
+            let mut draw = app.draw();
+            S::paint(&mut draw, model, frame.nth(), app.time);
+            draw.to_frame(app, &frame).unwrap();
+        }
+    }
+    if let Some(egui) = &model.egui {
+        egui.draw_to_frame(&frame).unwrap();
+    }
+    model.record_svg(frame.nth(), app.time);
+}
+
+fn raw_window_event<S: Sketch>(
+    _app: &App,
+    model: &mut Harness<S>,
+    event: &nannou::winit::event::WindowEvent,
+) {
+    if let Some(egui) = &mut model.egui {
+        egui.handle_raw_event(event);
+    }
+}
+
+fn key_released<S: Sketch>(app: &App, model: &mut Harness<S>, key: Key) {
+    match key {
+        Key::S => {
+            let name = capture_name(&app.exe_name().unwrap(), model.seed, app.elapsed_frames());
+            app.main_window().capture_frame(name);
+        }
+        // write the running parameters to the preset file
+        Key::W => model.preset.save(&model.params),
+        // cursor pulls circles in, pushes them away, or leaves them be
+        Key::C => model.mouse.pull = model.mouse.pull.next(),
+        // radius, delaunay, mst, knn, rng
+        Key::E => model.params.edges = model.params.edges.next(),
+        // record a mouse gesture, the plooms follow it once G is pressed again
+        Key::G => {
+            if let Some(path) = model.gesture.toggle(model.clock.dt()) {
+                follow(&mut model.plooms, &path);
+            }
+        }
+        _other_key => {}
+    }
+}
+
+fn mouse_pressed<S: Sketch>(app: &App, model: &mut Harness<S>, button: MouseButton) {
+    // clicks on the panel are the panel's
+    if model
+        .egui
+        .as_ref()
+        .is_some_and(|egui| egui.ctx().is_pointer_over_area())
+    {
+        return;
+    }
+    let at = app.mouse.position();
+    match button {
+        MouseButton::Left => model.mouse.press(at),
+        MouseButton::Right => {
+            if let Some(i) = nearest(&model.plooms, at) {
+                model.remove_ploom(i);
+            }
+        }
+        _other_button => {}
+    }
+}
+
+fn mouse_released<S: Sketch>(app: &App, model: &mut Harness<S>, button: MouseButton) {
+    if button != MouseButton::Left {
+        return;
+    }
+    match model.mouse.release(app.mouse.position()) {
+        Some(Stroke::Click(at)) => {
+            spawn_at(&mut model.plooms, &mut model.params, at, &mut model.rng)
+        }
+        Some(Stroke::Drag(from, to)) => steer_nearest(&mut model.plooms, from, to),
+        None => {}
+    }
+}
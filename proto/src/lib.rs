@@ -0,0 +1,31 @@
+// ploom = points(point) + N(mu,sigma*I)
+pub mod accum;
+pub mod audio;
+pub mod boundary;
+pub mod burst;
+pub mod camera;
+pub mod canvas;
+pub mod cli;
+pub mod clock;
+pub mod covariance;
+pub mod event;
+pub mod falloff;
+pub mod focus;
+pub mod graph;
+pub mod harness;
+pub mod mouse;
+pub mod noise;
+pub mod offline;
+pub mod osc;
+pub mod paint;
+pub mod palette;
+pub mod panel;
+pub mod ploom;
+pub mod ploom3;
+pub mod plotter;
+pub mod preset;
+pub mod record;
+pub mod rng;
+pub mod spatial;
+pub mod svg;
+pub mod swarm;
@@ -0,0 +1,176 @@
+use crate::cli::arg;
+use crate::event::Lifecycle;
+use crate::focus::FocusPath;
+use crate::ploom::{Ploom, PloomParams};
+use nannou::prelude::*;
+use rand::Rng;
+use std::fmt;
+use std::str::FromStr;
+
+// a press that moves less than this is a click, px
+const CLICK: f32 = 4.0;
+
+/// What the cursor does to circles near it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Pull {
+    #[default]
+    Off,
+    Attract,
+    Repel,
+}
+
+impl Pull {
+    /// Off, attract, repel, off...
+    pub fn next(self) -> Pull {
+        match self {
+            Pull::Off => Pull::Attract,
+            Pull::Attract => Pull::Repel,
+            Pull::Repel => Pull::Off,
+        }
+    }
+}
+
+/// What a press and release of the left button turned out to be.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Stroke {
+    Click(Vec2),
+    Drag(Vec2, Vec2), // from, to
+}
+
+/// The cursor as an instrument: an optional force on circles, clicks that
+/// spawn plooms and drags that steer them.
+pub struct Mouse {
+    pub pull: Pull,
+    pub strength: f32,    // px per second at the cursor
+    pub radius: f32,      // px, nothing is pulled from further away
+    pub at: Option<Vec2>, // none until the cursor is over the window
+    press: Option<Vec2>,
+}
+
+impl Mouse {
+    /// `--cursor off|attract|repel`, C cycles through them in the window.
+    pub fn from_args() -> Mouse {
+        Mouse {
+            pull: arg("--cursor").unwrap_or_default(),
+            ..Mouse::default()
+        }
+    }
+
+    /// Moves every circle within `radius` of the cursor towards it (or away),
+    /// strongest at the cursor and fading out linearly to the edge.
+    pub fn pull_circles(&self, plooms: &mut [Ploom], dt: f32) {
+        let at = match (self.pull, self.at) {
+            (Pull::Off, _) | (_, None) => return,
+            (_, Some(at)) => at,
+        };
+        let sign = if self.pull == Pull::Attract {
+            1.0
+        } else {
+            -1.0
+        };
+        for circle in plooms.iter_mut().flat_map(|p| p.circles.iter_mut()) {
+            let d = at - circle.x;
+            let distance = d.length();
+            if distance < self.radius && distance > 0.0 {
+                let step = self.strength * dt * (1.0 - distance / self.radius);
+                // attraction stops at the cursor rather than overshooting it
+                circle.x += d / distance * sign * step.min(distance);
+            }
+        }
+    }
+
+    pub fn press(&mut self, at: Vec2) {
+        self.press = Some(at);
+    }
+
+    /// `None` for a release without a press, e.g. one that started on the panel.
+    pub fn release(&mut self, at: Vec2) -> Option<Stroke> {
+        let from = self.press.take()?;
+        if from.distance(at) < CLICK {
+            Some(Stroke::Click(at))
+        } else {
+            Some(Stroke::Drag(from, at))
+        }
+    }
+}
+
+impl Default for Mouse {
+    fn default() -> Mouse {
+        Mouse {
+            pull: Pull::Off,
+            strength: 240.0,
+            radius: 150.0,
+            at: None,
+            press: None,
+        }
+    }
+}
+
+/// Adds a ploom centered on `at`, `params.num_plooms` follows so the panel
+/// and `resize_plooms` keep it. It takes after the last ploom, so on a
+/// closed `--path` it heads for that curve from `at`.
+pub fn spawn_at<R: Rng + ?Sized>(
+    plooms: &mut Vec<Ploom>,
+    params: &mut PloomParams,
+    at: Vec2,
+    rng: &mut R,
+) {
+    let mut ploom = Ploom::new(params, rng);
+    if let Some(other) = plooms.last() {
+        ploom.take_after(other);
+    }
+    ploom.focus = at;
+    ploom.last_focus = at;
+    for circle in &mut ploom.circles {
+        circle.x += at;
+        circle.last = circle.x;
+    }
+    ploom.events = vec![(Lifecycle::FocusSpawn, at)];
+    plooms.push(ploom);
+    params.num_plooms = plooms.len();
+}
+
+/// Index of the ploom whose focus is closest to `at`.
+pub fn nearest(plooms: &[Ploom], at: Vec2) -> Option<usize> {
+    plooms
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| {
+            let (a, b) = (a.focus.distance(at), b.focus.distance(at));
+            a.total_cmp(&b)
+        })
+        .map(|(i, _)| i)
+}
+
+/// Sets `v` of the ploom closest to `from` so its focus covers the drag
+/// every second, and switches it back to drifting.
+pub fn steer_nearest(plooms: &mut [Ploom], from: Vec2, to: Vec2) {
+    if let Some(i) = nearest(plooms, from) {
+        let ploom = &mut plooms[i];
+        ploom.v = to - from;
+        ploom.path = FocusPath::Drift;
+    }
+}
+
+impl fmt::Display for Pull {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Pull::Off => write!(f, "off"),
+            Pull::Attract => write!(f, "attract"),
+            Pull::Repel => write!(f, "repel"),
+        }
+    }
+}
+
+impl FromStr for Pull {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Pull, String> {
+        match s {
+            "off" => Ok(Pull::Off),
+            "attract" => Ok(Pull::Attract),
+            "repel" => Ok(Pull::Repel),
+            _ => Err(format!("expected off, attract or repel, got {:?}", s)),
+        }
+    }
+}
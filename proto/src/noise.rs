@@ -0,0 +1,198 @@
+use crate::cli::arg;
+use crate::covariance::Covariance;
+use crate::ploom::{Circle, Ploom, PloomParams};
+use nannou::noise::{Fbm, NoiseFn, OpenSimplex, Seedable};
+use nannou::prelude::*;
+use rand::distributions::{Distribution, Exp1};
+use rand::{Rng, RngCore};
+use std::f32::consts::PI;
+use std::ops::{Add, Mul, Sub};
+use std::sync::Arc;
+
+/// What a circle sees of its ploom during one step.
+pub struct Plume<'a> {
+    pub focus: Vec2,
+    pub covariance: Covariance,
+    pub t: f32,  // age of the ploom in seconds
+    pub dt: f32, // length of the step in seconds
+    pub params: &'a PloomParams,
+}
+
+/// `x` kicked by `dx` and pulled back towards `focus` by the fraction `pull`
+/// of its offset, the step `Gaussian`, `Levy` and `Drift` share. Generic so
+/// the 3D plooms of `ploom3` step the same way.
+pub fn kick<V>(x: V, focus: V, dx: V, pull: f32) -> V
+where
+    V: Copy + Add<Output = V> + Sub<Output = V> + Mul<f32, Output = V>,
+{
+    x + dx - (x - focus) * pull
+}
+
+/// Exact Ornstein–Uhlenbeck transition over a step of `dt` seconds with rate
+/// `theta` per second: the fraction of the offset from the focus that's left,
+/// and the variance of the noise per unit of plume variance.
+pub fn ou_transition(theta: f32, dt: f32) -> (f32, f32) {
+    let decay = (-theta * dt).exp();
+    let variance = if theta > 0.0 {
+        (1.0 - decay * decay) / (2.0 * theta)
+    } else {
+        dt
+    };
+    (decay, variance)
+}
+
+/// Stochastic model moving a circle around its focus, one per ploom.
+pub trait NoiseProcess: Send + Sync {
+    /// Position of `circle` after one step.
+    fn step(&self, circle: &Circle, plume: &Plume, rng: &mut dyn RngCore) -> Vec2;
+}
+
+/// The original model, N(0, sigma² dt) kicks divided by the radius plus a
+/// linear pull of `dampening` towards the focus.
+pub struct Gaussian;
+
+impl NoiseProcess for Gaussian {
+    fn step(&self, circle: &Circle, plume: &Plume, rng: &mut dyn RngCore) -> Vec2 {
+        let dx = plume.covariance.sample(rng) * plume.dt.sqrt() / circle.r;
+        kick(circle.x, plume.focus, dx, plume.params.pull(plume.dt))
+    }
+}
+
+/// dX = -θ (X - focus) dt + Σ^½ / r dW with θ from `dampening`, stepped with
+/// the exact transition density so large θ stays stable.
+pub struct OrnsteinUhlenbeck;
+
+impl NoiseProcess for OrnsteinUhlenbeck {
+    fn step(&self, circle: &Circle, plume: &Plume, rng: &mut dyn RngCore) -> Vec2 {
+        let (decay, variance) = ou_transition(plume.params.pull_rate(), plume.dt);
+        let dx = plume
+            .covariance
+            .scaled(variance / (circle.r * circle.r))
+            .sample(rng);
+        plume.focus + (circle.x - plume.focus) * decay + dx
+    }
+}
+
+/// Symmetric α-stable jumps shaped by the plume covariance, `alpha = 1` is
+/// Cauchy and `alpha = 2` the Gaussian model. Jumps scale with dt^(1/α) and
+/// are capped at the window size so a single outlier can't throw a circle to
+/// infinity.
+pub struct Levy {
+    pub alpha: f32,
+}
+
+impl Levy {
+    /// How far jumps reach over `dt` seconds, relative to a second.
+    pub fn scale(&self, dt: f32) -> f32 {
+        dt.powf(1.0 / self.alpha.clamp(0.1, 2.0))
+    }
+
+    /// Positive (α/2)-stable mixing variable, Kanter's representation.
+    pub fn mixing(&self, rng: &mut dyn RngCore) -> f32 {
+        let a = (self.alpha / 2.0).clamp(0.05, 1.0);
+        if a >= 1.0 {
+            return 1.0;
+        }
+        let u = rng.gen_range(0.0, PI);
+        let e: f64 = Exp1.sample(rng);
+        let e = e as f32;
+        (a * u).sin() / u.sin().powf(1.0 / a) * (((1.0 - a) * u).sin() / e).powf((1.0 - a) / a)
+    }
+}
+
+impl NoiseProcess for Levy {
+    fn step(&self, circle: &Circle, plume: &Plume, rng: &mut dyn RngCore) -> Vec2 {
+        let s = self.mixing(rng);
+        let dx = plume.covariance.sample(rng) * s.sqrt() * self.scale(plume.dt) / circle.r;
+        let dx = dx.clamp_length_max(plume.params.window_size as f32);
+        kick(circle.x, plume.focus, dx, plume.params.pull(plume.dt))
+    }
+}
+
+/// Circles ride a coherent flow field (Perlin, simplex...) that scrolls in
+/// time, with a bit of the Gaussian kick left as `jitter`.
+pub struct Drift<F> {
+    pub field: F,
+    pub scale: f32, // spatial frequency, per pixel
+    pub speed: f32, // temporal frequency, per second of the ploom's age
+    pub flow: f32,  // px per second per unit of sigma
+    pub jitter: f32,
+}
+
+impl Drift<Fbm> {
+    /// Fractal Perlin noise, `Perlin` itself is ambiguous in the `noise` re-export.
+    pub fn perlin(seed: u32) -> Drift<Fbm> {
+        Drift::new(Fbm::new().set_seed(seed))
+    }
+}
+
+impl Drift<OpenSimplex> {
+    pub fn simplex(seed: u32) -> Drift<OpenSimplex> {
+        Drift::new(OpenSimplex::new().set_seed(seed))
+    }
+}
+
+impl<F> Drift<F> {
+    pub fn new(field: F) -> Drift<F> {
+        Drift {
+            field,
+            scale: 0.004,
+            speed: 0.3,
+            flow: 7.75,
+            jitter: 0.25,
+        }
+    }
+}
+
+impl<F: NoiseFn<[f64; 3]> + Send + Sync> NoiseProcess for Drift<F> {
+    fn step(&self, circle: &Circle, plume: &Plume, rng: &mut dyn RngCore) -> Vec2 {
+        let p = circle.x * self.scale;
+        let angle =
+            self.field
+                .get([p.x as f64, p.y as f64, (plume.t * self.speed) as f64]) as f32
+                * TAU;
+        let c = plume.covariance;
+        let sigma = ((c.xx + c.yy) / 2.0).sqrt();
+        let flow = vec2(angle.cos(), angle.sin()) * sigma * self.flow * plume.dt / circle.r;
+        let dx = plume.covariance.sample(rng) * self.jitter * plume.dt.sqrt() / circle.r;
+        kick(
+            circle.x,
+            plume.focus,
+            flow + dx,
+            plume.params.pull(plume.dt),
+        )
+    }
+}
+
+/// `gaussian`, `ou`, `cauchy`, `levy:<alpha>`, `perlin` or `simplex`, the
+/// coherent fields are seeded with `seed`.
+pub fn from_name(name: &str, seed: u32) -> Arc<dyn NoiseProcess> {
+    match name.split_once(':') {
+        None if name == "gaussian" => Arc::new(Gaussian),
+        None if name == "ou" => Arc::new(OrnsteinUhlenbeck),
+        None if name == "cauchy" => Arc::new(Levy { alpha: 1.0 }),
+        None if name == "perlin" => Arc::new(Drift::perlin(seed)),
+        None if name == "simplex" => Arc::new(Drift::simplex(seed)),
+        Some(("levy", alpha)) => Arc::new(Levy {
+            alpha: alpha.parse().expect("levy:<alpha> expects a number"),
+        }),
+        _ => panic!(
+            "unknown noise {:?}, expected gaussian, ou, cauchy, levy:<alpha>, perlin or simplex",
+            name
+        ),
+    }
+}
+
+/// Reads `--noise <name>[,<name>...]` and hands the processes out to the
+/// plooms round robin.
+pub fn noise_from_args(plooms: &mut [Ploom], seed: u64) {
+    if let Some(names) = arg::<String>("--noise") {
+        let processes: Vec<Arc<dyn NoiseProcess>> = names
+            .split(',')
+            .map(|name| from_name(name, seed as u32))
+            .collect();
+        for (i, ploom) in plooms.iter_mut().enumerate() {
+            ploom.noise = processes[i % processes.len()].clone();
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use crate::accum::{Accum, Trails};
+use crate::cli::{arg, exe_name};
+use crate::clock::TICK_RATE;
+use std::fs;
+use std::path::PathBuf;
+
+// seconds handed to `advance` at a time: a tick's worth, so a slow frame
+// rate never runs into the `Clock`'s stall cap and loses time
+const SLICE: f64 = 1.0 / TICK_RATE as f64;
+
+/// Headless render settings, read from
+/// `--offline <frames> [--fps <fps>] [--out <dir>]`, plus the `Trails`
+/// flags.
+pub struct Offline {
+    pub frames: u64,
+    pub fps: f32,
+    pub out_dir: PathBuf,
+    pub trails: Trails,
+}
+
+/// `--fps <fps>`, panics unless it's a positive number: a frame of no time
+/// would never finish rendering.
+pub fn fps_from_args() -> Option<f32> {
+    arg("--fps").map(|fps| check_fps(fps).unwrap_or_else(|e| panic!("bad --fps: {}", e)))
+}
+
+pub fn check_fps(fps: f32) -> Result<f32, String> {
+    if fps > 0.0 && fps.is_finite() {
+        Ok(fps)
+    } else {
+        Err(format!("expected a positive frame rate, got {}", fps))
+    }
+}
+
+impl Offline {
+    /// `None` unless `--offline` was passed, in which case the sketch should
+    /// skip the window entirely.
+    pub fn from_args() -> Option<Offline> {
+        let frames: u64 = arg("--offline")?;
+        Some(Offline {
+            frames,
+            fps: fps_from_args().unwrap_or(60.0),
+            out_dir: arg("--out").unwrap_or_else(|| PathBuf::from("renders")),
+            trails: Trails::from_args().unwrap_or_default(),
+        })
+    }
+
+    /// Seconds between two frames.
+    pub fn dt(&self) -> f32 {
+        1.0 / self.fps
+    }
+
+    /// Advances `model` by one frame of `dt` seconds, a tick's worth at a
+    /// time, and paints each frame into a persistent accumulation buffer (so
+    /// fades leave trails just like the window does), writing
+    /// `<out>/<example>_seed<seed>/00000.png` onwards.
+    pub fn run<M, S, P>(&self, seed: u64, size: u32, model: &mut M, mut advance: S, mut paint: P)
+    where
+        S: FnMut(&mut M, f64),
+        P: FnMut(&mut Accum, &M, u64, f32),
+    {
+        let dir = self.out_dir.join(format!("{}_seed{}", exe_name(), seed));
+        fs::create_dir_all(&dir).unwrap();
+        let mut accum = Accum::new(size, size, self.trails);
+        for nth in 0..self.frames {
+            let mut left = 1.0 / self.fps as f64;
+            while left > 0.0 {
+                let seconds = SLICE.min(left);
+                advance(model, seconds);
+                left -= seconds;
+            }
+            accum.begin(self.dt());
+            paint(&mut accum, model, nth, nth as f32 * self.dt());
+            accum.save(dir.join(format!("{:05}.png", nth))).unwrap();
+        }
+        println!("wrote {} frames to {}", self.frames, dir.display());
+    }
+}
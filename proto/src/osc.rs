@@ -0,0 +1,362 @@
+use crate::cli::arg;
+use crate::preset::load;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr, UdpSocket};
+use std::path::{Path, PathBuf};
+
+/// Port the listener binds when the mapping doesn't say.
+pub const PORT: u16 = 9000;
+
+// largest datagram `poll` reads
+const MAX_PACKET: usize = 65536;
+
+/// An OSC argument. `h` and `d` come in as `Int` and `Float`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Arg {
+    Int(i32),
+    Float(f32),
+    Str(String),
+    Bool(bool),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Message {
+    pub addr: String,
+    pub args: Vec<Arg>,
+}
+
+impl Message {
+    pub fn new(addr: &str, args: Vec<Arg>) -> Message {
+        Message {
+            addr: addr.to_string(),
+            args,
+        }
+    }
+
+    /// First argument as a number, controllers send faders as `f` or `i`.
+    pub fn value(&self) -> Option<f32> {
+        match self.args.first()? {
+            Arg::Int(i) => Some(*i as f32),
+            Arg::Float(f) => Some(*f),
+            Arg::Bool(b) => Some(*b as u8 as f32),
+            Arg::Str(_) => None,
+        }
+    }
+
+    /// OSC 1.0 packet with a single message.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        put_str(&mut out, &self.addr);
+        let mut tags = String::from(",");
+        for arg in &self.args {
+            tags.push(match arg {
+                Arg::Int(_) => 'i',
+                Arg::Float(_) => 'f',
+                Arg::Str(_) => 's',
+                Arg::Bool(true) => 'T',
+                Arg::Bool(false) => 'F',
+            });
+        }
+        put_str(&mut out, &tags);
+        for arg in &self.args {
+            match arg {
+                Arg::Int(i) => out.extend(i.to_be_bytes()),
+                Arg::Float(f) => out.extend(f.to_be_bytes()),
+                Arg::Str(s) => put_str(&mut out, s),
+                Arg::Bool(_) => {}
+            }
+        }
+        out
+    }
+}
+
+/// Every message in a packet, bundles are flattened in order.
+pub fn decode(packet: &[u8]) -> Result<Vec<Message>, String> {
+    let mut messages = Vec::new();
+    decode_into(packet, &mut messages)?;
+    Ok(messages)
+}
+
+fn decode_into(packet: &[u8], messages: &mut Vec<Message>) -> Result<(), String> {
+    let mut r = Reader { packet, at: 0 };
+    let addr = r.str()?;
+    if addr == "#bundle" {
+        r.take(8)?; // time tag, everything applies on arrival
+        while r.at < packet.len() {
+            let size = r.i32()?;
+            let element = r.take(usize::try_from(size).map_err(|_| "negative size")?)?;
+            decode_into(element, messages)?;
+        }
+        return Ok(());
+    }
+    if !addr.starts_with('/') {
+        return Err(format!("not an OSC address: {:?}", addr));
+    }
+    let tags = if r.at < packet.len() {
+        r.str()?
+    } else {
+        String::from(",")
+    };
+    let tags = tags
+        .strip_prefix(',')
+        .ok_or_else(|| format!("bad type tags {:?}", tags))?;
+    let mut args = Vec::new();
+    for tag in tags.chars() {
+        args.push(match tag {
+            'i' => Arg::Int(r.i32()?),
+            'f' => Arg::Float(f32::from_be_bytes(r.array()?)),
+            'h' => Arg::Int(i64::from_be_bytes(r.array()?) as i32),
+            'd' => Arg::Float(f64::from_be_bytes(r.array()?) as f32),
+            's' | 'S' => Arg::Str(r.str()?),
+            'T' => Arg::Bool(true),
+            'F' => Arg::Bool(false),
+            'N' | 'I' => continue,
+            'b' => {
+                let size = r.i32()?.max(0) as usize;
+                r.take(pad(size))?;
+                continue;
+            }
+            other => return Err(format!("{}: unsupported type tag {:?}", addr, other)),
+        });
+    }
+    messages.push(Message { addr, args });
+    Ok(())
+}
+
+struct Reader<'a> {
+    packet: &'a [u8],
+    at: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], String> {
+        let bytes = self
+            .packet
+            .get(self.at..self.at + n)
+            .ok_or("packet too short")?;
+        self.at += n;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    fn i32(&mut self) -> Result<i32, String> {
+        Ok(i32::from_be_bytes(self.array()?))
+    }
+
+    /// NUL terminated, padded to 4 bytes.
+    fn str(&mut self) -> Result<String, String> {
+        let rest = &self.packet[self.at.min(self.packet.len())..];
+        let len = rest
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or("unterminated string")?;
+        let s = String::from_utf8_lossy(&rest[..len]).into_owned();
+        self.take(pad(len + 1).min(rest.len()))?;
+        Ok(s)
+    }
+}
+
+fn pad(n: usize) -> usize {
+    (n + 3) & !3
+}
+
+fn put_str(out: &mut Vec<u8>, s: &str) {
+    out.extend(s.as_bytes());
+    out.resize(out.len() + pad(s.len() + 1) - s.len(), 0);
+}
+
+/// Where an address goes: a parameter by name, the message's value taken
+/// as is, or mapped from `[0, 1]` onto `[min, max]` for faders.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Target {
+    Param(String),
+    Scaled { param: String, min: f32, max: f32 },
+}
+
+/// A mapping file, e.g. `osc/ploom.toml`:
+///
+/// ```toml
+/// port = 9000
+/// [params]
+/// "/ploom/decay" = "decay"
+/// "/ploom/spread" = { param = "ploom", min = 5.0, max = 60.0 }
+/// [triggers]
+/// "/ploom/reset" = "reset"
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OscMap {
+    pub port: Option<u16>,
+    pub params: BTreeMap<String, Target>,
+    pub triggers: BTreeMap<String, String>,
+}
+
+/// What a message asks the sketch to do.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Control {
+    Set(String, f32),
+    Trigger(String),
+}
+
+impl OscMap {
+    pub fn load(path: &Path) -> Result<OscMap, String> {
+        load(path)
+    }
+
+    /// `None` for addresses the mapping doesn't know or params sent without a
+    /// number.
+    pub fn control(&self, message: &Message) -> Option<Control> {
+        if let Some(trigger) = self.triggers.get(&message.addr) {
+            return Some(Control::Trigger(trigger.clone()));
+        }
+        let value = message.value()?;
+        match self.params.get(&message.addr)? {
+            Target::Param(name) => Some(Control::Set(name.clone(), value)),
+            Target::Scaled { param, min, max } => {
+                Some(Control::Set(param.clone(), min + (max - min) * value))
+            }
+        }
+    }
+}
+
+/// A bare mapping name is `osc/<name>.toml`, anything else a path.
+pub fn resolve(name: &str) -> PathBuf {
+    let path = PathBuf::from(name);
+    if path.extension().is_some() || path.components().count() > 1 {
+        path
+    } else {
+        Path::new("osc").join(name).with_extension("toml")
+    }
+}
+
+/// `params` with the field `name` set to `value`, through the same serde
+/// representation presets use. Integer fields are rounded, booleans are on
+/// above 0.5.
+pub fn set<T: Serialize + DeserializeOwned>(
+    params: &T,
+    name: &str,
+    value: f32,
+) -> Result<T, String> {
+    let mut table = toml::Table::try_from(params).map_err(|e| e.to_string())?;
+    let field = table
+        .get_mut(name)
+        .ok_or_else(|| format!("no parameter {:?}", name))?;
+    *field = match field {
+        toml::Value::Integer(_) => toml::Value::Integer(value.round().max(0.0) as i64),
+        toml::Value::Float(_) => toml::Value::Float(value as f64),
+        toml::Value::Boolean(_) => toml::Value::Boolean(value > 0.5),
+        _ => return Err(format!("{:?} is not a number", name)),
+    };
+    table.try_into().map_err(|e| format!("{}: {}", name, e))
+}
+
+/// Non-blocking UDP socket turning OSC packets into `Control`s.
+pub struct OscListener {
+    socket: UdpSocket,
+    pub map: OscMap,
+    unmapped: BTreeSet<String>, // addresses already warned about
+}
+
+impl OscListener {
+    /// Listens on every interface at `port`, 0 picks a free one.
+    pub fn bind(map: OscMap, port: u16) -> io::Result<OscListener> {
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, port))?;
+        socket.set_nonblocking(true)?;
+        Ok(OscListener {
+            socket,
+            map,
+            unmapped: BTreeSet::new(),
+        })
+    }
+
+    /// `--osc <mapping>`, on the mapping's port unless `--osc-port` is given.
+    pub fn from_args() -> Option<OscListener> {
+        let name: String = arg("--osc")?;
+        let path = resolve(&name);
+        let map = OscMap::load(&path).unwrap_or_else(|e| panic!("{}", e));
+        let port = arg("--osc-port").or(map.port).unwrap_or(PORT);
+        let listener =
+            OscListener::bind(map, port).unwrap_or_else(|e| panic!("osc port {}: {}", port, e));
+        println!("osc: {} on port {}", path.display(), port);
+        Some(listener)
+    }
+
+    pub fn port(&self) -> u16 {
+        self.socket.local_addr().map(|a| a.port()).unwrap_or(0)
+    }
+
+    /// Addresses that arrived without a mapping.
+    pub fn unmapped(&self) -> &BTreeSet<String> {
+        &self.unmapped
+    }
+
+    /// Everything that arrived since the last poll, in order. Bad packets
+    /// are printed and dropped, unmapped addresses are dropped and printed
+    /// the first time only, a controller may send them every frame.
+    pub fn poll(&mut self) -> Vec<Control> {
+        let mut controls = Vec::new();
+        let mut buffer = vec![0; MAX_PACKET];
+        while let Ok((n, _)) = self.socket.recv_from(&mut buffer) {
+            match decode(&buffer[..n]) {
+                Ok(messages) => {
+                    for message in messages {
+                        match self.map.control(&message) {
+                            Some(control) => controls.push(control),
+                            None => {
+                                if self.unmapped.insert(message.addr.clone()) {
+                                    eprintln!("osc: unmapped {:?}", message);
+                                }
+                            }
+                        }
+                    }
+                }
+                Err(e) => eprintln!("osc: {}", e),
+            }
+        }
+        controls
+    }
+
+    /// Polls, applies every `Set` to `params` and returns the triggers.
+    pub fn poll_params<T: Serialize + DeserializeOwned>(&mut self, params: &mut T) -> Vec<String> {
+        let mut triggers = Vec::new();
+        for control in self.poll() {
+            match control {
+                Control::Set(name, value) => match set(params, &name, value) {
+                    Ok(p) => *params = p,
+                    Err(e) => eprintln!("osc: {}", e),
+                },
+                Control::Trigger(trigger) => triggers.push(trigger),
+            }
+        }
+        triggers
+    }
+}
+
+/// Sends to a listener on this machine, for trying mappings without a
+/// controller.
+pub struct OscSender {
+    socket: UdpSocket,
+    target: SocketAddr,
+}
+
+impl OscSender {
+    pub fn new(port: u16) -> io::Result<OscSender> {
+        Ok(OscSender {
+            socket: UdpSocket::bind((Ipv4Addr::LOCALHOST, 0))?,
+            target: SocketAddr::from((Ipv4Addr::LOCALHOST, port)),
+        })
+    }
+
+    pub fn send(&self, message: &Message) -> io::Result<()> {
+        self.socket
+            .send_to(&message.encode(), self.target)
+            .map(|_| ())
+    }
+}
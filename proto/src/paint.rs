@@ -0,0 +1,46 @@
+use nannou::color::IntoLinSrgba;
+use nannou::prelude::*;
+
+/// The handful of shapes the sketches draw, so the same drawing code can target
+/// a live nannou `Draw`, the offline `Canvas` or an `Accum`.
+pub trait Painter {
+    fn background<C: IntoLinSrgba<f32>>(&mut self, color: C);
+    fn rect<C: IntoLinSrgba<f32>>(&mut self, xy: Vec2, wh: Vec2, color: C);
+    fn ellipse<C: IntoLinSrgba<f32>>(&mut self, xy: Vec2, radius: f32, color: C);
+    fn line<C: IntoLinSrgba<f32>>(&mut self, start: Vec2, end: Vec2, weight: f32, color: C);
+    fn quad<C: IntoLinSrgba<f32>>(&mut self, points: [Vec2; 4], color: C);
+
+    /// Fades what was drawn so far towards `color` by its alpha, the trail
+    /// effect. A translucent window sized rect unless the target knows better.
+    fn fade<C: IntoLinSrgba<f32>>(&mut self, wh: Vec2, color: C) {
+        self.rect(Vec2::ZERO, wh, color);
+    }
+}
+
+impl Painter for Draw {
+    fn background<C: IntoLinSrgba<f32>>(&mut self, color: C) {
+        Draw::background(self).color(color);
+    }
+
+    fn rect<C: IntoLinSrgba<f32>>(&mut self, xy: Vec2, wh: Vec2, color: C) {
+        Draw::rect(self).xy(xy).wh(wh).color(color);
+    }
+
+    fn ellipse<C: IntoLinSrgba<f32>>(&mut self, xy: Vec2, radius: f32, color: C) {
+        Draw::ellipse(self).xy(xy).radius(radius).color(color);
+    }
+
+    fn line<C: IntoLinSrgba<f32>>(&mut self, start: Vec2, end: Vec2, weight: f32, color: C) {
+        Draw::line(self)
+            .start(start)
+            .end(end)
+            .weight(weight)
+            .color(color);
+    }
+
+    fn quad<C: IntoLinSrgba<f32>>(&mut self, points: [Vec2; 4], color: C) {
+        Draw::quad(self)
+            .points(points[0], points[1], points[2], points[3])
+            .color(color);
+    }
+}
@@ -0,0 +1,222 @@
+use crate::cli::arg;
+use crate::clock::TICK_RATE;
+use crate::ploom::{Ploom, PloomParams};
+use crate::preset::load;
+use nannou::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+// seconds for `ColorBy::Age` to run through the palette once
+const AGE_PERIOD: f32 = 10.0;
+
+/// Inigo Quilez's `a + b cos(2π(c t + d))`, one row per channel, see
+/// https://iquilezles.org/articles/palettes/
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Cosine {
+    pub a: [f32; 3],
+    pub b: [f32; 3],
+    pub c: [f32; 3],
+    pub d: [f32; 3],
+}
+
+impl Cosine {
+    pub fn at(&self, t: f32) -> [f32; 3] {
+        let mut rgb = [0.0; 3];
+        for (i, x) in rgb.iter_mut().enumerate() {
+            *x = self.a[i] + self.b[i] * (TAU * (self.c[i] * t + self.d[i])).cos();
+        }
+        rgb
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Stop {
+    pub t: f32,
+    pub color: [f32; 3],
+}
+
+/// Maps `t` in `[0, 1]` to a color. Palette files are TOML (or RON) with a
+/// single `cosine`, `gradient` or `swatch` entry.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Palette {
+    Cosine(Cosine),
+    /// Linear between stops, which are sorted by `t` (`checked` sorts them).
+    Gradient(Vec<Stop>),
+    /// Discrete colors, `t` picks one of `n` equal bins.
+    Swatch(Vec<[f32; 3]>),
+}
+
+impl Palette {
+    pub fn swatch(colors: &[Rgba]) -> Palette {
+        Palette::Swatch(colors.iter().map(|c| [c.red, c.green, c.blue]).collect())
+    }
+
+    /// One of the built in cosine palettes, or a palette file. A bare name
+    /// that isn't built in is `palettes/<name>.toml`.
+    pub fn named(name: &str) -> Result<Palette, String> {
+        if let Some(palette) = builtin(name) {
+            return Ok(palette);
+        }
+        let mut path = PathBuf::from(name);
+        if path.extension().is_none() && path.components().count() == 1 {
+            path = PathBuf::from("palettes").join(name).with_extension("toml");
+        }
+        load::<Palette>(&path)?
+            .checked()
+            .map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// The palette with gradient stops sorted by `t`, or an error when there
+    /// are no colors to pick from.
+    pub fn checked(self) -> Result<Palette, String> {
+        match self {
+            Palette::Gradient(stops) if stops.is_empty() => Err("empty gradient".to_string()),
+            Palette::Swatch(colors) if colors.is_empty() => Err("empty swatch".to_string()),
+            Palette::Gradient(mut stops) => {
+                if stops.iter().any(|s| !s.t.is_finite()) {
+                    return Err("gradient stop without a finite t".to_string());
+                }
+                stops.sort_by(|a, b| a.t.total_cmp(&b.t));
+                Ok(Palette::Gradient(stops))
+            }
+            palette => Ok(palette),
+        }
+    }
+
+    /// `--palette <name>`, or `default`.
+    pub fn from_args(default: Palette) -> Palette {
+        match arg::<String>("--palette") {
+            Some(name) => Palette::named(&name).unwrap_or_else(|e| panic!("bad --palette {}", e)),
+            None => default,
+        }
+    }
+
+    /// Color at `t`, black for a palette without colors.
+    pub fn rgb(&self, t: f32) -> [f32; 3] {
+        let t = t.clamp(0.0, 1.0);
+        let rgb = match self {
+            Palette::Cosine(cosine) => cosine.at(t),
+            Palette::Gradient(stops) if stops.is_empty() => [0.0; 3],
+            Palette::Swatch(colors) if colors.is_empty() => [0.0; 3],
+            Palette::Gradient(stops) => gradient(stops, t),
+            Palette::Swatch(colors) => {
+                let i = (t * colors.len() as f32) as usize;
+                colors[i.min(colors.len() - 1)]
+            }
+        };
+        rgb.map(|x| x.clamp(0.0, 1.0))
+    }
+
+    pub fn rgba(&self, t: f32, alpha: f32) -> Rgba {
+        let [r, g, b] = self.rgb(t);
+        Rgba::new(r, g, b, alpha)
+    }
+}
+
+fn gradient(stops: &[Stop], t: f32) -> [f32; 3] {
+    let next = stops.iter().position(|s| s.t > t).unwrap_or(stops.len());
+    if next == 0 {
+        return stops[0].color;
+    }
+    if next == stops.len() {
+        return stops[next - 1].color;
+    }
+    let (from, to) = (&stops[next - 1], &stops[next]);
+    let k = (t - from.t) / (to.t - from.t);
+    let mut rgb = from.color;
+    for (i, x) in rgb.iter_mut().enumerate() {
+        *x += (to.color[i] - *x) * k;
+    }
+    rgb
+}
+
+/// The palettes from the article, named by what they look like.
+pub fn builtin(name: &str) -> Option<Palette> {
+    let half = [0.5, 0.5, 0.5];
+    let (a, b, c, d) = match name {
+        "rainbow" => (half, half, [1.0, 1.0, 1.0], [0.0, 0.33, 0.67]),
+        "cool" => (half, half, [1.0, 1.0, 1.0], [0.0, 0.1, 0.2]),
+        "warm" => (half, half, [1.0, 1.0, 1.0], [0.3, 0.2, 0.2]),
+        "earth" => (half, half, [1.0, 1.0, 0.5], [0.8, 0.9, 0.3]),
+        "sunset" => (half, half, [1.0, 0.7, 0.4], [0.0, 0.15, 0.2]),
+        "candy" => (half, half, [2.0, 1.0, 0.0], [0.5, 0.2, 0.25]),
+        "rust" => (
+            [0.8, 0.5, 0.4],
+            [0.2, 0.4, 0.2],
+            [2.0, 1.0, 1.0],
+            [0.0, 0.25, 0.25],
+        ),
+        _ => return None,
+    };
+    Some(Palette::Cosine(Cosine { a, b, c, d }))
+}
+
+/// What picks a circle's place in the palette.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ColorBy {
+    /// Which circle of its ploom it is, the old `colors[i % colors.len()]`.
+    #[default]
+    Circle,
+    /// Which ploom it belongs to.
+    Index,
+    /// How far the ploom's sigma has decayed, log scaled from the initial
+    /// `ploom` down to `threshold`.
+    Sigma,
+    /// How far the circle moved last tick, relative to the ploom scale.
+    Speed,
+    /// Time since the ploom spawned, cycling every `AGE_PERIOD` seconds.
+    Age,
+}
+
+impl ColorBy {
+    /// `--color-by circle|index|sigma|speed|age`.
+    pub fn from_args() -> ColorBy {
+        arg("--color-by").unwrap_or_default()
+    }
+
+    /// Palette position of circle `c` of ploom `i`, in `[0, 1]`. Discrete
+    /// choices land in the middle of their bin.
+    pub fn t(&self, plooms: &[Ploom], i: usize, c: usize, params: &PloomParams) -> f32 {
+        let ploom = &plooms[i];
+        match self {
+            ColorBy::Circle => (c as f32 + 0.5) / ploom.circles.len() as f32,
+            ColorBy::Index => (i as f32 + 0.5) / plooms.len() as f32,
+            ColorBy::Sigma => {
+                let range = (params.ploom / params.threshold).ln();
+                if range > 0.0 {
+                    1.0 - (ploom.sigma / params.threshold).ln() / range
+                } else {
+                    0.0 // nothing to decay through
+                }
+            }
+            ColorBy::Speed => {
+                // against a tick's worth of the initial kicks
+                let circle = &ploom.circles[c];
+                let kick = params.ploom / TICK_RATE.sqrt();
+                1.0 - (-circle.x.distance(circle.last) / kick).exp()
+            }
+            ColorBy::Age => (ploom.t / AGE_PERIOD).fract(),
+        }
+        .clamp(0.0, 1.0)
+    }
+}
+
+impl FromStr for ColorBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<ColorBy, String> {
+        match s {
+            "circle" => Ok(ColorBy::Circle),
+            "index" => Ok(ColorBy::Index),
+            "sigma" => Ok(ColorBy::Sigma),
+            "speed" => Ok(ColorBy::Speed),
+            "age" => Ok(ColorBy::Age),
+            _ => Err(format!(
+                "expected circle, index, sigma, speed or age, got {:?}",
+                s
+            )),
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use crate::boundary::Boundary;
+use crate::falloff::Falloff;
+use crate::graph::EdgeMode;
+use crate::ploom::PloomParams;
+use nannou_egui::egui::{self, CtxRef};
+use rand::Rng;
+use std::ops::RangeInclusive;
+
+// slider ranges, also what `randomize` draws from
+const PLOOM: RangeInclusive<f32> = 5.0..=800.0;
+const DECAY: RangeInclusive<f32> = 0.05..=0.95;
+const THRESHOLD: RangeInclusive<f32> = 0.1..=40.0;
+const DAMPENING: RangeInclusive<f32> = 0.05..=1.0;
+const ALPHA: RangeInclusive<f32> = 0.0..=1.0;
+const MAX_DISTANCE: RangeInclusive<f32> = 0.0..=400.0;
+const NUM_PLOOMS: RangeInclusive<usize> = 1..=1000;
+const NUM_CIRCLES: RangeInclusive<usize> = 1..=20;
+const BOUNDARIES: [Boundary; 5] = [
+    Boundary::Open,
+    Boundary::Wrap,
+    Boundary::Bounce,
+    Boundary::Attract(0.5),
+    Boundary::Respawn,
+];
+const EDGE_MODES: [EdgeMode; 5] = [
+    EdgeMode::Radius,
+    EdgeMode::Delaunay,
+    EdgeMode::Mst,
+    EdgeMode::Knn(3),
+    EdgeMode::Relative,
+];
+const EDGE_FADE: RangeInclusive<f32> = 0.0..=3.0;
+const FALLOFFS: [Falloff; 4] = [
+    Falloff::Step,
+    Falloff::Linear,
+    Falloff::Smooth,
+    Falloff::Power(2.0),
+];
+
+/// Buttons pressed in the panel this frame.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PanelAction {
+    Randomize,
+    Reset,
+}
+
+/// Sliders for the live tweakable `PloomParams`. Count changes take effect
+/// through `ploom::resize`, the buttons are up to the sketch.
+pub fn ploom_panel(ctx: &CtxRef, params: &mut PloomParams) -> Option<PanelAction> {
+    let mut action = None;
+    egui::Window::new("ploom").show(ctx, |ui| {
+        ui.add(egui::Slider::new(&mut params.ploom, PLOOM).text("scale (initial sigma)"));
+        ui.add(egui::Slider::new(&mut params.decay, DECAY).text("decay per second"));
+        ui.add(
+            egui::Slider::new(&mut params.threshold, THRESHOLD)
+                .logarithmic(true)
+                .text("threshold"),
+        );
+        ui.add(
+            egui::Slider::new(&mut params.dampening, DAMPENING)
+                .logarithmic(true)
+                .text("dampening per second"),
+        );
+        ui.add(
+            egui::Slider::new(&mut params.alpha, ALPHA)
+                .logarithmic(true)
+                .text("alpha"),
+        );
+        ui.add(egui::Slider::new(&mut params.max_distance, MAX_DISTANCE).text("max distance"));
+        ui.add(
+            egui::Slider::new(&mut params.num_plooms, NUM_PLOOMS)
+                .logarithmic(true)
+                .text("plooms"),
+        );
+        ui.add(egui::Slider::new(&mut params.num_circles, NUM_CIRCLES).text("circles"));
+        egui::ComboBox::from_label("boundary")
+            .selected_text(params.boundary)
+            .show_ui(ui, |ui| {
+                for boundary in BOUNDARIES {
+                    ui.selectable_value(&mut params.boundary, boundary, boundary.to_string());
+                }
+            });
+        egui::ComboBox::from_label("edges")
+            .selected_text(params.edges)
+            .show_ui(ui, |ui| {
+                for edges in EDGE_MODES {
+                    ui.selectable_value(&mut params.edges, edges, edges.to_string());
+                }
+            });
+        ui.add(egui::Slider::new(&mut params.edge_fade, EDGE_FADE).text("edge fade (s)"));
+        egui::ComboBox::from_label("falloff")
+            .selected_text(params.edge_style.falloff)
+            .show_ui(ui, |ui| {
+                for falloff in FALLOFFS {
+                    let style = &mut params.edge_style;
+                    ui.selectable_value(&mut style.falloff, falloff, falloff.to_string());
+                }
+            });
+        ui.add(
+            egui::Slider::new(&mut params.edge_style.alpha, ALPHA)
+                .logarithmic(true)
+                .text("line alpha"),
+        );
+        ui.horizontal(|ui| {
+            if ui.button("randomize").clicked() {
+                action = Some(PanelAction::Randomize);
+            }
+            if ui.button("reset").clicked() {
+                action = Some(PanelAction::Reset);
+            }
+        });
+    });
+    action
+}
+
+/// Draws every panel parameter from its slider range, counts excluded.
+pub fn randomize<R: Rng + ?Sized>(params: &mut PloomParams, rng: &mut R) {
+    let mut uniform = |range: RangeInclusive<f32>| rng.gen_range(*range.start(), *range.end());
+    params.ploom = uniform(PLOOM);
+    params.decay = uniform(DECAY);
+    // a tenth of the scale at most, and never past the slider
+    let most = THRESHOLD.end().min(params.ploom / 10.0);
+    params.threshold = uniform(*THRESHOLD.start()..=most);
+    params.dampening = uniform(DAMPENING);
+    params.alpha = uniform(0.01..=0.2);
+    params.max_distance = uniform(MAX_DISTANCE);
+}
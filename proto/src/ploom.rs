@@ -0,0 +1,346 @@
+use crate::audio::AudioMap;
+use crate::boundary::Boundary;
+use crate::cli::arg;
+use crate::clock::per_tick;
+use crate::covariance::{Align, Covariance};
+use crate::event::Lifecycle;
+use crate::falloff::EdgeStyle;
+use crate::focus::FocusPath;
+use crate::graph::EdgeMode;
+use crate::noise::{Gaussian, NoiseProcess, Plume};
+use nannou::prelude::*;
+use rand::distributions::{Distribution, Normal};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Runtime replacement for the `const` block every ploom sketch used to carry.
+/// Fields missing from a preset file keep their defaults.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PloomParams {
+    // nannou params
+    pub window_size: u32,
+
+    // point and line parameters
+    pub max_radius: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+    pub alpha: f32, // trailing effect, per rendered frame
+
+    // number of objects
+    pub num_plooms: usize,
+    pub num_circles: usize,
+
+    // randomness and physics parameters
+    pub decay: f32,     // fraction of the variance left after a second
+    pub threshold: f32, // min variance
+    pub ploom: f32,     // initial variance, kicks spread this far in a second
+    pub dampening: f32, // fraction of the offset from the focus pulled back in a second
+
+    // plume shape, 1.0 is the isotropic N(mu, sigma*I)
+    pub stretch: f32,
+    pub align: Align,
+
+    // what keeps foci and circles on the canvas
+    pub boundary: Boundary,
+
+    // which foci are connected, edges fade in and out over `edge_fade` seconds
+    pub edges: EdgeMode,
+    pub edge_fade: f32,
+    pub edge_style: EdgeStyle, // from `min_distance` to `max_distance`
+
+    // how `--audio` drives the sketch
+    pub audio: AudioMap,
+}
+
+impl Default for PloomParams {
+    fn default() -> PloomParams {
+        PloomParams {
+            window_size: 1000,
+            max_radius: 3.0,
+            min_distance: 0.0,
+            max_distance: 100.0,
+            alpha: 0.02,
+            num_plooms: 100,
+            num_circles: 3,
+            decay: 0.3,
+            threshold: 1.55,
+            ploom: 155.0,
+            dampening: 0.7,
+            stretch: 1.0,
+            align: Align::Velocity,
+            boundary: Boundary::Open,
+            edges: EdgeMode::Radius,
+            edge_fade: 0.5,
+            edge_style: EdgeStyle::default(),
+            audio: AudioMap::default(),
+        }
+    }
+}
+
+impl PloomParams {
+    /// Fraction of a circle's offset from its focus pulled back over a tick
+    /// of `dt` seconds.
+    pub fn pull(&self, dt: f32) -> f32 {
+        1.0 - per_tick(self.keep(), dt)
+    }
+
+    /// `dampening` as the rate θ of dX = -θ (X - focus) dt, per second.
+    pub fn pull_rate(&self) -> f32 {
+        -self.keep().ln()
+    }
+
+    fn keep(&self) -> f32 {
+        (1.0 - self.dampening).clamp(0.0, 1.0)
+    }
+
+    /// Overrides the plume shape from `--stretch <factor>` and
+    /// `--align fixed|velocity|spin:<rate>`, the canvas edge from
+    /// `--boundary open|wrap|bounce|attract[:<k>]|respawn` and the lines from
+    /// `--edges radius|delaunay|mst|knn[:<k>]|rng` and
+    /// `--falloff step|linear|smooth|power[:<p>]`.
+    pub fn with_args(mut self) -> PloomParams {
+        if let Some(stretch) = arg("--stretch") {
+            self.stretch = stretch;
+        }
+        if let Some(align) = arg("--align") {
+            self.align = align;
+        }
+        if let Some(boundary) = arg("--boundary") {
+            self.boundary = boundary;
+        }
+        if let Some(edges) = EdgeMode::from_args() {
+            self.edges = edges;
+        }
+        if let Some(falloff) = arg("--falloff") {
+            self.edge_style.falloff = falloff;
+        }
+        self
+    }
+}
+
+pub fn rand_normal_vec2<R: Rng + ?Sized>(rng: &mut R, mu: f32, var: f32) -> Vec2 {
+    let normal = Normal::new(mu as f64, var as f64);
+    vec2(normal.sample(rng) as f32, normal.sample(rng) as f32)
+}
+
+/// Spread of a new ploom's drift velocity, px per second.
+pub const DRIFT: f32 = 60.0;
+
+pub fn rand_uniform_vec2<R: Rng + ?Sized>(rng: &mut R, window_size: u32) -> Vec2 {
+    let bound = window_size as f32 / 2.0;
+    vec2(rng.gen_range(-bound, bound), rng.gen_range(-bound, bound))
+}
+
+/// Radius between 1 and `max_radius`, 1 when there's no room above it, as
+/// a fader or preset may ask for.
+pub fn rand_radius<R: Rng + ?Sized>(rng: &mut R, max_radius: f32) -> f32 {
+    if max_radius > 1.0 {
+        rng.gen_range(1.0, max_radius)
+    } else {
+        1.0
+    }
+}
+
+pub struct Circle {
+    pub x: Vec2,
+    pub last: Vec2, // position one tick ago, for interpolation
+    pub r: f32,
+}
+
+pub struct Ploom {
+    pub circles: Vec<Circle>,
+    pub focus: Vec2,
+    pub last_focus: Vec2,
+    pub sigma: f32,
+    pub v: Vec2,    // px per second
+    pub speed: f32, // factor on `v`
+    pub path: FocusPath,
+    pub t: f32,     // seconds since spawn, drives `path`
+    pub angle: f32, // major axis of the plume
+    pub noise: Arc<dyn NoiseProcess>,
+    pub events: Vec<(Lifecycle, Vec2)>, // since the last `Events::collect`
+}
+
+// events a ploom holds on to when nothing collects them
+const MAX_EVENTS: usize = 16;
+
+impl Ploom {
+    pub fn new<R: Rng + ?Sized>(params: &PloomParams, rng: &mut R) -> Ploom {
+        let circles = Circle::spawn_random_circles(params.num_circles, params, rng);
+        let focus = rand_uniform_vec2(rng, params.window_size);
+        let v = rand_normal_vec2(rng, 0.0, DRIFT);
+        Ploom {
+            circles,
+            focus,
+            last_focus: focus,
+            sigma: params.ploom,
+            v,
+            speed: 1.0,
+            path: FocusPath::Drift,
+            t: 0.0,
+            angle: v.y.atan2(v.x),
+            noise: Arc::new(Gaussian),
+            events: vec![(Lifecycle::FocusSpawn, focus)],
+        }
+    }
+
+    pub fn spawn_random_plooms<R: Rng + ?Sized>(
+        n: usize,
+        params: &PloomParams,
+        rng: &mut R,
+    ) -> Vec<Ploom> {
+        (0..n).map(|_| Ploom::new(params, rng)).collect()
+    }
+
+    /// Grows or shrinks `plooms` to `params.num_plooms` and every ploom to
+    /// `params.num_circles`, new plooms take after the existing ones.
+    pub fn resize_plooms<R: Rng + ?Sized>(
+        plooms: &mut Vec<Ploom>,
+        params: &PloomParams,
+        rng: &mut R,
+    ) {
+        plooms.truncate(params.num_plooms);
+        let n = plooms.len();
+        while plooms.len() < params.num_plooms {
+            let mut ploom = Ploom::new(params, rng);
+            if n > 0 {
+                ploom.take_after(&plooms[plooms.len() % n]);
+            }
+            plooms.push(ploom);
+        }
+        for ploom in plooms.iter_mut() {
+            ploom.resize(params, rng);
+        }
+    }
+
+    /// Takes on `other`'s noise process and focus path, starting on the
+    /// path if it's a fixed curve.
+    pub fn take_after(&mut self, other: &Ploom) {
+        self.noise = other.noise.clone();
+        self.path = other.path.clone();
+        if let Some(focus) = self.path.position(self.t) {
+            self.focus = focus;
+            self.last_focus = focus;
+        }
+    }
+
+    /// Decays `sigma` over a tick of `dt` seconds and moves the focus,
+    /// returns true when the ploom resets.
+    pub fn update<R: Rng + ?Sized>(&mut self, params: &PloomParams, dt: f32, rng: &mut R) -> bool {
+        self.t += dt;
+        self.last_focus = self.focus;
+        if self.sigma > params.threshold {
+            self.sigma *= per_tick(params.decay, dt);
+            if self.sigma <= params.threshold {
+                self.emit(Lifecycle::SigmaThresholdCrossed);
+            }
+            let last = self.focus;
+            self.focus = self
+                .path
+                .next(self.focus, self.v * self.speed, self.t, dt, rng);
+            self.turn(self.focus - last, params, dt);
+            false
+        } else {
+            self.emit(Lifecycle::PloomReset);
+            if self.path.teleports() {
+                self.focus = rand_uniform_vec2(rng, params.window_size);
+                self.last_focus = self.focus;
+                self.emit(Lifecycle::FocusSpawn);
+            } else {
+                self.focus = self
+                    .path
+                    .next(self.focus, self.v * self.speed, self.t, dt, rng);
+            }
+            self.sigma = params.ploom;
+            true
+        }
+    }
+
+    /// Records `kind` at the current focus.
+    pub fn emit(&mut self, kind: Lifecycle) {
+        if self.events.len() == MAX_EVENTS {
+            self.events.remove(0);
+        }
+        self.events.push((kind, self.focus));
+    }
+
+    /// Rotates the plume according to `params.align` after the focus moved by
+    /// `dx` over a tick of `dt` seconds.
+    fn turn(&mut self, dx: Vec2, params: &PloomParams, dt: f32) {
+        match params.align {
+            Align::Fixed => {}
+            Align::Velocity => {
+                if dx != Vec2::ZERO {
+                    self.angle = dx.y.atan2(dx.x);
+                }
+            }
+            Align::Spin(rate) => self.angle += rate * dt,
+        }
+    }
+
+    pub fn covariance(&self, params: &PloomParams) -> Covariance {
+        Covariance::rotated(self.sigma, params.stretch, self.angle)
+    }
+
+    /// Steps the ploom and then every circle bound to it.
+    pub fn step<R: Rng>(&mut self, params: &PloomParams, dt: f32, rng: &mut R) -> bool {
+        let mut reset = self.update(params, dt, rng);
+        reset |= params.boundary.focus(self, params, dt, rng);
+        let plume = Plume {
+            focus: self.focus,
+            covariance: self.covariance(params),
+            t: self.t,
+            dt,
+            params,
+        };
+        for circle in &mut self.circles {
+            circle.last = circle.x;
+            circle.x = self.noise.step(circle, &plume, rng);
+        }
+        params.boundary.circles(self, params, rng);
+        reset
+    }
+
+    /// Grows or shrinks the ploom to `params.num_circles`, new circles start
+    /// around the focus.
+    pub fn resize<R: Rng + ?Sized>(&mut self, params: &PloomParams, rng: &mut R) {
+        self.circles.truncate(params.num_circles);
+        while self.circles.len() < params.num_circles {
+            let mut circle = Circle::new(params, rng);
+            circle.x += self.focus;
+            circle.last = circle.x;
+            self.circles.push(circle);
+        }
+    }
+
+    /// Focus `alpha` of the way from the last tick to the current one.
+    pub fn lerp_focus(&self, alpha: f32) -> Vec2 {
+        self.last_focus.lerp(self.focus, alpha)
+    }
+}
+
+impl Circle {
+    /// A circle a second's worth of kicks from the origin.
+    pub fn new<R: Rng + ?Sized>(params: &PloomParams, rng: &mut R) -> Circle {
+        let x = rand_normal_vec2(rng, 0.0, params.ploom);
+        Circle {
+            x,
+            last: x,
+            r: rand_radius(rng, params.max_radius),
+        }
+    }
+
+    pub fn lerp(&self, alpha: f32) -> Vec2 {
+        self.last.lerp(self.x, alpha)
+    }
+
+    pub fn spawn_random_circles<R: Rng + ?Sized>(
+        n: usize,
+        params: &PloomParams,
+        rng: &mut R,
+    ) -> Vec<Circle> {
+        (0..n).map(|_| Circle::new(params, rng)).collect()
+    }
+}
@@ -0,0 +1,228 @@
+use crate::cli::arg;
+use crate::clock::per_tick;
+use crate::noise::{kick, ou_transition, Levy};
+use crate::ploom::{rand_normal_vec2, rand_radius, PloomParams, DRIFT};
+use nannou::prelude::*;
+use rand::distributions::{Distribution, Normal};
+use rand::Rng;
+use std::fmt;
+use std::str::FromStr;
+
+/// `rand_normal_vec2` with a third axis.
+pub fn rand_normal_vec3<R: Rng + ?Sized>(rng: &mut R, mu: f32, var: f32) -> Vec3 {
+    let xy = rand_normal_vec2(rng, mu, var);
+    xy.extend(Normal::new(mu as f64, var as f64).sample(rng) as f32)
+}
+
+/// Uniform in a centered cube `window_size` across.
+pub fn rand_uniform_vec3<R: Rng + ?Sized>(rng: &mut R, window_size: u32) -> Vec3 {
+    let bound = window_size as f32 / 2.0;
+    vec3(
+        rng.gen_range(-bound, bound),
+        rng.gen_range(-bound, bound),
+        rng.gen_range(-bound, bound),
+    )
+}
+
+/// The isotropic noise processes of `noise` in three dimensions, stepping
+/// with the same `kick`, `ou_transition` and `Levy::mixing`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Noise3 {
+    /// N(0, sigma² dt I) kicks divided by the radius, pulled back by
+    /// `dampening`.
+    #[default]
+    Gaussian,
+    /// Exact Ornstein–Uhlenbeck transition with θ from `dampening`.
+    OrnsteinUhlenbeck,
+    /// α-stable jumps capped at the window size, 1 is Cauchy.
+    Levy(f32),
+}
+
+impl Noise3 {
+    /// Position of `circle` after a step of `dt` seconds around `focus`.
+    pub fn step<R: Rng>(
+        self,
+        circle: &Circle3,
+        focus: Vec3,
+        sigma: f32,
+        params: &PloomParams,
+        dt: f32,
+        rng: &mut R,
+    ) -> Vec3 {
+        let pull = params.pull(dt);
+        match self {
+            Noise3::Gaussian => {
+                let dx = rand_normal_vec3(rng, 0.0, sigma) * dt.sqrt() / circle.r;
+                kick(circle.x, focus, dx, pull)
+            }
+            Noise3::OrnsteinUhlenbeck => {
+                let (decay, variance) = ou_transition(params.pull_rate(), dt);
+                let dx = rand_normal_vec3(rng, 0.0, sigma * variance.sqrt()) / circle.r;
+                focus + (circle.x - focus) * decay + dx
+            }
+            Noise3::Levy(alpha) => {
+                let levy = Levy { alpha };
+                let s = levy.mixing(rng);
+                let dx = rand_normal_vec3(rng, 0.0, sigma) * s.sqrt() * levy.scale(dt) / circle.r;
+                let dx = dx.clamp_length_max(params.window_size as f32);
+                kick(circle.x, focus, dx, pull)
+            }
+        }
+    }
+}
+
+impl FromStr for Noise3 {
+    type Err = String;
+
+    /// `gaussian`, `ou`, `cauchy` or `levy:<alpha>`.
+    fn from_str(s: &str) -> Result<Noise3, String> {
+        match s.split_once(':') {
+            None if s == "gaussian" => Ok(Noise3::Gaussian),
+            None if s == "ou" => Ok(Noise3::OrnsteinUhlenbeck),
+            None if s == "cauchy" => Ok(Noise3::Levy(1.0)),
+            Some(("levy", alpha)) => alpha
+                .parse()
+                .map(Noise3::Levy)
+                .map_err(|e| format!("{}", e)),
+            _ => Err(format!(
+                "expected gaussian, ou, cauchy or levy:<alpha>, got {:?}",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Noise3 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Noise3::Gaussian => write!(f, "gaussian"),
+            Noise3::OrnsteinUhlenbeck => write!(f, "ou"),
+            Noise3::Levy(alpha) => write!(f, "levy:{}", alpha),
+        }
+    }
+}
+
+pub struct Circle3 {
+    pub x: Vec3,
+    pub last: Vec3, // position one tick ago, for interpolation
+    pub r: f32,
+}
+
+/// A ploom in a cube `window_size` across: the focus drifts by `v` and
+/// teleports on reset, circles follow it with noise in all three axes.
+pub struct Ploom3 {
+    pub circles: Vec<Circle3>,
+    pub focus: Vec3,
+    pub last_focus: Vec3,
+    pub sigma: f32,
+    pub v: Vec3, // px per second
+    pub noise: Noise3,
+}
+
+impl Ploom3 {
+    pub fn new<R: Rng + ?Sized>(params: &PloomParams, rng: &mut R) -> Ploom3 {
+        let focus = rand_uniform_vec3(rng, params.window_size);
+        let circles = (0..params.num_circles)
+            .map(|_| Circle3::new(focus, params, rng))
+            .collect();
+        Ploom3 {
+            circles,
+            focus,
+            last_focus: focus,
+            sigma: params.ploom,
+            v: rand_normal_vec3(rng, 0.0, DRIFT),
+            noise: Noise3::Gaussian,
+        }
+    }
+
+    pub fn spawn_random_plooms<R: Rng + ?Sized>(
+        n: usize,
+        params: &PloomParams,
+        rng: &mut R,
+    ) -> Vec<Ploom3> {
+        (0..n).map(|_| Ploom3::new(params, rng)).collect()
+    }
+
+    /// `Ploom::resize_plooms` in 3D: grows or shrinks `plooms` to
+    /// `params.num_plooms` and every ploom to `params.num_circles`.
+    pub fn resize_plooms<R: Rng + ?Sized>(
+        plooms: &mut Vec<Ploom3>,
+        params: &PloomParams,
+        rng: &mut R,
+    ) {
+        plooms.truncate(params.num_plooms);
+        let n = plooms.len();
+        while plooms.len() < params.num_plooms {
+            let mut ploom = Ploom3::new(params, rng);
+            if n > 0 {
+                ploom.noise = plooms[plooms.len() % n].noise;
+            }
+            plooms.push(ploom);
+        }
+        for ploom in plooms.iter_mut() {
+            ploom.circles.truncate(params.num_circles);
+            while ploom.circles.len() < params.num_circles {
+                ploom.circles.push(Circle3::new(ploom.focus, params, rng));
+            }
+        }
+    }
+
+    /// `Ploom::step` in 3D: decays `sigma` over a tick of `dt` seconds, moves
+    /// the focus and then every circle, returns true when the ploom resets.
+    pub fn step<R: Rng>(&mut self, params: &PloomParams, dt: f32, rng: &mut R) -> bool {
+        self.last_focus = self.focus;
+        let reset = if self.sigma > params.threshold {
+            self.sigma *= per_tick(params.decay, dt);
+            self.focus += self.v * dt;
+            false
+        } else {
+            self.focus = rand_uniform_vec3(rng, params.window_size);
+            self.last_focus = self.focus;
+            self.sigma = params.ploom;
+            true
+        };
+        for circle in &mut self.circles {
+            let x = self
+                .noise
+                .step(circle, self.focus, self.sigma, params, dt, rng);
+            circle.last = circle.x;
+            circle.x = x;
+        }
+        reset
+    }
+
+    /// Focus `alpha` of the way from the last tick to the current one.
+    pub fn lerp_focus(&self, alpha: f32) -> Vec3 {
+        self.last_focus.lerp(self.focus, alpha)
+    }
+}
+
+impl Circle3 {
+    /// A circle around `focus`.
+    pub fn new<R: Rng + ?Sized>(focus: Vec3, params: &PloomParams, rng: &mut R) -> Circle3 {
+        let x = focus + rand_normal_vec3(rng, 0.0, params.ploom);
+        Circle3 {
+            x,
+            last: x,
+            r: rand_radius(rng, params.max_radius),
+        }
+    }
+
+    pub fn lerp(&self, alpha: f32) -> Vec3 {
+        self.last.lerp(self.x, alpha)
+    }
+}
+
+/// Reads `--noise <name>[,<name>...]` and hands the processes out to the
+/// plooms round robin.
+pub fn noise3_from_args(plooms: &mut [Ploom3]) {
+    if let Some(names) = arg::<String>("--noise") {
+        let processes: Vec<Noise3> = names
+            .split(',')
+            .map(|name| name.parse().unwrap_or_else(|e| panic!("--noise: {}", e)))
+            .collect();
+        for (i, ploom) in plooms.iter_mut().enumerate() {
+            ploom.noise = processes[i % processes.len()];
+        }
+    }
+}
@@ -0,0 +1,352 @@
+use crate::cli::{arg, exe_name};
+use nannou::prelude::*;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::str::FromStr;
+
+/// Polylines traced by moving points (ploom foci, trellis balls...), keyed by
+/// an id per point. A trail is split whenever its point jumps or leaves the window.
+pub struct Trails {
+    pub bound: f32,
+    pub polylines: Vec<Vec<Vec2>>,
+    active: HashMap<usize, usize>,
+}
+
+impl Trails {
+    /// `window_size` is the side of the square window the points live in.
+    pub fn new(window_size: u32) -> Trails {
+        Trails {
+            bound: window_size as f32 / 2.0,
+            polylines: Vec::new(),
+            active: HashMap::new(),
+        }
+    }
+
+    /// Appends `point` to trail `id`, starting a new polyline if `jumped`.
+    pub fn trace(&mut self, id: usize, point: Vec2, jumped: bool) {
+        if jumped || point.x.abs() > self.bound || point.y.abs() > self.bound {
+            self.active.remove(&id);
+        }
+        if point.x.abs() > self.bound || point.y.abs() > self.bound {
+            return;
+        }
+        let polylines = &mut self.polylines;
+        let index = *self.active.entry(id).or_insert_with(|| {
+            polylines.push(Vec::new());
+            polylines.len() - 1
+        });
+        self.polylines[index].push(point);
+    }
+
+    /// Ends trail `id` for good and moves the ids above it down by one, for
+    /// when point `id` is removed from a list.
+    pub fn remove(&mut self, id: usize) {
+        self.active.remove(&id);
+        self.active = self
+            .active
+            .drain()
+            .map(|(i, index)| (if i > id { i - 1 } else { i }, index))
+            .collect();
+    }
+}
+
+/// Ramer–Douglas–Peucker: drops points closer than `epsilon` to the simplified line.
+pub fn simplify(points: &[Vec2], epsilon: f32) -> Vec<Vec2> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+    let mut stack = vec![(0, points.len() - 1)];
+    while let Some((first, last)) = stack.pop() {
+        let (a, b) = (points[first], points[last]);
+        let mut worst = (0.0, first);
+        for (i, p) in points.iter().enumerate().take(last).skip(first + 1) {
+            let d = distance_to_segment(*p, a, b);
+            if d > worst.0 {
+                worst = (d, i);
+            }
+        }
+        if worst.0 > epsilon {
+            keep[worst.1] = true;
+            stack.push((first, worst.1));
+            stack.push((worst.1, last));
+        }
+    }
+    points
+        .iter()
+        .zip(keep)
+        .filter(|(_, k)| *k)
+        .map(|(p, _)| *p)
+        .collect()
+}
+
+fn distance_to_segment(p: Vec2, a: Vec2, b: Vec2) -> f32 {
+    let ab = b - a;
+    let length2 = ab.length_squared();
+    if length2 == 0.0 {
+        return p.distance(a);
+    }
+    let t = ((p - a).dot(ab) / length2).clamp(0.0, 1.0);
+    p.distance(a + ab * t)
+}
+
+/// Greedy nearest-neighbor ordering, reversing paths when their far end is
+/// closer, to keep pen-up travel short. Starts from `start`.
+pub fn order_paths(mut paths: Vec<Vec<Vec2>>, start: Vec2) -> Vec<Vec<Vec2>> {
+    let mut ordered: Vec<Vec<Vec2>> = Vec::with_capacity(paths.len());
+    let mut pen = start;
+    while !paths.is_empty() {
+        let mut best = (f32::MAX, 0, false);
+        for (i, path) in paths.iter().enumerate() {
+            let head = pen.distance(path[0]);
+            let tail = pen.distance(path[path.len() - 1]);
+            if head < best.0 {
+                best = (head, i, false);
+            }
+            if tail < best.0 {
+                best = (tail, i, true);
+            }
+        }
+        let mut path = paths.swap_remove(best.1);
+        if best.2 {
+            path.reverse();
+        }
+        pen = path[path.len() - 1];
+        ordered.push(path);
+    }
+    ordered
+}
+
+/// Paper size and margins in millimetres.
+#[derive(Clone, Debug)]
+pub struct Paper {
+    pub width: f32,
+    pub height: f32,
+    pub margin: f32,
+}
+
+impl Paper {
+    pub fn a4() -> Paper {
+        Paper {
+            width: 210.0,
+            height: 297.0,
+            margin: 15.0,
+        }
+    }
+
+    pub fn a3() -> Paper {
+        Paper {
+            width: 297.0,
+            height: 420.0,
+            margin: 20.0,
+        }
+    }
+
+    /// Reads `--paper a4|a3|<w>x<h>` and `--margin <mm>`, defaulting to A4.
+    pub fn from_args() -> Paper {
+        let paper = arg("--paper").unwrap_or_else(Paper::a4);
+        match arg::<f32>("--margin") {
+            Some(margin) => paper
+                .with_margin(margin)
+                .unwrap_or_else(|e| panic!("bad --margin {:?}: {}", margin, e)),
+            None => paper,
+        }
+    }
+
+    /// The paper with `margin` millimetres all round, as long as that leaves
+    /// something to draw on.
+    pub fn with_margin(self, margin: f32) -> Result<Paper, String> {
+        let most = self.width.min(self.height) / 2.0;
+        if (0.0..most).contains(&margin) {
+            Ok(Paper { margin, ..self })
+        } else {
+            Err(format!(
+                "expected a margin from 0 to under {}mm on {}x{}mm paper",
+                most, self.width, self.height
+            ))
+        }
+    }
+
+    /// Maps a point of a centered square window of side `window_size` into
+    /// millimetres on the paper, centered inside the margins. Paper y points up.
+    pub fn to_paper(&self, p: Vec2, window_size: u32) -> Vec2 {
+        let printable = (self.width - 2.0 * self.margin).min(self.height - 2.0 * self.margin);
+        let scale = printable / window_size as f32;
+        vec2(
+            self.width / 2.0 + p.x * scale,
+            self.height / 2.0 + p.y * scale,
+        )
+    }
+}
+
+impl FromStr for Paper {
+    type Err = String;
+
+    /// `a4`, `a3` or `<width>x<height>` in millimetres with a 15mm margin.
+    fn from_str(s: &str) -> Result<Paper, String> {
+        let expected = || format!("expected a4, a3 or <w>x<h> in mm, got {:?}", s);
+        match s {
+            "a4" => Ok(Paper::a4()),
+            "a3" => Ok(Paper::a3()),
+            _ => {
+                let (w, h) = s.split_once('x').ok_or_else(expected)?;
+                let mm = |x: &str| x.parse::<f32>().ok().filter(|x| *x > 0.0 && x.is_finite());
+                Paper {
+                    width: mm(w).ok_or_else(expected)?,
+                    height: mm(h).ok_or_else(expected)?,
+                    margin: 0.0,
+                }
+                .with_margin(15.0)
+            }
+        }
+    }
+}
+
+/// Pen commands for G-code output, these differ between servo and z-axis pens.
+#[derive(Clone, Debug)]
+pub struct Pen {
+    pub up: String,
+    pub down: String,
+    pub feed_rate: f32, // mm/min while drawing
+}
+
+impl Default for Pen {
+    fn default() -> Pen {
+        Pen {
+            up: String::from("G0 Z5"),
+            down: String::from("G0 Z0"),
+            feed_rate: 3000.0,
+        }
+    }
+}
+
+impl Pen {
+    /// Reads `--pen-up <gcode>`, `--pen-down <gcode>` and `--feed <mm/min>`.
+    pub fn from_args() -> Pen {
+        let default = Pen::default();
+        Pen {
+            up: arg("--pen-up").unwrap_or(default.up),
+            down: arg("--pen-down").unwrap_or(default.down),
+            feed_rate: arg("--feed").unwrap_or(default.feed_rate),
+        }
+    }
+}
+
+/// HPGL with 40 plotter units per millimetre.
+pub fn to_hpgl(paths: &[Vec<Vec2>], paper: &Paper, window_size: u32) -> String {
+    let unit = |p: Vec2| {
+        let mm = paper.to_paper(p, window_size);
+        ((mm.x * 40.0).round() as i32, (mm.y * 40.0).round() as i32)
+    };
+    let mut hpgl = String::from("IN;SP1;\n");
+    for path in paths {
+        let (x, y) = unit(path[0]);
+        write!(hpgl, "PU{},{};PD", x, y).unwrap();
+        let points: Vec<String> = path[1..]
+            .iter()
+            .map(|p| {
+                let (x, y) = unit(*p);
+                format!("{},{}", x, y)
+            })
+            .collect();
+        writeln!(hpgl, "{};", points.join(",")).unwrap();
+    }
+    hpgl.push_str("PU0,0;SP0;\n");
+    hpgl
+}
+
+pub fn to_gcode(paths: &[Vec<Vec2>], paper: &Paper, window_size: u32, pen: &Pen) -> String {
+    let mut gcode = String::from("G21 ; millimetres\nG90 ; absolute\n");
+    writeln!(gcode, "{}", pen.up).unwrap();
+    for path in paths {
+        let start = paper.to_paper(path[0], window_size);
+        writeln!(gcode, "G0 X{:.3} Y{:.3}", start.x, start.y).unwrap();
+        writeln!(gcode, "{}", pen.down).unwrap();
+        for p in &path[1..] {
+            let p = paper.to_paper(*p, window_size);
+            writeln!(gcode, "G1 X{:.3} Y{:.3} F{}", p.x, p.y, pen.feed_rate).unwrap();
+        }
+        writeln!(gcode, "{}", pen.up).unwrap();
+    }
+    gcode.push_str("G0 X0 Y0\n");
+    gcode
+}
+
+/// Traces trajectories for `--plot <ticks>` simulation ticks, 60 to the
+/// second whatever the frame rate, then writes `<example>_seed<seed>.hpgl`
+/// and `.gcode`. `--epsilon` sets the simplification tolerance in window
+/// pixels.
+pub struct PlotExport {
+    pub ticks: u64,
+    pub recorded: u64,
+    pub trails: Trails,
+    pub epsilon: f32,
+    pub paper: Paper,
+    pub pen: Pen,
+    name: String,
+}
+
+impl PlotExport {
+    pub fn from_args(seed: u64, window_size: u32) -> Option<PlotExport> {
+        let ticks: u64 = arg("--plot")?;
+        Some(PlotExport {
+            ticks,
+            recorded: 0,
+            trails: Trails::new(window_size),
+            epsilon: arg("--epsilon").unwrap_or(0.5),
+            paper: Paper::from_args(),
+            pen: Pen::from_args(),
+            name: format!("{}_seed{}", exe_name(), seed),
+        })
+    }
+
+    pub fn trace(&mut self, id: usize, point: Vec2, jumped: bool) {
+        if self.recorded < self.ticks {
+            self.trails.trace(id, point, jumped);
+        }
+    }
+
+    /// See `Trails::remove`.
+    pub fn remove(&mut self, id: usize) {
+        self.trails.remove(id);
+    }
+
+    /// Call once per tick, writes the plot files after the last one.
+    pub fn end_tick(&mut self) {
+        self.recorded += 1;
+        if self.recorded == self.ticks {
+            self.save().unwrap();
+        }
+    }
+
+    /// Simplified, travel-ordered paths in window coordinates.
+    pub fn paths(&self) -> Vec<Vec<Vec2>> {
+        let paths: Vec<Vec<Vec2>> = self
+            .trails
+            .polylines
+            .iter()
+            .map(|line| simplify(line, self.epsilon))
+            .filter(|line| line.len() > 1)
+            .collect();
+        let corner = vec2(-self.trails.bound, -self.trails.bound);
+        order_paths(paths, corner)
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let window_size = (self.trails.bound * 2.0) as u32;
+        let paths = self.paths();
+        let hpgl = format!("{}.hpgl", self.name);
+        let gcode = format!("{}.gcode", self.name);
+        fs::write(&hpgl, to_hpgl(&paths, &self.paper, window_size))?;
+        fs::write(
+            &gcode,
+            to_gcode(&paths, &self.paper, window_size, &self.pen),
+        )?;
+        println!("wrote {} paths to {} and {}", paths.len(), hpgl, gcode);
+        Ok(())
+    }
+}
@@ -0,0 +1,135 @@
+use crate::cli::{arg, exe_name};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+// how often `poll` looks at the file's modification time
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Reads TOML, or RON when the file ends in `.ron`.
+pub fn load<T: DeserializeOwned>(path: &Path) -> Result<T, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let value = if is_ron(path) {
+        ron::from_str(&text).map_err(|e| e.to_string())
+    } else {
+        toml::from_str(&text).map_err(|e| e.to_string())
+    };
+    value.map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Writes TOML, or RON when the file ends in `.ron`, creating the directory.
+pub fn save<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    let text = if is_ron(path) {
+        ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())
+            .map_err(|e| e.to_string())?
+    } else {
+        toml::to_string_pretty(value).map_err(|e| e.to_string())?
+    };
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    }
+    fs::write(path, text).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn is_ron(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "ron")
+}
+
+/// Where a preset name lives: a bare name is `presets/<example>/<name>.toml`,
+/// anything with an extension or a directory is taken as a path.
+pub fn resolve(name: &str) -> PathBuf {
+    let path = PathBuf::from(name);
+    if path.extension().is_some() || path.components().count() > 1 {
+        path
+    } else {
+        Path::new("presets")
+            .join(exe_name())
+            .join(name)
+            .with_extension("toml")
+    }
+}
+
+/// A sketch's parameter file, watched for changes. `--preset <name>` picks
+/// it, otherwise it is `seed<seed>` so the first save of a run has a home.
+pub struct Preset {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    checked: Option<Instant>,
+}
+
+impl Preset {
+    pub fn new<P: Into<PathBuf>>(path: P) -> Preset {
+        Preset {
+            path: path.into(),
+            modified: None,
+            checked: None,
+        }
+    }
+
+    pub fn from_args(seed: u64) -> Preset {
+        let name = arg::<String>("--preset").unwrap_or_else(|| format!("seed{}", seed));
+        Preset::new(resolve(&name))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The preset's values over `default`, or `default` when there is no
+    /// file yet. A file that doesn't parse is a mistake worth stopping for.
+    pub fn load_or<T: DeserializeOwned>(&mut self, default: T) -> T {
+        self.modified = self.modified_time();
+        if self.modified.is_none() {
+            return default;
+        }
+        load(&self.path).unwrap_or_else(|e| panic!("bad preset {}", e))
+    }
+
+    /// `load_or` for a running sketch: a file that doesn't parse is printed
+    /// like in `poll` and gives `None`, so the running values can be kept.
+    pub fn reload_or<T: DeserializeOwned>(&mut self, default: T) -> Option<T> {
+        self.modified = self.modified_time();
+        if self.modified.is_none() {
+            return Some(default);
+        }
+        self.load_reported()
+    }
+
+    /// New values when the file changed since the last load or save. Parse
+    /// errors while editing are printed and the running values kept.
+    pub fn poll<T: DeserializeOwned>(&mut self) -> Option<T> {
+        if self.checked.is_some_and(|t| t.elapsed() < POLL_INTERVAL) {
+            return None;
+        }
+        self.checked = Some(Instant::now());
+        let modified = self.modified_time();
+        if modified.is_none() || modified == self.modified {
+            return None;
+        }
+        self.modified = modified;
+        let value = self.load_reported()?;
+        println!("reloaded {}", self.path.display());
+        Some(value)
+    }
+
+    /// Writes the running values back, without triggering a reload.
+    pub fn save<T: Serialize>(&mut self, value: &T) {
+        match save(&self.path, value) {
+            Ok(()) => {
+                println!("saved {}", self.path.display());
+                self.modified = self.modified_time();
+            }
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+
+    fn load_reported<T: DeserializeOwned>(&self) -> Option<T> {
+        load(&self.path).map_err(|e| eprintln!("{}", e)).ok()
+    }
+
+    fn modified_time(&self) -> Option<SystemTime> {
+        fs::metadata(&self.path).and_then(|m| m.modified()).ok()
+    }
+}
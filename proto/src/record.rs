@@ -0,0 +1,362 @@
+use crate::cli::arg;
+use crate::ploom::{Circle, Ploom, PloomParams};
+use nannou::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+// columns of a CSV recording, one row per focus, circle or ball
+const CSV_HEADER: &str = "frame,time,kind,id,index,x,y,vx,vy,r,sigma";
+
+/// Everything a sketch recorded at one tick. Ploom sketches fill `plooms`,
+/// trellis sketches `balls`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub frame: u64, // tick since the recording started
+    pub time: f32,  // seconds, `frame * dt`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub plooms: Vec<PloomState>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub balls: Vec<BallState>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PloomState {
+    pub focus: [f32; 2],
+    pub sigma: f32,
+    pub circles: Vec<CircleState>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CircleState {
+    pub x: [f32; 2],
+    pub r: f32,
+}
+
+/// A trellis ball, `index` within cell `cell`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BallState {
+    pub cell: usize,
+    pub index: usize,
+    pub loc: [f32; 2],
+    pub v: [f32; 2],
+}
+
+impl PloomState {
+    pub fn of(ploom: &Ploom) -> PloomState {
+        PloomState {
+            focus: ploom.focus.to_array(),
+            sigma: ploom.sigma,
+            circles: ploom
+                .circles
+                .iter()
+                .map(|c| CircleState {
+                    x: c.x.to_array(),
+                    r: c.r,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Puts recorded plooms back, growing or shrinking `plooms` and their circles
+/// to match and keeping `params` counts in step so `resize_plooms` leaves
+/// them be. What was there becomes `last`, so drawing interpolates between
+/// snapshots.
+pub fn restore_plooms<R: Rng + ?Sized>(
+    plooms: &mut Vec<Ploom>,
+    states: &[PloomState],
+    params: &mut PloomParams,
+    rng: &mut R,
+) {
+    params.num_plooms = states.len();
+    if let Some(first) = states.first() {
+        params.num_circles = first.circles.len();
+    }
+    plooms.truncate(states.len());
+    while plooms.len() < states.len() {
+        plooms.push(Ploom::new(params, rng));
+    }
+    for (ploom, state) in plooms.iter_mut().zip(states) {
+        ploom.last_focus = ploom.focus;
+        ploom.focus = Vec2::from(state.focus);
+        ploom.sigma = state.sigma;
+        ploom.circles.truncate(state.circles.len());
+        while ploom.circles.len() < state.circles.len() {
+            let mut circle = Circle::new(params, rng);
+            circle.x = ploom.focus;
+            ploom.circles.push(circle);
+        }
+        for (circle, recorded) in ploom.circles.iter_mut().zip(&state.circles) {
+            circle.last = circle.x;
+            circle.x = Vec2::from(recorded.x);
+            circle.r = recorded.r;
+        }
+    }
+}
+
+/// Holds `plooms` where they are for a tick `Replay::advance` had nothing
+/// new for, so drawing doesn't interpolate from the last snapshot again.
+pub fn hold_plooms(plooms: &mut [Ploom]) {
+    for ploom in plooms.iter_mut() {
+        ploom.last_focus = ploom.focus;
+        for circle in ploom.circles.iter_mut() {
+            circle.last = circle.x;
+        }
+    }
+}
+
+/// What a recording is written as, from the file extension.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// A row per focus, circle or ball, `CSV_HEADER` columns.
+    Csv,
+    /// A `Snapshot` as JSON per line.
+    JsonLines,
+}
+
+impl Format {
+    /// `.csv` is CSV, anything else (`.jsonl`, `.ndjson`...) JSON lines.
+    pub fn of(path: &Path) -> Format {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("csv") => Format::Csv,
+            _ => Format::JsonLines,
+        }
+    }
+}
+
+/// Streams snapshots to disk as the sketch runs, keeping every `every`th tick.
+pub struct Recorder {
+    writer: BufWriter<File>,
+    pub format: Format,
+    pub every: u64,
+    ticks: u64,
+}
+
+impl Recorder {
+    pub fn create(path: &Path, every: u64) -> io::Result<Recorder> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let format = Format::of(path);
+        let mut writer = BufWriter::new(File::create(path)?);
+        if format == Format::Csv {
+            writeln!(writer, "{}", CSV_HEADER)?;
+        }
+        Ok(Recorder {
+            writer,
+            format,
+            every: every.max(1),
+            ticks: 0,
+        })
+    }
+
+    /// `--record <file.csv|file.jsonl>`, keeping every `--record-every`th
+    /// tick (1 by default).
+    pub fn from_args() -> Option<Recorder> {
+        let path: String = arg("--record")?;
+        let every = arg("--record-every").unwrap_or(1);
+        let recorder =
+            Recorder::create(Path::new(&path), every).unwrap_or_else(|e| panic!("{}: {}", path, e));
+        println!("recording to {}", path);
+        Some(recorder)
+    }
+
+    /// Counts a tick of `dt` seconds and writes what `snapshot` returns when
+    /// it's one to keep, stamped with the tick and its time. `snapshot` isn't
+    /// called for the others. Flushed right away, the window may exit without
+    /// dropping the model.
+    pub fn record<F>(&mut self, dt: f32, snapshot: F) -> io::Result<()>
+    where
+        F: FnOnce() -> Snapshot,
+    {
+        let frame = self.ticks;
+        self.ticks += 1;
+        if !frame.is_multiple_of(self.every) {
+            return Ok(());
+        }
+        let snapshot = Snapshot {
+            frame,
+            time: (frame as f64 * dt as f64) as f32,
+            ..snapshot()
+        };
+        match self.format {
+            Format::JsonLines => {
+                serde_json::to_writer(&mut self.writer, &snapshot)?;
+                writeln!(self.writer)?;
+            }
+            Format::Csv => self.writer.write_all(csv_rows(&snapshot).as_bytes())?,
+        }
+        self.writer.flush()
+    }
+}
+
+fn csv_rows(s: &Snapshot) -> String {
+    let mut rows = String::new();
+    let stamp = format!("{},{}", s.frame, s.time);
+    for (i, ploom) in s.plooms.iter().enumerate() {
+        let [x, y] = ploom.focus;
+        writeln!(
+            rows,
+            "{},focus,{},,{},{},,,,{}",
+            stamp, i, x, y, ploom.sigma
+        )
+        .unwrap();
+        for (c, circle) in ploom.circles.iter().enumerate() {
+            let [x, y] = circle.x;
+            writeln!(
+                rows,
+                "{},circle,{},{},{},{},,,{},",
+                stamp, i, c, x, y, circle.r
+            )
+            .unwrap();
+        }
+    }
+    for ball in &s.balls {
+        let ([x, y], [vx, vy]) = (ball.loc, ball.v);
+        writeln!(
+            rows,
+            "{},ball,{},{},{},{},{},{},,",
+            stamp, ball.cell, ball.index, x, y, vx, vy
+        )
+        .unwrap();
+    }
+    rows
+}
+
+/// Reads a recording made by `Recorder`, in either format.
+pub fn load(path: &Path) -> Result<Vec<Snapshot>, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    match Format::of(path) {
+        Format::JsonLines => text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(n, line)| {
+                serde_json::from_str(line)
+                    .map_err(|e| format!("{}:{}: {}", path.display(), n + 1, e))
+            })
+            .collect(),
+        Format::Csv => parse_csv(&text).map_err(|e| format!("{}:{}", path.display(), e)),
+    }
+}
+
+fn parse_csv(text: &str) -> Result<Vec<Snapshot>, String> {
+    let mut snapshots: Vec<Snapshot> = Vec::new();
+    for (n, line) in text.lines().enumerate().skip(1) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let error = |e: String| format!("{}: {}", n + 1, e);
+        let fields: Vec<&str> = line.split(',').collect();
+        if fields.len() != CSV_HEADER.split(',').count() {
+            return Err(error(format!("expected the columns {}", CSV_HEADER)));
+        }
+        let field = |i: usize| -> Result<f32, String> {
+            fields[i]
+                .parse()
+                .map_err(|_| format!("bad number {:?}", fields[i]))
+        };
+        let index = |i: usize| -> Result<usize, String> {
+            fields[i]
+                .parse()
+                .map_err(|_| format!("bad index {:?}", fields[i]))
+        };
+        let frame: u64 = fields[0]
+            .parse()
+            .map_err(|_| error(format!("bad frame {:?}", fields[0])))?;
+        if snapshots.last().map(|s| s.frame) != Some(frame) {
+            snapshots.push(Snapshot {
+                frame,
+                time: field(1).map_err(error)?,
+                ..Snapshot::default()
+            });
+        }
+        let snapshot = snapshots.last_mut().unwrap();
+        let id = index(3).map_err(error)?;
+        let xy = [field(5).map_err(error)?, field(6).map_err(error)?];
+        match fields[2] {
+            "focus" => {
+                if id != snapshot.plooms.len() {
+                    return Err(error(format!("focus {} out of order", id)));
+                }
+                snapshot.plooms.push(PloomState {
+                    focus: xy,
+                    sigma: field(10).map_err(error)?,
+                    circles: Vec::new(),
+                });
+            }
+            "circle" => {
+                let ploom = snapshot
+                    .plooms
+                    .get_mut(id)
+                    .ok_or_else(|| error(format!("circle of ploom {} before its focus", id)))?;
+                ploom.circles.push(CircleState {
+                    x: xy,
+                    r: field(9).map_err(error)?,
+                });
+            }
+            "ball" => snapshot.balls.push(BallState {
+                cell: id,
+                index: index(4).map_err(error)?,
+                loc: xy,
+                v: [field(7).map_err(error)?, field(8).map_err(error)?],
+            }),
+            other => return Err(error(format!("unknown kind {:?}", other))),
+        }
+    }
+    Ok(snapshots)
+}
+
+/// A recording played back tick by tick in place of the simulation.
+pub struct Replay {
+    pub snapshots: Vec<Snapshot>,
+    ticks: u64,
+    next: usize, // first snapshot not shown yet
+}
+
+impl Replay {
+    pub fn new(snapshots: Vec<Snapshot>) -> Replay {
+        Replay {
+            snapshots,
+            ticks: 0,
+            next: 0,
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Replay, String> {
+        load(path).map(Replay::new)
+    }
+
+    /// `--replay <recording>`.
+    pub fn from_args() -> Option<Replay> {
+        let path: String = arg("--replay")?;
+        Some(Replay::load(Path::new(&path)).unwrap_or_else(|e| panic!("{}", e)))
+    }
+
+    /// Moves on by a tick of `dt` seconds. The latest snapshot due by then,
+    /// or `None` if that's still the one returned last time, so a decimated
+    /// recording holds each snapshot until the next one is due and the last
+    /// one stays up once it's over.
+    pub fn advance(&mut self, dt: f32) -> Option<&Snapshot> {
+        let now = self.ticks as f64 * dt as f64;
+        self.ticks += 1;
+        let start = self.snapshots.first()?.time as f64;
+        let due = self.snapshots[self.next..]
+            .iter()
+            .take_while(|s| s.time as f64 - start <= now + 1e-6)
+            .count();
+        if due == 0 {
+            return None;
+        }
+        self.next += due;
+        self.snapshots.get(self.next - 1)
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.next == self.snapshots.len()
+    }
+}
@@ -0,0 +1,20 @@
+use crate::cli::arg;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// Reads `--seed <u64>` from the command line, falling back to a random seed.
+/// The seed is printed so a good run can be replayed.
+pub fn seed_from_args() -> u64 {
+    let seed = arg("--seed").unwrap_or_else(rand::random);
+    println!("seed: {}", seed);
+    seed
+}
+
+pub fn seeded_rng(seed: u64) -> StdRng {
+    StdRng::seed_from_u64(seed)
+}
+
+/// File name for a captured frame, e.g. `ploom_d_seed42_frame1200.png`.
+pub fn capture_name(exe_name: &str, seed: u64, frame: u64) -> String {
+    format!("{}_seed{}_frame{}.png", exe_name, seed, frame)
+}
@@ -0,0 +1,71 @@
+use nannou::prelude::*;
+use std::collections::HashMap;
+
+/// Uniform grid over a set of points for radius queries.
+pub struct Grid {
+    cell_size: f32,
+    points: Vec<Vec2>,
+    cells: HashMap<(i32, i32), Vec<usize>>,
+}
+
+impl Grid {
+    pub fn new(points: &[Vec2], cell_size: f32) -> Grid {
+        assert!(cell_size > 0.0, "cell_size must be positive");
+        let mut cells: HashMap<(i32, i32), Vec<usize>> = HashMap::new();
+        for (i, p) in points.iter().enumerate() {
+            cells.entry(cell_of(*p, cell_size)).or_default().push(i);
+        }
+        Grid {
+            cell_size,
+            points: points.to_vec(),
+            cells,
+        }
+    }
+
+    /// Indices of every point strictly closer than `radius` to `p`, in ascending order.
+    pub fn within(&self, p: Vec2, radius: f32) -> Vec<usize> {
+        let (cx, cy) = cell_of(p, self.cell_size);
+        let reach = (radius / self.cell_size).ceil() as i32;
+        let mut found: Vec<usize> = Vec::new();
+        for x in cx - reach..=cx + reach {
+            for y in cy - reach..=cy + reach {
+                if let Some(cell) = self.cells.get(&(x, y)) {
+                    found.extend(
+                        cell.iter()
+                            .filter(|&&i| self.points[i].distance(p) < radius),
+                    );
+                }
+            }
+        }
+        found.sort_unstable();
+        found
+    }
+
+    /// Every unordered pair `(i, j, distance)` with `i < j` closer than `radius`.
+    pub fn pairs(&self, radius: f32) -> Vec<(usize, usize, f32)> {
+        let mut pairs: Vec<(usize, usize, f32)> = Vec::new();
+        for (i, p) in self.points.iter().enumerate() {
+            for j in self.within(*p, radius) {
+                if j > i {
+                    pairs.push((i, j, p.distance(self.points[j])));
+                }
+            }
+        }
+        pairs
+    }
+}
+
+fn cell_of(p: Vec2, cell_size: f32) -> (i32, i32) {
+    (
+        (p.x / cell_size).floor() as i32,
+        (p.y / cell_size).floor() as i32,
+    )
+}
+
+/// Pairs of points closer than `radius`, using a grid sized to the radius.
+pub fn pairs_within(points: &[Vec2], radius: f32) -> Vec<(usize, usize, f32)> {
+    if radius <= 0.0 {
+        return Vec::new();
+    }
+    Grid::new(points, radius).pairs(radius)
+}
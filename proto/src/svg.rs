@@ -0,0 +1,204 @@
+use crate::canvas::linear_to_srgb;
+use crate::cli::{arg, exe_name};
+use crate::paint::Painter;
+use nannou::color::{IntoLinSrgba, LinSrgba};
+use nannou::prelude::*;
+use std::cell::RefCell;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Collects everything painted into it as vector shapes. Circles and edges go
+/// into separate layers (Inkscape picks the groups up as layers), the
+/// translucent fade rects used for trails are dropped since vectors don't fade.
+pub struct SvgRecorder {
+    pub width: u32,
+    pub height: u32,
+    background: Option<String>,
+    circles: String,
+    edges: String,
+    quads: String,
+}
+
+impl SvgRecorder {
+    pub fn new(width: u32, height: u32) -> SvgRecorder {
+        SvgRecorder {
+            width,
+            height,
+            background: None,
+            circles: String::new(),
+            edges: String::new(),
+            quads: String::new(),
+        }
+    }
+
+    pub fn to_svg(&self) -> String {
+        let (w, h) = (self.width as f32, self.height as f32);
+        let mut svg = String::new();
+        writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape" width="{}" height="{}" viewBox="{} {} {} {}">"#,
+            w, h, -w / 2.0, -h / 2.0, w, h
+        )
+        .unwrap();
+        if let Some(fill) = &self.background {
+            writeln!(
+                svg,
+                r#"<rect id="background" x="{}" y="{}" width="{}" height="{}" {}/>"#,
+                -w / 2.0,
+                -h / 2.0,
+                w,
+                h,
+                fill
+            )
+            .unwrap();
+        }
+        for (name, layer) in [
+            ("quads", &self.quads),
+            ("circles", &self.circles),
+            ("edges", &self.edges),
+        ] {
+            if !layer.is_empty() {
+                writeln!(
+                    svg,
+                    r#"<g id="{0}" inkscape:groupmode="layer" inkscape:label="{0}">"#,
+                    name
+                )
+                .unwrap();
+                svg.push_str(layer);
+                svg.push_str("</g>\n");
+            }
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_svg())
+    }
+}
+
+/// `fill="#rrggbb" fill-opacity="a"` style attributes for `color`.
+fn paint_attrs(kind: &str, color: LinSrgba) -> String {
+    let hex = |c: f32| (linear_to_srgb(c) * 255.0).round() as u8;
+    format!(
+        r##"{0}="#{1:02x}{2:02x}{3:02x}" {0}-opacity="{4:.3}""##,
+        kind,
+        hex(color.red),
+        hex(color.green),
+        hex(color.blue),
+        color.alpha.clamp(0.0, 1.0)
+    )
+}
+
+impl Painter for SvgRecorder {
+    fn background<C: IntoLinSrgba<f32>>(&mut self, color: C) {
+        if self.background.is_none() {
+            let mut color = color.into_lin_srgba();
+            color.alpha = 1.0;
+            self.background = Some(paint_attrs("fill", color));
+        }
+    }
+
+    fn rect<C: IntoLinSrgba<f32>>(&mut self, _xy: Vec2, wh: Vec2, color: C) {
+        // a window sized rect is the trail fade, its color is the background
+        if wh.x >= self.width as f32 && wh.y >= self.height as f32 {
+            self.background(color);
+        }
+    }
+
+    fn ellipse<C: IntoLinSrgba<f32>>(&mut self, xy: Vec2, radius: f32, color: C) {
+        let color = color.into_lin_srgba();
+        if radius > 0.0 && color.alpha > 0.0 {
+            writeln!(
+                self.circles,
+                r#"<circle cx="{:.2}" cy="{:.2}" r="{:.2}" {}/>"#,
+                xy.x,
+                -xy.y,
+                radius,
+                paint_attrs("fill", color)
+            )
+            .unwrap();
+        }
+    }
+
+    fn line<C: IntoLinSrgba<f32>>(&mut self, start: Vec2, end: Vec2, weight: f32, color: C) {
+        let color = color.into_lin_srgba();
+        if weight > 0.0 && color.alpha > 0.0 {
+            writeln!(
+                self.edges,
+                r#"<line x1="{:.2}" y1="{:.2}" x2="{:.2}" y2="{:.2}" stroke-width="{:.2}" {}/>"#,
+                start.x,
+                -start.y,
+                end.x,
+                -end.y,
+                weight,
+                paint_attrs("stroke", color)
+            )
+            .unwrap();
+        }
+    }
+
+    fn quad<C: IntoLinSrgba<f32>>(&mut self, points: [Vec2; 4], color: C) {
+        let points: Vec<String> = points
+            .iter()
+            .map(|p| format!("{:.2},{:.2}", p.x, -p.y))
+            .collect();
+        writeln!(
+            self.quads,
+            r#"<polygon points="{}" {}/>"#,
+            points.join(" "),
+            paint_attrs("fill", color.into_lin_srgba())
+        )
+        .unwrap();
+    }
+}
+
+/// Records a span of frames into one SVG, read from
+/// `--svg-frames <n> [--svg-from <frame>]`. Lives in the `Model` and is fed from
+/// the same `paint` as the window, hence the `RefCell`.
+pub struct SvgExport {
+    pub from: u64,
+    pub frames: u64,
+    pub path: PathBuf,
+    recorder: RefCell<SvgRecorder>,
+}
+
+impl SvgExport {
+    pub fn new(from: u64, frames: u64, path: PathBuf, size: u32) -> SvgExport {
+        SvgExport {
+            from,
+            frames,
+            path,
+            recorder: RefCell::new(SvgRecorder::new(size, size)),
+        }
+    }
+
+    pub fn from_args(seed: u64, size: u32) -> Option<SvgExport> {
+        let frames: u64 = arg("--svg-frames")?;
+        let from: u64 = arg("--svg-from").unwrap_or(0);
+        let path = PathBuf::from(format!(
+            "{}_seed{}_frames{}-{}.svg",
+            exe_name(),
+            seed,
+            from,
+            from + frames
+        ));
+        Some(SvgExport::new(from, frames, path, size))
+    }
+
+    /// Paints frame `nth` into the recorder if it is inside the span and writes
+    /// the file once the last frame of the span has been recorded.
+    pub fn record<F: FnOnce(&mut SvgRecorder)>(&self, nth: u64, paint: F) {
+        if nth < self.from || nth >= self.from + self.frames {
+            return;
+        }
+        let mut recorder = self.recorder.borrow_mut();
+        paint(&mut recorder);
+        if nth + 1 == self.from + self.frames {
+            recorder.save(&self.path).unwrap();
+            println!("wrote {}", self.path.display());
+        }
+    }
+}
@@ -0,0 +1,330 @@
+use crate::boundary::Boundary;
+use crate::cli::arg;
+use crate::clock::per_tick;
+use crate::covariance::{Align, Covariance};
+use crate::event::Lifecycle;
+use crate::noise::kick;
+use crate::ploom::{rand_normal_vec2, rand_radius, rand_uniform_vec2, Ploom, PloomParams, DRIFT};
+use crate::rng::seeded_rng;
+use nannou::prelude::*;
+use rand::distributions::StandardNormal;
+use rand::rngs::StdRng;
+use rand::Rng;
+use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+
+/// Plooms or circles per rayon task, each with its own RNG stream. Fixed, so
+/// how the work is split doesn't depend on the number of threads.
+pub const CHUNK: usize = 4096;
+
+/// Every ploom and circle of a sketch as flat arrays instead of a `Vec<Ploom>`,
+/// stepped in parallel. Circles of ploom `p` are
+/// `p * per_ploom..(p + 1) * per_ploom`. Covers the original model only:
+/// drifting foci that teleport on reset, Gaussian kicks and an open canvas.
+pub struct Swarm {
+    pub per_ploom: usize, // circles per ploom
+
+    // per ploom
+    pub focus: Vec<Vec2>,
+    pub last_focus: Vec<Vec2>,
+    pub v: Vec<Vec2>,
+    pub sigma: Vec<f32>,
+    pub angle: Vec<f32>,
+    cholesky: Vec<(f32, f32, f32)>, // of the plume covariance this tick
+
+    // per circle
+    pub x: Vec<Vec2>,
+    pub last: Vec<Vec2>,
+    pub r: Vec<f32>,
+
+    seed: u64,
+    pub ticks: u64,
+}
+
+impl Swarm {
+    /// `params.num_plooms` plooms of `params.num_circles` circles each, the
+    /// circles starting around their focus.
+    pub fn new(params: &PloomParams, seed: u64) -> Swarm {
+        let (n, per_ploom) = (params.num_plooms, params.num_circles);
+        let mut rng = seeded_rng(seed);
+        let mut swarm = Swarm::empty(n, per_ploom, seed);
+        for _ in 0..n {
+            let focus = rand_uniform_vec2(&mut rng, params.window_size);
+            let v = rand_normal_vec2(&mut rng, 0.0, DRIFT);
+            swarm.push_ploom(focus, v, params.ploom);
+            for _ in 0..per_ploom {
+                let x = focus + rand_normal_vec2(&mut rng, 0.0, params.ploom);
+                swarm.push_circle(x, rand_radius(&mut rng, params.max_radius));
+            }
+        }
+        swarm
+    }
+
+    /// The same plooms and circles as `plooms`, which must all have the
+    /// same number of circles. `seed` seeds the streams from here on.
+    pub fn from_plooms(plooms: &[Ploom], seed: u64) -> Result<Swarm, String> {
+        let per_ploom = plooms.first().map_or(0, |p| p.circles.len());
+        let mut swarm = Swarm::empty(plooms.len(), per_ploom, seed);
+        for (i, ploom) in plooms.iter().enumerate() {
+            if ploom.circles.len() != per_ploom {
+                return Err(format!(
+                    "ploom {} has {} circles, expected {}",
+                    i,
+                    ploom.circles.len(),
+                    per_ploom
+                ));
+            }
+            swarm.push_ploom(ploom.focus, ploom.v * ploom.speed, ploom.sigma);
+            swarm.last_focus[i] = ploom.last_focus;
+            swarm.angle[i] = ploom.angle;
+            for circle in &ploom.circles {
+                swarm.push_circle(circle.x, circle.r);
+                *swarm.last.last_mut().unwrap() = circle.last;
+            }
+        }
+        Ok(swarm)
+    }
+
+    fn empty(plooms: usize, per_ploom: usize, seed: u64) -> Swarm {
+        let circles = plooms * per_ploom;
+        Swarm {
+            per_ploom,
+            focus: Vec::with_capacity(plooms),
+            last_focus: Vec::with_capacity(plooms),
+            v: Vec::with_capacity(plooms),
+            sigma: Vec::with_capacity(plooms),
+            angle: Vec::with_capacity(plooms),
+            cholesky: vec![(0.0, 0.0, 0.0); plooms],
+            x: Vec::with_capacity(circles),
+            last: Vec::with_capacity(circles),
+            r: Vec::with_capacity(circles),
+            seed,
+            ticks: 0,
+        }
+    }
+
+    fn push_ploom(&mut self, focus: Vec2, v: Vec2, sigma: f32) {
+        self.focus.push(focus);
+        self.last_focus.push(focus);
+        self.v.push(v);
+        self.sigma.push(sigma);
+        self.angle.push(v.y.atan2(v.x));
+    }
+
+    fn push_circle(&mut self, x: Vec2, r: f32) {
+        self.x.push(x);
+        self.last.push(x);
+        self.r.push(r);
+    }
+
+    pub fn num_plooms(&self) -> usize {
+        self.focus.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.x.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.x.is_empty()
+    }
+
+    /// Whether `plooms` are still the ones stepped last, i.e. nothing but
+    /// `write_to` changed their foci, velocities, sigmas or circles since.
+    pub fn matches(&self, plooms: &[Ploom]) -> bool {
+        plooms.len() == self.num_plooms()
+            && plooms.iter().enumerate().all(|(i, ploom)| {
+                let first = i * self.per_ploom;
+                ploom.circles.len() == self.per_ploom
+                    && ploom.focus == self.focus[i]
+                    && ploom.v * ploom.speed == self.v[i]
+                    && ploom.sigma == self.sigma[i]
+                    && ploom.circles.iter().enumerate().all(|(c, circle)| {
+                        circle.x == self.x[first + c] && circle.r == self.r[first + c]
+                    })
+            })
+    }
+
+    /// Copies the swarm back into `plooms`, which `matches` it, after a tick
+    /// of `dt` seconds, with the events `Ploom::step` would have emitted.
+    pub fn write_to(&self, plooms: &mut [Ploom], params: &PloomParams, dt: f32) {
+        for (i, ploom) in plooms.iter_mut().enumerate() {
+            ploom.t += dt;
+            let reset = ploom.sigma <= params.threshold;
+            if reset {
+                ploom.emit(Lifecycle::PloomReset);
+            }
+            let crossed = !reset && self.sigma[i] <= params.threshold;
+            ploom.focus = self.focus[i];
+            ploom.last_focus = self.last_focus[i];
+            ploom.sigma = self.sigma[i];
+            ploom.angle = self.angle[i];
+            if reset {
+                ploom.emit(Lifecycle::FocusSpawn);
+            } else if crossed {
+                ploom.emit(Lifecycle::SigmaThresholdCrossed);
+            }
+            let first = i * self.per_ploom;
+            for (c, circle) in ploom.circles.iter_mut().enumerate() {
+                circle.x = self.x[first + c];
+                circle.last = self.last[first + c];
+            }
+        }
+    }
+
+    /// Positions of the circles of ploom `p`.
+    pub fn circles(&self, p: usize) -> &[Vec2] {
+        &self.x[p * self.per_ploom..(p + 1) * self.per_ploom]
+    }
+
+    /// Circle `i` `alpha` of the way from the last tick to the current one.
+    pub fn lerp(&self, i: usize, alpha: f32) -> Vec2 {
+        self.last[i].lerp(self.x[i], alpha)
+    }
+
+    /// `Ploom::step` for every ploom over a tick of `dt` seconds, plooms and
+    /// then circles in parallel chunks. Returns how many plooms reset. The
+    /// result only depends on the seed and the tick, not on the thread count.
+    pub fn step(&mut self, params: &PloomParams, dt: f32) -> usize {
+        let (seed, tick) = (self.seed, self.ticks);
+        self.ticks += 1;
+        let factor = per_tick(params.decay, dt);
+
+        let resets = self
+            .focus
+            .par_chunks_mut(CHUNK)
+            .zip(self.last_focus.par_chunks_mut(CHUNK))
+            .zip(self.sigma.par_chunks_mut(CHUNK))
+            .zip(self.angle.par_chunks_mut(CHUNK))
+            .zip(self.cholesky.par_chunks_mut(CHUNK))
+            .zip(self.v.par_chunks(CHUNK))
+            .enumerate()
+            .map(|(k, (((((focus, last), sigma), angle), cholesky), v))| {
+                let mut rng = stream(seed, tick, 2 * k as u64);
+                let mut resets = 0;
+                for i in 0..focus.len() {
+                    last[i] = focus[i];
+                    if sigma[i] > params.threshold {
+                        sigma[i] *= factor;
+                        focus[i] += v[i] * dt;
+                        if let Align::Spin(rate) = params.align {
+                            angle[i] += rate * dt;
+                        }
+                    } else {
+                        focus[i] = rand_uniform_vec2(&mut rng, params.window_size);
+                        last[i] = focus[i];
+                        sigma[i] = params.ploom;
+                        resets += 1;
+                    }
+                    cholesky[i] =
+                        Covariance::rotated(sigma[i], params.stretch, angle[i]).cholesky();
+                }
+                resets
+            })
+            .sum();
+
+        let (per_ploom, focus, cholesky) = (self.per_ploom, &self.focus, &self.cholesky);
+        let (pull, scale) = (params.pull(dt), dt.sqrt());
+        self.x
+            .par_chunks_mut(CHUNK)
+            .zip(self.last.par_chunks_mut(CHUNK))
+            .zip(self.r.par_chunks(CHUNK))
+            .enumerate()
+            .for_each(|(k, ((x, last), r))| {
+                let mut rng = stream(seed, tick, 2 * k as u64 + 1);
+                last.copy_from_slice(x);
+                for i in 0..x.len() {
+                    let p = (k * CHUNK + i) / per_ploom;
+                    let (l11, l21, l22) = cholesky[p];
+                    let z = vec2(normal(&mut rng), normal(&mut rng));
+                    let dx = vec2(l11 * z.x, l21 * z.x + l22 * z.y) * scale / r[i];
+                    x[i] = kick(x[i], focus[p], dx, pull);
+                }
+            });
+        resets
+    }
+}
+
+/// `--swarm <threads>`: steps a sketch's plooms as a `Swarm` on a pool of
+/// `threads` threads, 0 for one per core. Only the original model runs on
+/// it, see `check`.
+pub struct SwarmRun {
+    pool: ThreadPool,
+    swarm: Option<Swarm>,
+    seed: u64,
+}
+
+impl SwarmRun {
+    /// Panics when `--swarm` comes with something `Swarm` doesn't model.
+    pub fn from_args(seed: u64, params: &PloomParams) -> Option<SwarmRun> {
+        let threads = arg::<usize>("--swarm")?;
+        for flag in ["--path", "--noise"] {
+            if arg::<String>(flag).is_some() {
+                panic!("--swarm can't run with {}", flag);
+            }
+        }
+        check(params).unwrap_or_else(|e| panic!("{}", e));
+        Some(SwarmRun::new(threads, seed))
+    }
+
+    pub fn new(threads: usize, seed: u64) -> SwarmRun {
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap_or_else(|e| panic!("--swarm: {}", e));
+        SwarmRun {
+            pool,
+            swarm: None,
+            seed,
+        }
+    }
+
+    /// `Swarm::step` on `plooms` over a tick of `dt` seconds. The swarm is
+    /// rebuilt from `plooms` whenever something else changed them, keeping
+    /// its tick count so the streams carry on. Fails when `params` or the
+    /// plooms ask for more than `Swarm` models.
+    pub fn step(
+        &mut self,
+        plooms: &mut [Ploom],
+        params: &PloomParams,
+        dt: f32,
+    ) -> Result<usize, String> {
+        check(params)?;
+        let swarm = match self.swarm.take() {
+            Some(swarm) if swarm.matches(plooms) => swarm,
+            last => {
+                let mut swarm = Swarm::from_plooms(plooms, self.seed)?;
+                swarm.ticks = last.map_or(0, |last| last.ticks);
+                swarm
+            }
+        };
+        let swarm = self.swarm.insert(swarm);
+        let resets = self.pool.install(|| swarm.step(params, dt));
+        swarm.write_to(plooms, params, dt);
+        Ok(resets)
+    }
+}
+
+/// Whether `Swarm` can step plooms under `params`: it only knows the open
+/// canvas.
+pub fn check(params: &PloomParams) -> Result<(), String> {
+    match params.boundary {
+        Boundary::Open => Ok(()),
+        other => Err(format!("--swarm can't run with --boundary {}", other)),
+    }
+}
+
+fn normal(rng: &mut StdRng) -> f32 {
+    let z: f64 = rng.sample(StandardNormal);
+    z as f32
+}
+
+/// RNG for stream `k` of tick `tick`, splitmix64 over the three so
+/// neighbouring streams and ticks start far apart.
+fn stream(seed: u64, tick: u64, k: u64) -> StdRng {
+    let mut z =
+        seed ^ tick.wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ k.wrapping_mul(0xD1B5_4A32_D192_ED03);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    seeded_rng(z ^ (z >> 31))
+}
@@ -0,0 +1,102 @@
+use nannou::color::IntoLinSrgba;
+use nannou::prelude::*;
+use proto::accum::{Accum, ToneMap, Trails};
+use proto::canvas::Blend;
+use proto::paint::Painter;
+
+fn center(accum: &Accum) -> [f32; 3] {
+    let (w, h) = (accum.canvas.width, accum.canvas.height);
+    accum.canvas.pixels[(h / 2 * w + w / 2) as usize]
+}
+
+#[test]
+fn explicit_decay_reaches_true_black() {
+    let trails = Trails {
+        decay: Some(0.5),
+        ..Trails::default()
+    };
+    let mut accum = Accum::new(32, 32, trails);
+    accum.begin(1.0 / 60.0);
+    accum.background(BLACK);
+    accum.ellipse(Vec2::ZERO, 8.0, WHITE);
+    for _ in 0..60 {
+        accum.begin(1.0 / 60.0);
+        accum.background(BLACK); // only sets what to decay to
+    }
+    assert!((center(&accum)[0] - 0.5).abs() < 1e-4);
+    for _ in 0..60 * 20 {
+        accum.begin(1.0 / 60.0);
+    }
+    let image = accum.to_image();
+    assert!(image.pixels().all(|p| p.0 == [0, 0, 0]));
+}
+
+#[test]
+fn sketch_fades_are_frame_rate_independent() {
+    let at = |fps: u32| {
+        let mut accum = Accum::new(16, 16, Trails::default());
+        accum.begin(1.0 / fps as f32);
+        accum.background(BLACK);
+        accum.ellipse(Vec2::ZERO, 8.0, WHITE);
+        for _ in 0..fps {
+            accum.begin(1.0 / fps as f32);
+            accum.fade(vec2(16.0, 16.0), Rgba::new(0.0, 0.0, 0.0, 0.1));
+        }
+        center(&accum)[0]
+    };
+    // alpha is per frame at 60fps
+    assert!((at(60) - 0.9f32.powi(60)).abs() < 1e-5);
+    assert!((at(30) - at(60)).abs() < 1e-5);
+    assert!((at(144) - at(60)).abs() < 1e-5);
+}
+
+#[test]
+fn fades_head_for_their_color() {
+    let mut accum = Accum::new(16, 16, Trails::default());
+    accum.begin(1.0 / 60.0);
+    accum.background(BLACK);
+    for _ in 0..600 {
+        accum.begin(1.0 / 60.0);
+        accum.fade(vec2(16.0, 16.0), Rgba::new(0.2, 0.4, 0.6, 0.1));
+    }
+    let target = IntoLinSrgba::<f32>::into_lin_srgba(Rgba::new(0.2, 0.4, 0.6, 1.0));
+    let pixel = center(&accum);
+    assert!((pixel[2] - target.blue).abs() < 1e-4);
+}
+
+#[test]
+fn additive_blending_goes_past_white() {
+    let paint = |blend| {
+        let trails = Trails {
+            blend,
+            ..Trails::default()
+        };
+        let mut accum = Accum::new(16, 16, trails);
+        accum.begin(1.0 / 60.0);
+        for _ in 0..4 {
+            accum.ellipse(Vec2::ZERO, 6.0, Rgba::new(1.0, 1.0, 1.0, 0.5));
+        }
+        center(&accum)[0]
+    };
+    assert!((paint(Blend::Add) - 2.0).abs() < 1e-5);
+    assert!((paint(Blend::Alpha) - (1.0 - 0.5f32.powi(4))).abs() < 1e-5);
+}
+
+#[test]
+fn tone_maps_squash_hdr_into_range() {
+    assert_eq!(ToneMap::Clamp.apply(3.0), 3.0);
+    assert_eq!(ToneMap::Reinhard.apply(1.0), 0.5);
+    assert!(ToneMap::Aces.apply(0.0).abs() < 1e-6);
+    assert!((ToneMap::Log.apply(16.0) - 1.0).abs() < 1e-6);
+    for tone in [ToneMap::Reinhard, ToneMap::Aces, ToneMap::Log] {
+        let mut last = -1.0;
+        for i in 0..100 {
+            let v = tone.apply(i as f32 * 0.15); // up to LOG_WHITE
+            assert!(v > last && v <= 1.05, "{:?}", tone);
+            last = v;
+        }
+    }
+    assert_eq!("aces".parse::<ToneMap>().unwrap(), ToneMap::Aces);
+    assert_eq!("add".parse::<Blend>().unwrap(), Blend::Add);
+    assert!("hdr".parse::<ToneMap>().is_err());
+}
@@ -0,0 +1,116 @@
+use proto::audio::{Analysis, Audio, Track};
+use proto::ploom::{Ploom, PloomParams};
+use proto::rng::seeded_rng;
+use std::f32::consts::TAU;
+use std::path::PathBuf;
+
+const RATE: u32 = 44100;
+
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join("nomos_audio_tests");
+    std::fs::create_dir_all(&dir).unwrap();
+    dir.join(name)
+}
+
+fn tone(hz: f32, seconds: f32) -> Vec<f32> {
+    (0..(seconds * RATE as f32) as usize)
+        .map(|i| 0.5 * (TAU * hz * i as f32 / RATE as f32).sin())
+        .collect()
+}
+
+fn track(samples: Vec<f32>) -> Track {
+    Track {
+        rate: RATE,
+        samples,
+    }
+}
+
+#[test]
+fn loads_stereo_pcm_as_mono() {
+    let path = scratch("stereo.wav");
+    let spec = hound::WavSpec {
+        channels: 2,
+        sample_rate: 22050,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+    for _ in 0..100 {
+        writer.write_sample(i16::MAX).unwrap();
+        writer.write_sample(0i16).unwrap();
+    }
+    writer.finalize().unwrap();
+
+    let track = Track::load(&path).unwrap();
+    assert_eq!(track.rate, 22050);
+    assert_eq!(track.samples.len(), 100);
+    assert!(track.samples.iter().all(|s| (s - 0.5).abs() < 1e-3));
+    assert!(Track::load(&scratch("missing.wav")).is_err());
+}
+
+#[test]
+fn tones_land_in_their_band() {
+    let analysis = Analysis::new(&track(tone(80.0, 0.5)), 30.0);
+    let f = analysis.frame(7);
+    assert!(f.bands[0] > 0.9 && f.bands[3] < 0.1, "{:?}", f);
+
+    let mut samples = tone(80.0, 0.5);
+    samples.extend(tone(5000.0, 0.5));
+    let analysis = Analysis::new(&track(samples), 30.0);
+    assert_eq!(analysis.frames.len(), 30);
+    let (low, high) = (analysis.frame(5), analysis.frame(25));
+    assert!(low.bands[0] > high.bands[0]);
+    assert!(high.bands[3] > low.bands[3]);
+    assert!((high.bands[3] - 1.0).abs() < 1e-3);
+    assert_eq!(analysis.frame(30), Default::default());
+}
+
+#[test]
+fn loudness_is_frame_exact() {
+    // silence for 30 frames at 60fps, then a tone
+    let mut samples = vec![0.0; 30 * RATE as usize / 60];
+    samples.extend(tone(440.0, 0.5));
+    let analysis = Analysis::new(&track(samples), 60.0);
+    assert_eq!(analysis.frame(29).rms, 0.0);
+    assert!(analysis.frame(30).rms > 0.99);
+}
+
+#[test]
+fn ticks_follow_the_render_frames() {
+    let analysis = Analysis::new(&track(tone(440.0, 1.0)), 30.0);
+    let mut audio = Audio::new(analysis);
+    let frames: Vec<usize> = (0..8).map(|_| audio.tick(1.0 / 60.0)).collect();
+    assert_eq!(frames, [0, 0, 1, 1, 2, 2, 3, 3]);
+    // no drift after an hour of ticks
+    let mut audio = Audio::new(Analysis::new(&track(vec![]), 24.0));
+    let mut last = 0;
+    for _ in 0..60 * 3600 {
+        last = audio.tick(1.0 / 60.0);
+    }
+    assert_eq!(last, 24 * 3600 - 1);
+}
+
+#[test]
+fn bass_onsets_kick_sigma_once() {
+    let mut samples = vec![0.0; RATE as usize / 2];
+    samples.extend(tone(60.0, 0.5));
+    let mut audio = Audio::new(Analysis::new(&track(samples), 60.0));
+    let params = PloomParams {
+        decay: 1.0,
+        ..PloomParams::default()
+    };
+    let mut rng = seeded_rng(1);
+    let mut plooms = Ploom::spawn_random_plooms(1, &params, &mut rng);
+    let mut sigmas = Vec::new();
+    for _ in 0..60 {
+        audio.tick(1.0 / 60.0);
+        audio.modulate(&mut plooms, &params);
+        sigmas.push(plooms[0].sigma);
+    }
+    // the FFT window reaches a frame and a half ahead of the onset
+    assert_eq!(sigmas[27], params.ploom);
+    // the bass ramps from 0 to 1 over a few frames, kicks add up to `ploom`
+    let kicked = sigmas.last().unwrap() - params.ploom;
+    assert!((kicked - params.ploom).abs() < 0.1, "{}", kicked);
+    assert!(plooms[0].speed > 2.9);
+}
@@ -0,0 +1,97 @@
+use proto::boundary::{reflect, wrap, Boundary};
+use proto::focus::FocusPath;
+use proto::ploom::{Ploom, PloomParams};
+use proto::rng::seeded_rng;
+
+fn run(boundary: Boundary, path: FocusPath, speed: f32, mut check: impl FnMut(&Ploom)) {
+    let params = PloomParams {
+        num_plooms: 20,
+        decay: 0.9, // resets rarely, so foci get to the edges
+        boundary,
+        ..PloomParams::default()
+    };
+    let mut rng = seeded_rng(21);
+    let mut plooms = Ploom::spawn_random_plooms(params.num_plooms, &params, &mut rng);
+    for ploom in plooms.iter_mut() {
+        ploom.v *= speed;
+        ploom.path = path.clone();
+    }
+    for _ in 0..60 * 60 {
+        for ploom in plooms.iter_mut() {
+            ploom.step(&params, 1.0 / 60.0, &mut rng);
+            check(ploom);
+        }
+    }
+}
+
+fn inside(p: nannou::prelude::Vec2, half: f32) -> bool {
+    p.x.abs() <= half && p.y.abs() <= half
+}
+
+#[test]
+fn folding_helpers() {
+    let p = wrap(nannou::prelude::vec2(510.0, -1490.0), 500.0);
+    assert!((p.x + 490.0).abs() < 1e-3 && (p.y + 490.0).abs() < 1e-3);
+    let (p, flip) = reflect(nannou::prelude::vec2(510.0, 20.0), 500.0);
+    assert!((p.x - 490.0).abs() < 1e-3 && (p.y - 20.0).abs() < 1e-3);
+    assert_eq!((flip.x, flip.y), (-1.0, 1.0));
+}
+
+#[test]
+fn wrap_bounce_and_respawn_keep_foci_on_the_canvas() {
+    for boundary in [Boundary::Wrap, Boundary::Bounce, Boundary::Respawn] {
+        run(boundary, FocusPath::Drift, 8.0, |ploom| {
+            assert!(
+                inside(ploom.focus, 500.0),
+                "{:?} {:?}",
+                boundary,
+                ploom.focus
+            );
+        });
+    }
+}
+
+#[test]
+fn bounce_and_respawn_keep_circles_on_the_canvas() {
+    for boundary in [Boundary::Bounce, Boundary::Respawn] {
+        run(boundary, FocusPath::Drift, 8.0, |ploom| {
+            assert!(ploom.circles.iter().all(|c| inside(c.x, 500.0)));
+        });
+    }
+}
+
+#[test]
+fn attract_bounds_drift_and_walks() {
+    let mut far = 0.0f32;
+    run(Boundary::Attract(1.0), FocusPath::Drift, 1.0, |ploom| {
+        far = far.max(ploom.focus.length());
+    });
+    run(
+        Boundary::Attract(1.0),
+        FocusPath::RandomWalk { step: 4.0 },
+        1.0,
+        |ploom| {
+            far = far.max(ploom.focus.length());
+        },
+    );
+    assert!(far < 1000.0, "{}", far);
+
+    let mut open = 0.0f32;
+    run(Boundary::Open, FocusPath::Drift, 1.0, |ploom| {
+        open = open.max(ploom.focus.length());
+    });
+    assert!(open > 1000.0, "{}", open);
+}
+
+#[test]
+fn parses_and_round_trips() {
+    for s in ["open", "wrap", "bounce", "attract:0.25", "respawn"] {
+        let boundary: Boundary = s.parse().unwrap();
+        assert_eq!(boundary.to_string(), s);
+    }
+    assert_eq!(
+        "attract".parse::<Boundary>().unwrap(),
+        Boundary::Attract(0.5)
+    );
+    assert!("sticky".parse::<Boundary>().is_err());
+}
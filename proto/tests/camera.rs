@@ -0,0 +1,115 @@
+use nannou::prelude::*;
+use proto::camera::{Camera, Projection};
+
+const SIZE: u32 = 1000;
+
+fn close(a: Vec2, b: Vec2) -> bool {
+    a.distance(b) < 1e-3
+}
+
+#[test]
+fn default_view_matches_the_window() {
+    let camera = Camera::new(SIZE);
+    let (right, up, forward) = camera.basis();
+    assert!(right.distance(Vec3::X) < 1e-6);
+    assert!(up.distance(Vec3::Y) < 1e-6);
+    assert!(forward.distance(-Vec3::Z) < 1e-6);
+
+    let target = camera.project(Vec3::ZERO).unwrap();
+    assert!(close(target.xy, Vec2::ZERO));
+    assert_eq!(target.depth, camera.distance);
+    assert!((target.scale - camera.focal() / camera.distance).abs() < 1e-6);
+    // the whole cube fits in the window
+    let bound = SIZE as f32 / 2.0;
+    let corner = camera.project(vec3(bound, bound, bound)).unwrap();
+    assert!(corner.xy.x < bound && corner.xy.y < bound && corner.xy.x > 0.0);
+}
+
+#[test]
+fn perspective_shrinks_with_depth_orthographic_does_not() {
+    let mut camera = Camera::new(SIZE);
+    let (near, far) = (vec3(100.0, 50.0, 200.0), vec3(100.0, 50.0, -200.0));
+    let (n, f) = (camera.project(near).unwrap(), camera.project(far).unwrap());
+    assert!(n.depth < f.depth && n.scale > f.scale);
+    assert!(n.xy.length() > f.xy.length());
+    // scale is focal length over depth
+    assert!((n.scale * n.depth - camera.focal()).abs() < 1e-2);
+    // nothing behind the eye
+    assert_eq!(camera.project(camera.eye() * 2.0), None);
+
+    camera.projection = camera.projection.next();
+    assert_eq!(camera.projection, Projection::Orthographic);
+    let (n, f) = (camera.project(near).unwrap(), camera.project(far).unwrap());
+    assert_eq!(n.scale, f.scale);
+    assert!(close(n.xy, f.xy));
+    assert!(camera.project(camera.eye() * 2.0).is_some());
+}
+
+#[test]
+fn orbiting_keeps_the_eye_on_its_sphere() {
+    let mut camera = Camera::new(SIZE);
+    let distance = camera.distance;
+    camera.press(Vec2::ZERO);
+    // a quarter turn to the side
+    camera.drag_to(vec2(-PI / 2.0 / 0.01, 0.0));
+    assert!((camera.yaw - PI / 2.0).abs() < 1e-4);
+    assert!((camera.eye().distance(camera.target) - distance).abs() < 1e-2);
+    // +x now faces the eye, it's at the center and nearer than the target
+    let p = camera.project(vec3(100.0, 0.0, 0.0)).unwrap();
+    assert!(close(p.xy, Vec2::ZERO));
+    assert!((p.depth - (distance - 100.0)).abs() < 1e-2);
+
+    // pitch stops short of the poles, however far the drag
+    camera.drag_to(vec2(0.0, -1e5));
+    assert!(camera.pitch < PI / 2.0 && camera.pitch > 1.5);
+    assert!(camera.project(Vec3::ZERO).is_some());
+    camera.release();
+    let (yaw, pitch) = (camera.yaw, camera.pitch);
+    camera.drag_to(vec2(300.0, 300.0));
+    assert_eq!((camera.yaw, camera.pitch), (yaw, pitch));
+
+    camera.spin = 1.0;
+    camera.update(0.5);
+    assert_eq!(camera.yaw, yaw + 0.5);
+    camera.reset();
+    assert_eq!(
+        (camera.yaw, camera.pitch, camera.distance),
+        (0.0, 0.0, distance)
+    );
+}
+
+#[test]
+fn zoom_is_multiplicative_and_bounded() {
+    let mut camera = Camera::new(SIZE);
+    let distance = camera.distance;
+    camera.zoom(3.0);
+    assert!(camera.distance < distance);
+    camera.zoom(-3.0);
+    assert!((camera.distance - distance).abs() < 1e-2);
+    camera.zoom(1000.0);
+    assert_eq!(camera.distance, camera.focal() / 4.0);
+    camera.zoom(-1000.0);
+    assert_eq!(camera.distance, camera.focal() * 20.0);
+}
+
+#[test]
+fn fade_runs_from_front_to_back() {
+    let camera = Camera::new(SIZE);
+    let (d, range) = (camera.distance, camera.depth_range);
+    assert_eq!(camera.fade(d), 0.5);
+    assert_eq!(camera.fade(d - range / 2.0), 1.0);
+    assert_eq!(camera.fade(d + range / 2.0), 0.0);
+    assert_eq!(camera.fade(0.0), 1.0);
+    assert_eq!(camera.fade(1e6), 0.0);
+    let mut last = 1.0;
+    for n in 0..=100 {
+        let fade = camera.fade(d - range / 2.0 + range * n as f32 / 100.0);
+        assert!(fade <= last);
+        last = fade;
+    }
+
+    for projection in [Projection::Perspective, Projection::Orthographic] {
+        assert_eq!(projection.to_string().parse(), Ok(projection));
+    }
+    assert!("fisheye".parse::<Projection>().is_err());
+}
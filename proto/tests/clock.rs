@@ -0,0 +1,44 @@
+use proto::clock::{per_tick, Clock, TICK_RATE};
+
+#[test]
+fn matching_frame_rate_ticks_once_per_frame() {
+    let mut clock = Clock::default();
+    for _ in 0..10_000 {
+        assert_eq!(clock.advance(1.0 / TICK_RATE as f64), 1);
+        assert_eq!(clock.alpha(), 0.0);
+    }
+}
+
+#[test]
+fn ticks_follow_real_time_at_any_refresh_rate() {
+    for fps in [30.0, 75.0, 144.0, 240.0] {
+        let mut clock = Clock::default();
+        let ticks: u32 = (0..(fps as u32 * 10))
+            .map(|_| clock.advance(1.0 / fps))
+            .sum();
+        assert!(
+            (ticks as i64 - 600).abs() <= 1,
+            "{} fps: {} ticks",
+            fps,
+            ticks
+        );
+        assert!((0.0..=1.0).contains(&clock.alpha()));
+    }
+}
+
+#[test]
+fn stalls_are_capped() {
+    let mut clock = Clock::default();
+    assert_eq!(clock.advance(5.0), clock.max_ticks);
+    assert!(clock.advance(0.0) <= 1);
+}
+
+#[test]
+fn per_tick_rates_compound_to_per_second() {
+    let dt = 1.0 / TICK_RATE;
+    let mut x = 1.0f32;
+    for _ in 0..TICK_RATE as usize {
+        x *= per_tick(0.3, dt);
+    }
+    assert!((x - 0.3).abs() < 1e-4);
+}
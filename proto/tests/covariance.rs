@@ -0,0 +1,134 @@
+use nannou::prelude::*;
+use proto::covariance::{Align, Covariance};
+use proto::ploom::{Ploom, PloomParams};
+use proto::rng::seeded_rng;
+use std::f32::consts::FRAC_PI_2;
+
+fn empirical(covariance: &Covariance, n: usize, seed: u64) -> Covariance {
+    let mut rng = seeded_rng(seed);
+    let samples: Vec<Vec2> = (0..n).map(|_| covariance.sample(&mut rng)).collect();
+    let mean = samples.iter().fold(Vec2::ZERO, |m, x| m + *x) / n as f32;
+    let (mut xx, mut xy, mut yy) = (0.0, 0.0, 0.0);
+    for x in &samples {
+        let d = *x - mean;
+        xx += d.x * d.x;
+        xy += d.x * d.y;
+        yy += d.y * d.y;
+    }
+    let n = (n - 1) as f32;
+    Covariance {
+        xx: xx / n,
+        xy: xy / n,
+        yy: yy / n,
+    }
+}
+
+fn assert_close(a: &Covariance, b: &Covariance, tolerance: f32) {
+    let scale = b.xx.max(b.yy);
+    for (x, y) in [(a.xx, b.xx), (a.xy, b.xy), (a.yy, b.yy)] {
+        assert!(
+            (x - y).abs() <= tolerance * scale,
+            "{:?} is not close to {:?}",
+            a,
+            b
+        );
+    }
+}
+
+#[test]
+fn cholesky_reconstructs_covariance() {
+    for (sigma, stretch, angle) in [(1.0, 1.0, 0.0), (20.0, 3.0, 0.7), (0.5, 0.2, -2.0)] {
+        let c = Covariance::rotated(sigma, stretch, angle);
+        let (l11, l21, l22) = c.cholesky();
+        let reconstructed = Covariance {
+            xx: l11 * l11,
+            xy: l11 * l21,
+            yy: l21 * l21 + l22 * l22,
+        };
+        assert_close(&reconstructed, &c, 1e-5);
+    }
+}
+
+#[test]
+fn rotated_keeps_area_and_axes() {
+    let c = Covariance::rotated(4.0, 3.0, FRAC_PI_2);
+    assert!((c.xx - (4.0f32 / 3.0).powi(2)).abs() < 1e-3);
+    assert!((c.yy - 144.0).abs() < 1e-3);
+    let det = c.xx * c.yy - c.xy * c.xy;
+    assert!((det - 4.0f32.powi(4)).abs() < 1e-1);
+    assert!(Covariance::rotated(2.0, 1.0, 1.234).xy.abs() < 1e-6);
+}
+
+#[test]
+fn empirical_covariance_matches() {
+    let cases = [
+        Covariance::isotropic(1.0),
+        Covariance::isotropic(20.0),
+        Covariance::rotated(5.0, 4.0, 0.3),
+        Covariance::rotated(1.0, 2.0, -1.2),
+        Covariance {
+            xx: 2.0,
+            xy: -1.5,
+            yy: 3.0,
+        },
+    ];
+    for (seed, c) in cases.iter().enumerate() {
+        assert_close(&empirical(c, 200_000, seed as u64), c, 0.02);
+    }
+}
+
+#[test]
+fn degenerate_covariance_samples_a_line() {
+    let c = Covariance {
+        xx: 4.0,
+        xy: 2.0,
+        yy: 1.0,
+    };
+    let mut rng = seeded_rng(9);
+    for _ in 0..100 {
+        let x = c.sample(&mut rng);
+        assert!((x.x - 2.0 * x.y).abs() < 1e-3);
+    }
+}
+
+#[test]
+fn velocity_alignment_follows_focus() {
+    let params = PloomParams {
+        stretch: 3.0,
+        align: Align::Velocity,
+        ..PloomParams::default()
+    };
+    let mut rng = seeded_rng(5);
+    let mut ploom = Ploom::new(&params, &mut rng);
+    ploom.v = vec2(0.0, -2.0);
+    ploom.update(&params, 1.0 / 60.0, &mut rng);
+    assert!((ploom.angle + FRAC_PI_2).abs() < 1e-6);
+    let c = ploom.covariance(&params);
+    assert!(c.yy > c.xx * 80.0);
+}
+
+#[test]
+fn spin_is_per_second_at_any_tick_rate() {
+    let params = PloomParams {
+        align: Align::Spin(FRAC_PI_2),
+        threshold: 0.0, // no resets
+        ..PloomParams::default()
+    };
+    for rate in [30, 60, 240] {
+        let mut rng = seeded_rng(6);
+        let mut ploom = Ploom::new(&params, &mut rng);
+        let start = ploom.angle;
+        for _ in 0..rate * 2 {
+            ploom.update(&params, 1.0 / rate as f32, &mut rng);
+        }
+        assert!((ploom.angle - start - PI).abs() < 1e-4, "{}", rate);
+    }
+}
+
+#[test]
+fn align_parses() {
+    assert_eq!("fixed".parse::<Align>(), Ok(Align::Fixed));
+    assert_eq!("velocity".parse::<Align>(), Ok(Align::Velocity));
+    assert_eq!("spin:0.05".parse::<Align>(), Ok(Align::Spin(0.05)));
+    assert!("spin".parse::<Align>().is_err());
+}
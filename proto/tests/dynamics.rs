@@ -0,0 +1,258 @@
+use nannou::prelude::*;
+use proto::clock::{per_tick, TICK_RATE};
+use proto::harness::{ploom_d, ploom_e, ploom_f};
+use proto::noise::{Gaussian, Levy, NoiseProcess, OrnsteinUhlenbeck};
+use proto::ploom::{Ploom, PloomParams};
+use proto::ploom3::Ploom3;
+use proto::rng::seeded_rng;
+use proto::swarm::Swarm;
+use rand::rngs::StdRng;
+use std::sync::Arc;
+
+const DT: f32 = 1.0 / TICK_RATE;
+
+/// The presets of ploom_d, ploom_e and ploom_f.
+fn sketches() -> [PloomParams; 3] {
+    [ploom_d(), ploom_e(), ploom_f()]
+}
+
+fn spawn(params: &PloomParams, seed: u64) -> (Vec<Ploom>, StdRng) {
+    let mut rng = seeded_rng(seed);
+    let plooms = Ploom::spawn_random_plooms(params.num_plooms, params, &mut rng);
+    (plooms, rng)
+}
+
+/// Ticks after a reset (or spawn) until the next one: `sigma` shrinks by
+/// `decay^dt` until it's no more than `threshold`, the tick after resets.
+fn reset_period(params: &PloomParams) -> usize {
+    let decays = (params.threshold / params.ploom).ln() / per_tick(params.decay, DT).ln();
+    decays.ceil() as usize + 1
+}
+
+#[test]
+fn sigma_decays_geometrically_to_threshold() {
+    for params in sketches() {
+        let (mut plooms, mut rng) = spawn(&params, 1);
+        let ploom = &mut plooms[0];
+        let factor = per_tick(params.decay, DT);
+        let mut last = ploom.sigma;
+        for _ in 1..reset_period(&params) {
+            ploom.step(&params, DT, &mut rng);
+            assert!((ploom.sigma / last / factor - 1.0).abs() < 1e-5);
+            last = ploom.sigma;
+        }
+        // it ends at the threshold, not somewhere past it
+        assert!(last <= params.threshold && last > params.threshold * factor);
+
+        // a second of ticks takes off `decay`, whatever the tick rate
+        let (mut plooms, mut rng) = spawn(&params, 1);
+        for _ in 0..TICK_RATE as usize {
+            plooms[0].step(&params, DT, &mut rng);
+        }
+        let expected = params.ploom * params.decay;
+        assert!((plooms[0].sigma / expected - 1.0).abs() < 1e-4);
+    }
+}
+
+#[test]
+fn plooms_reset_with_the_period_implied_by_decay_and_threshold() {
+    for params in sketches() {
+        let period = reset_period(&params);
+        // about ln(threshold / ploom) / ln(decay) seconds
+        let seconds = (params.threshold / params.ploom).ln() / params.decay.ln();
+        assert!((period as f32 * DT - seconds).abs() <= 2.0 * DT);
+
+        let (mut plooms, mut rng) = spawn(&params, 2);
+        let mut resets: Vec<Vec<usize>> = vec![Vec::new(); plooms.len()];
+        for tick in 1..=period * 5 {
+            for (i, ploom) in plooms.iter_mut().enumerate() {
+                if ploom.step(&params, DT, &mut rng) {
+                    resets[i].push(tick);
+                    assert_eq!(ploom.sigma, params.ploom);
+                }
+            }
+        }
+        for ticks in resets {
+            let expected: Vec<usize> = (1..=5).map(|n| n * period).collect();
+            assert_eq!(ticks, expected, "period {}", period);
+        }
+    }
+}
+
+#[test]
+fn circles_disperse_around_the_focus_as_predicted() {
+    // circles relax towards a focus that moves by `v dt` every tick, with d
+    // the pull over a tick: y' = (1 - d)(y - v dt) + e, e ~ N(0, (sigma / r)^2
+    // dt I), so around a lag of -v dt (1 - d) / d they spread with variance
+    // (sigma / r)^2 dt / (1 - (1 - d)^2) per axis
+    for dampening in [0.5, 0.9] {
+        let params = PloomParams {
+            num_plooms: 40,
+            num_circles: 4,
+            decay: 1.0, // sigma stays at `ploom`
+            dampening,
+            ..PloomParams::default()
+        };
+        let (mut plooms, mut rng) = spawn(&params, 3);
+        let keep = 1.0 - params.pull(DT);
+        let variance = params.ploom.powi(2) * DT / (1.0 - keep * keep);
+        let (mut scaled, mut lag, mut n) = (0.0f64, Vec2::ZERO, 0);
+        for tick in 0..6000 {
+            for ploom in plooms.iter_mut() {
+                ploom.step(&params, DT, &mut rng);
+            }
+            if tick < 1000 {
+                continue; // burn in
+            }
+            for ploom in &plooms {
+                let expected = -ploom.v * DT * keep / (1.0 - keep);
+                for circle in &ploom.circles {
+                    let y = circle.x - ploom.focus;
+                    // r scales the kicks, undo it so every circle counts the same
+                    scaled += ((y - expected) * circle.r).length_squared() as f64 / 2.0;
+                    lag += (y - expected) * circle.r;
+                    n += 1;
+                }
+            }
+        }
+        let measured = (scaled / n as f64) as f32;
+        assert!(
+            (measured / variance - 1.0).abs() < 0.05,
+            "dampening {}: {} vs {}",
+            dampening,
+            measured,
+            variance
+        );
+        // and the lag is right, the offsets average out
+        assert!((lag / n as f32).length() < 0.05 * variance.sqrt());
+    }
+}
+
+#[test]
+fn no_circle_diverges() {
+    // how many spreads a settled circle may stray past its lag, heavy tailed
+    // jumps only have the cap at the window size
+    let processes: [(&str, Arc<dyn NoiseProcess>, f32); 3] = [
+        ("gaussian", Arc::new(Gaussian), 6.0),
+        ("ou", Arc::new(OrnsteinUhlenbeck), 6.0),
+        ("levy:1.5", Arc::new(Levy { alpha: 1.5 }), 50.0),
+    ];
+    for params in sketches() {
+        let params = PloomParams {
+            num_plooms: 30,
+            ..params
+        };
+        let period = reset_period(&params);
+        let keep = 1.0 - params.pull(DT);
+        // per axis spread of a circle of radius 1 at the initial sigma
+        let spread = params.ploom * (DT / (1.0 - keep * keep)).sqrt();
+        // what's left of a teleport half a period later
+        let teleport = 2.0 * params.window_size as f32 * keep.powi(period as i32 / 2);
+        for (name, noise, bound) in &processes {
+            let (mut plooms, mut rng) = spawn(&params, 4);
+            for ploom in plooms.iter_mut() {
+                ploom.noise = noise.clone();
+            }
+            let mut worst = 0.0f32;
+            // several resets, each teleporting the focus away from its circles
+            for tick in 1..=period * 6 {
+                let settled = tick % period > period / 2;
+                for ploom in plooms.iter_mut() {
+                    ploom.step(&params, DT, &mut rng);
+                    assert!(ploom.sigma.is_finite() && ploom.sigma <= params.ploom);
+                    let lag = ploom.v.length() * DT * keep / (1.0 - keep);
+                    for circle in &ploom.circles {
+                        assert!(circle.x.is_finite(), "{}", name);
+                        if settled {
+                            let distance = circle.x.distance(ploom.focus);
+                            worst = worst.max((distance - lag - teleport) / spread);
+                        }
+                    }
+                }
+            }
+            assert!(worst < *bound, "{}: {} spreads out", name, worst);
+        }
+    }
+}
+
+#[test]
+fn fixed_seeds_give_identical_runs() {
+    let params = sketches()[0].clone();
+    let run = |seed| {
+        let (mut plooms, mut rng) = spawn(&params, seed);
+        for _ in 0..500 {
+            for ploom in plooms.iter_mut() {
+                ploom.step(&params, DT, &mut rng);
+            }
+        }
+        plooms
+            .iter()
+            .flat_map(|p| p.circles.iter().map(|c| c.x))
+            .collect::<Vec<Vec2>>()
+    };
+    assert_eq!(run(5), run(5));
+    assert_ne!(run(5), run(6));
+}
+
+#[test]
+fn radii_at_or_below_one_spawn_plain_circles() {
+    // what an OSC fader or a preset can set
+    for max_radius in [1.0, 0.5, 0.0] {
+        let params = PloomParams {
+            num_plooms: 3,
+            max_radius,
+            ..PloomParams::default()
+        };
+        let (plooms, mut rng) = spawn(&params, 2);
+        assert!(plooms.iter().all(|p| p.circles.iter().all(|c| c.r == 1.0)));
+        let cloud = Ploom3::spawn_random_plooms(3, &params, &mut rng);
+        assert!(cloud.iter().all(|p| p.circles.iter().all(|c| c.r == 1.0)));
+        assert!(Swarm::new(&params, 2).r.iter().all(|&r| r == 1.0));
+    }
+}
+
+#[test]
+fn motion_does_not_depend_on_the_tick_rate() {
+    let params = PloomParams {
+        num_plooms: 40,
+        decay: 1.0, // sigma stays at `ploom`
+        ..PloomParams::default()
+    };
+    // spread around the focus and distance drifted over ten seconds
+    let run = |dt: f32| {
+        let (mut plooms, mut rng) = spawn(&params, 6);
+        let start: Vec<Vec2> = plooms.iter().map(|p| p.focus).collect();
+        let (mut spread, mut n) = (0.0f64, 0);
+        for tick in 0..(10.0 / dt).round() as usize {
+            for ploom in plooms.iter_mut() {
+                ploom.step(&params, dt, &mut rng);
+            }
+            if tick as f32 * dt > 5.0 {
+                for ploom in &plooms {
+                    for circle in &ploom.circles {
+                        let y = (circle.x - ploom.focus) * circle.r;
+                        spread += y.length_squared() as f64 / 2.0;
+                        n += 1;
+                    }
+                }
+            }
+        }
+        let drifted: f32 = plooms
+            .iter()
+            .zip(&start)
+            .map(|(p, s)| p.focus.distance(*s))
+            .sum();
+        ((spread / n as f64) as f32, drifted)
+    };
+    let (spread, drifted) = run(DT);
+    for dt in [DT / 2.0, DT * 2.0] {
+        let (other, other_drifted) = run(dt);
+        assert!(
+            (other / spread - 1.0).abs() < 0.1,
+            "{} vs {}",
+            other,
+            spread
+        );
+        assert!((other_drifted / drifted - 1.0).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,131 @@
+use proto::boundary::Boundary;
+use proto::burst::Bursts;
+use proto::event::{Events, Lifecycle};
+use proto::ploom::{Ploom, PloomParams};
+use proto::rng::seeded_rng;
+
+const DT: f32 = 1.0 / 60.0;
+
+fn run(params: &PloomParams, ticks: usize) -> Vec<proto::event::PloomEvent> {
+    let mut rng = seeded_rng(9);
+    let mut plooms = Ploom::spawn_random_plooms(params.num_plooms, params, &mut rng);
+    let mut events = Events::default();
+    let mut seen = Vec::new();
+    for _ in 0..ticks {
+        for ploom in plooms.iter_mut() {
+            ploom.step(params, DT, &mut rng);
+        }
+        events.collect(&mut plooms);
+        seen.extend(events.drain());
+    }
+    seen
+}
+
+#[test]
+fn every_ploom_reports_its_own_resets() {
+    let params = PloomParams {
+        num_plooms: 12,
+        ..PloomParams::default()
+    };
+    // sigma goes from 20 to 0.2 at 0.3 per second, ~3.8s per cycle
+    let events = run(&params, 60 * 10);
+    for i in 0..params.num_plooms {
+        let kinds: Vec<Lifecycle> = events
+            .iter()
+            .filter(|e| e.ploom == i)
+            .map(|e| e.kind)
+            .collect();
+        assert_eq!(kinds[0], Lifecycle::FocusSpawn);
+        let resets = kinds
+            .iter()
+            .filter(|&&k| k == Lifecycle::PloomReset)
+            .count();
+        assert!(resets >= 2, "ploom {}: {:?}", i, kinds);
+        // crossing, then the reset with its teleport, in that order
+        for w in kinds.windows(2) {
+            if w[0] == Lifecycle::SigmaThresholdCrossed {
+                assert_eq!(w[1], Lifecycle::PloomReset);
+            }
+            if w[0] == Lifecycle::PloomReset {
+                assert_eq!(w[1], Lifecycle::FocusSpawn);
+            }
+        }
+    }
+}
+
+#[test]
+fn reset_is_reported_where_the_focus_was() {
+    let params = PloomParams {
+        num_plooms: 1,
+        ..PloomParams::default()
+    };
+    let events = run(&params, 60 * 5);
+    let crossed = events
+        .iter()
+        .find(|e| e.kind == Lifecycle::SigmaThresholdCrossed)
+        .unwrap();
+    let reset = events
+        .iter()
+        .find(|e| e.kind == Lifecycle::PloomReset)
+        .unwrap();
+    let spawn = events
+        .iter()
+        .filter(|e| e.kind == Lifecycle::FocusSpawn)
+        .nth(1)
+        .unwrap();
+    // the drift moves the focus by v during the crossing tick only
+    assert!(crossed.at.distance(reset.at) < 10.0);
+    assert!(reset.at != spawn.at);
+}
+
+#[test]
+fn boundary_respawns_are_events_too() {
+    let params = PloomParams {
+        num_plooms: 10,
+        decay: 0.95,
+        boundary: Boundary::Respawn,
+        ..PloomParams::default()
+    };
+    let events = run(&params, 60 * 60);
+    for e in events.iter().filter(|e| e.kind == Lifecycle::FocusSpawn) {
+        assert!(e.at.x.abs() <= 500.0 && e.at.y.abs() <= 500.0);
+    }
+    let resets = events
+        .iter()
+        .filter(|e| e.kind == Lifecycle::PloomReset)
+        .count();
+    assert!(resets > 0);
+}
+
+#[test]
+fn bursts_grow_and_expire() {
+    let params = PloomParams {
+        num_plooms: 4,
+        ..PloomParams::default()
+    };
+    let mut bursts = Bursts::default();
+    for event in run(&params, 60 * 5) {
+        bursts.on_event(&event);
+    }
+    assert!(!bursts.rings.is_empty());
+    assert!(bursts
+        .rings
+        .iter()
+        .all(|r| r.age == 0.0 && r.at.x.abs() < 2000.0));
+    let lifetime = bursts.lifetime;
+    bursts.update(lifetime / 2.0);
+    assert!(!bursts.rings.is_empty());
+    bursts.update(lifetime / 2.0);
+    assert!(bursts.rings.is_empty());
+}
+
+#[test]
+fn uncollected_events_are_capped() {
+    let params = PloomParams::default();
+    let mut rng = seeded_rng(2);
+    let mut ploom = Ploom::new(&params, &mut rng);
+    for _ in 0..60 * 120 {
+        ploom.step(&params, DT, &mut rng);
+    }
+    assert!(ploom.events.len() <= 16);
+}
@@ -0,0 +1,139 @@
+use nannou::prelude::*;
+use proto::falloff::{EdgeStyle, Falloff};
+use proto::graph::EdgeMode;
+use proto::ploom::PloomParams;
+
+const CURVES: [Falloff; 5] = [
+    Falloff::Step,
+    Falloff::Linear,
+    Falloff::Smooth,
+    Falloff::Power(2.0),
+    Falloff::Power(0.5),
+];
+
+#[test]
+fn curves_fall_from_one_to_zero() {
+    for falloff in CURVES {
+        assert_eq!(falloff.apply(-1.0), 1.0, "{}", falloff);
+        assert_eq!(falloff.apply(0.0), 1.0, "{}", falloff);
+        assert_eq!(falloff.apply(1.0), 0.0, "{}", falloff);
+        assert_eq!(falloff.apply(2.0), 0.0, "{}", falloff);
+        let mut last = 1.0;
+        for n in 0..=100 {
+            let s = falloff.apply(n as f32 / 100.0);
+            assert!(
+                s <= last && (0.0..=1.0).contains(&s),
+                "{} at {}",
+                falloff,
+                n
+            );
+            last = s;
+        }
+    }
+    assert_eq!(Falloff::Smooth.apply(0.5), 0.5);
+    assert_eq!(Falloff::Power(2.0).apply(0.5), 0.25);
+}
+
+#[test]
+fn edges_vanish_continuously_at_max_distance() {
+    let (min, max) = (100.0, 150.0);
+    for falloff in [Falloff::Linear, Falloff::Smooth, Falloff::Power(2.0)] {
+        let style = EdgeStyle {
+            falloff,
+            ..EdgeStyle::default()
+        };
+        // no jump anywhere along the way, in particular not at `max`
+        let mut last = style.strength(0.0, min, max);
+        assert_eq!(last, 1.0);
+        for n in 1..=200 {
+            let s = style.strength(n as f32, min, max);
+            assert!((last - s).abs() < 0.05, "{} at {}", falloff, n);
+            last = s;
+        }
+        assert_eq!(style.strength(max, min, max), 0.0);
+        assert_eq!(style.weight(0.0), style.thin);
+        assert_eq!(style.weight(1.0), style.weight);
+    }
+
+    // step is the old cutoff
+    let step = EdgeStyle {
+        falloff: Falloff::Step,
+        ..EdgeStyle::default()
+    };
+    assert_eq!(step.strength(149.9, min, max), 1.0);
+    assert_eq!(step.strength(150.0, min, max), 0.0);
+}
+
+#[test]
+fn graph_edges_outlast_max_distance() {
+    // two clusters far apart, the spanning tree has to bridge them
+    let foci = [
+        vec2(0.0, 0.0),
+        vec2(30.0, 0.0),
+        vec2(0.0, 40.0),
+        vec2(400.0, 0.0),
+        vec2(420.0, 30.0),
+    ];
+    let (min, max) = (100.0, 150.0);
+    let edges = EdgeMode::Mst.edges(&foci, max);
+    let longest = edges.iter().map(|&(_, _, d)| d).fold(0.0, f32::max);
+    assert!(longest > max);
+    let style = EdgeStyle::default();
+    for mode in [EdgeMode::Mst, EdgeMode::Delaunay, EdgeMode::Knn(2)] {
+        let reach = mode.reach(max, longest);
+        for &(i, j, d) in &mode.edges(&foci, max) {
+            let strength = style.strength(d, min, reach);
+            assert!(strength > 0.0, "{} ({}, {})", mode, i, j);
+        }
+    }
+    // radius still ends at max_distance, and short graph edges look the same
+    assert_eq!(EdgeMode::Radius.reach(max, longest), max);
+    assert_eq!(EdgeMode::Mst.reach(max, 50.0), max);
+}
+
+#[test]
+fn palette_position_is_clamped() {
+    assert_eq!(EdgeStyle::t(50.0, 100.0, 150.0), 0.0);
+    assert_eq!(EdgeStyle::t(125.0, 100.0, 150.0), 0.5);
+    assert_eq!(EdgeStyle::t(400.0, 100.0, 150.0), 1.0);
+    // an empty band is a cutoff
+    assert_eq!(EdgeStyle::t(99.0, 100.0, 100.0), 0.0);
+    assert_eq!(EdgeStyle::t(100.0, 100.0, 100.0), 1.0);
+}
+
+#[test]
+fn envelope_is_bounded() {
+    let style = EdgeStyle::default();
+    assert_eq!(style.envelope(0.0), 0.0);
+    assert!(style.envelope(style.fade_in / 4.0) < 0.5);
+    assert_eq!(style.envelope(style.fade_in / 2.0), 0.5);
+    // the old `time / 500` kept growing, this stops at 1
+    for time in [style.fade_in, 500.0, 1e6] {
+        assert_eq!(style.envelope(time), 1.0);
+    }
+    let instant = EdgeStyle {
+        fade_in: 0.0,
+        ..EdgeStyle::default()
+    };
+    assert_eq!(instant.envelope(0.0), 1.0);
+}
+
+#[test]
+fn style_round_trips_through_presets() {
+    for falloff in CURVES {
+        assert_eq!(falloff.to_string().parse::<Falloff>(), Ok(falloff));
+    }
+    assert_eq!("power".parse::<Falloff>(), Ok(Falloff::Power(2.0)));
+    assert!("cubic".parse::<Falloff>().is_err());
+
+    let mut params = PloomParams::default();
+    params.edge_style.falloff = Falloff::Power(3.0);
+    params.edge_style.fade_in = 5.0;
+    let text = toml::to_string(&params).unwrap();
+    let back: PloomParams = toml::from_str(&text).unwrap();
+    assert_eq!(back.edge_style, params.edge_style);
+
+    // presets written before the table keep the defaults
+    let old: PloomParams = toml::from_str("max_distance = 120.0").unwrap();
+    assert_eq!(old.edge_style, EdgeStyle::default());
+}
@@ -0,0 +1,105 @@
+use nannou::prelude::*;
+use proto::focus::{follow, FocusPath, Gesture};
+use proto::ploom::{Ploom, PloomParams};
+use proto::rng::seeded_rng;
+
+fn close(a: Vec2, b: Vec2) -> bool {
+    a.distance(b) < 1e-3
+}
+
+#[test]
+fn closed_paths_repeat_after_a_period() {
+    let mut rng = seeded_rng(7);
+    for kind in ["circle", "lissajous", "spline", "bezier"] {
+        let path = FocusPath::random(kind, 1000, &mut rng);
+        let period = path.period().unwrap();
+        for t in [0.0, 13.0, period / 3.0] {
+            let (a, b) = (
+                path.position(t).unwrap(),
+                path.position(t + period).unwrap(),
+            );
+            assert!(a.distance(b) < 0.1, "{} at {}: {} vs {}", kind, t, a, b);
+        }
+    }
+}
+
+#[test]
+fn spline_passes_through_waypoints() {
+    let waypoints = vec![
+        vec2(0.0, 0.0),
+        vec2(100.0, 0.0),
+        vec2(100.0, 100.0),
+        vec2(0.0, 100.0),
+    ];
+    let path = FocusPath::Spline {
+        waypoints: waypoints.clone(),
+        period: 400.0,
+    };
+    for (i, w) in waypoints.iter().enumerate() {
+        assert!(close(path.position(i as f32 * 100.0).unwrap(), *w));
+    }
+}
+
+#[test]
+fn bezier_goes_there_and_back() {
+    let control = [
+        vec2(0.0, 0.0),
+        vec2(50.0, 100.0),
+        vec2(100.0, -100.0),
+        vec2(200.0, 0.0),
+    ];
+    let path = FocusPath::Bezier {
+        control,
+        period: 100.0,
+    };
+    assert!(close(path.position(0.0).unwrap(), control[0]));
+    assert!(close(path.position(50.0).unwrap(), control[3]));
+    assert!(close(
+        path.position(20.0).unwrap(),
+        path.position(80.0).unwrap()
+    ));
+}
+
+#[test]
+fn recorded_gesture_interpolates_and_loops() {
+    let mut gesture = Gesture::default();
+    gesture.sample(vec2(5.0, 5.0)); // not recording yet
+    assert!(gesture.toggle(0.5).is_none());
+    for x in [0.0, 10.0, 20.0] {
+        gesture.sample(vec2(x, 0.0));
+    }
+    // sampled half a second apart
+    let path = gesture.toggle(0.5).unwrap();
+    assert_eq!(path.period(), Some(1.5));
+    assert!(close(path.position(0.75).unwrap(), vec2(15.0, 0.0)));
+    assert!(close(path.position(1.25).unwrap(), vec2(10.0, 0.0)));
+    assert!(close(path.position(2.0).unwrap(), vec2(10.0, 0.0)));
+}
+
+#[test]
+fn plooms_follow_paths_through_resets() {
+    let params = PloomParams::default();
+    let mut rng = seeded_rng(3);
+    let mut plooms = Ploom::spawn_random_plooms(4, &params, &mut rng);
+    let path = FocusPath::random("circle", params.window_size, &mut rng);
+    follow(&mut plooms, &path);
+    for _ in 0..500 {
+        for ploom in &mut plooms {
+            ploom.update(&params, 1.0 / 60.0, &mut rng);
+            assert!(close(ploom.focus, path.position(ploom.t).unwrap()));
+        }
+    }
+}
+
+#[test]
+fn drift_keeps_constant_velocity() {
+    let params = PloomParams::default();
+    let mut rng = seeded_rng(1);
+    let mut ploom = Ploom::new(&params, &mut rng);
+    let start = ploom.focus;
+    for _ in 0..10 {
+        ploom.update(&params, 1.0 / 60.0, &mut rng);
+    }
+    // `v` is per second
+    assert!(close(ploom.focus, start + ploom.v * (10.0 / 60.0)));
+}
@@ -0,0 +1,169 @@
+use nannou::prelude::*;
+use proto::graph::{delaunay, knn, mst, relative, EdgeFade, EdgeMode};
+use proto::ploom::rand_uniform_vec2;
+use proto::rng::seeded_rng;
+use std::collections::BTreeSet;
+
+fn random_points(n: usize, seed: u64) -> Vec<Vec2> {
+    let mut rng = seeded_rng(seed);
+    (0..n).map(|_| rand_uniform_vec2(&mut rng, 1000)).collect()
+}
+
+fn pairs(edges: &[(usize, usize, f32)]) -> BTreeSet<(usize, usize)> {
+    edges.iter().map(|&(i, j, _)| (i, j)).collect()
+}
+
+#[test]
+fn delaunay_is_planar_and_connected() {
+    let points = random_points(200, 1);
+    let edges = delaunay(&points);
+    // Euler: a planar graph on n points has at most 3n - 6 edges
+    assert!(edges.len() <= 3 * points.len() - 6);
+    assert!(edges.iter().all(|&(i, j, _)| i < j));
+    let touched: BTreeSet<usize> = edges.iter().flat_map(|&(i, j, _)| [i, j]).collect();
+    assert_eq!(touched.len(), points.len());
+
+    // a square triangulates into its 4 sides and one diagonal
+    let square = [
+        vec2(0.0, 0.0),
+        vec2(1.0, 0.0),
+        vec2(1.0, 1.1),
+        vec2(0.0, 1.0),
+    ];
+    assert_eq!(delaunay(&square).len(), 5);
+    assert!(delaunay(&[vec2(0.0, 0.0)]).is_empty());
+}
+
+#[test]
+fn mst_is_the_lightest_spanning_tree() {
+    for seed in [2, 3, 4] {
+        let points = random_points(150, seed);
+        let tree = mst(&points);
+        assert_eq!(tree.len(), points.len() - 1);
+        assert!(pairs(&tree).is_subset(&pairs(&delaunay(&points))));
+
+        // Prim over every pair gives the same total length
+        let n = points.len();
+        let mut best = vec![f32::INFINITY; n];
+        let mut done = vec![false; n];
+        best[0] = 0.0;
+        let mut total = 0.0;
+        for _ in 0..n {
+            let i = (0..n)
+                .filter(|&i| !done[i])
+                .min_by(|&a, &b| best[a].total_cmp(&best[b]))
+                .unwrap();
+            done[i] = true;
+            total += best[i];
+            for j in 0..n {
+                best[j] = best[j].min(points[i].distance(points[j]));
+            }
+        }
+        let length: f32 = tree.iter().map(|e| e.2).sum();
+        assert!(
+            (length - total).abs() < 1e-2 * total,
+            "{} {}",
+            length,
+            total
+        );
+    }
+}
+
+#[test]
+fn rng_sits_between_mst_and_delaunay() {
+    let points = random_points(150, 5);
+    let graph = pairs(&relative(&points));
+    assert!(pairs(&mst(&points)).is_subset(&graph));
+    assert!(graph.is_subset(&pairs(&delaunay(&points))));
+
+    // no third point is closer to both ends than they are to each other
+    for &(i, j) in &graph {
+        let d = points[i].distance(points[j]);
+        for (k, p) in points.iter().enumerate() {
+            if k != i && k != j {
+                assert!(p.distance(points[i]).max(p.distance(points[j])) >= d);
+            }
+        }
+    }
+}
+
+#[test]
+fn knn_connects_every_point_to_its_nearest() {
+    let points = random_points(100, 6);
+    for k in [1, 3, 6] {
+        let edges = knn(&points, k);
+        let graph = pairs(&edges);
+        assert_eq!(graph.len(), edges.len(), "an edge is listed twice");
+        for (i, p) in points.iter().enumerate() {
+            let degree = graph.iter().filter(|&&(a, b)| a == i || b == i).count();
+            assert!(degree >= k);
+            let nearest = (0..points.len())
+                .filter(|&j| j != i)
+                .min_by(|&a, &b| p.distance(points[a]).total_cmp(&p.distance(points[b])))
+                .unwrap();
+            assert!(graph.contains(&(i.min(nearest), i.max(nearest))));
+        }
+    }
+    assert!(knn(&points[..1], 3).is_empty());
+    assert_eq!(knn(&points[..3], 5).len(), 3);
+}
+
+#[test]
+fn edges_fade_in_and_out() {
+    let mut fade = EdgeFade::default();
+    let a = [(0, 1, 10.0)];
+    let b = [(1, 2, 10.0)];
+    let dt = 0.125; // a quarter of the fade
+    fade.update(&a, 0.5, dt);
+    assert!(fade.weight(0, 1) > 0.0 && fade.weight(0, 1) < 0.5);
+    for _ in 0..3 {
+        fade.update(&a, 0.5, dt);
+    }
+    assert_eq!(fade.weight(1, 0), 1.0);
+
+    // the topology changes, the old edge fades out while the new one fades in
+    let mut last = (fade.weight(0, 1), fade.weight(1, 2));
+    for _ in 0..4 {
+        fade.update(&b, 0.5, dt);
+        let now = (fade.weight(0, 1), fade.weight(1, 2));
+        assert!(now.0 < last.0 && now.1 > last.1);
+        last = now;
+    }
+    assert_eq!(fade.len(), 1);
+    assert_eq!(fade.weight(1, 2), 1.0);
+
+    // no fade switches straight over
+    fade.update(&a, 0.0, dt);
+    assert_eq!(fade.iter().collect::<Vec<_>>(), vec![(0, 1, 1.0)]);
+}
+
+#[test]
+fn modes_parse_and_cycle() {
+    for mode in [
+        EdgeMode::Radius,
+        EdgeMode::Delaunay,
+        EdgeMode::Mst,
+        EdgeMode::Knn(5),
+        EdgeMode::Relative,
+    ] {
+        assert_eq!(mode.to_string().parse::<EdgeMode>(), Ok(mode));
+    }
+    assert_eq!("knn".parse::<EdgeMode>(), Ok(EdgeMode::Knn(3)));
+    assert!("knn:x".parse::<EdgeMode>().is_err());
+    assert!("tree".parse::<EdgeMode>().is_err());
+
+    let mut mode = EdgeMode::Radius;
+    let mut seen = BTreeSet::new();
+    for _ in 0..5 {
+        seen.insert(mode.to_string());
+        mode = mode.next();
+    }
+    assert_eq!(mode, EdgeMode::Radius);
+    assert_eq!(seen.len(), 5, "every mode comes up once");
+
+    let points = random_points(50, 7);
+    assert_eq!(
+        EdgeMode::Radius.edges(&points, 120.0),
+        proto::spatial::pairs_within(&points, 120.0)
+    );
+}
@@ -0,0 +1,146 @@
+use nannou::prelude::*;
+use proto::event::{Events, Lifecycle};
+use proto::focus::FocusPath;
+use proto::graph::EdgeFade;
+use proto::mouse::{nearest, spawn_at, steer_nearest, Mouse, Pull, Stroke};
+use proto::ploom::{Ploom, PloomParams};
+use proto::plotter::Trails;
+use proto::rng::seeded_rng;
+
+const DT: f32 = 1.0 / 60.0;
+
+fn plooms(n: usize) -> (Vec<Ploom>, PloomParams) {
+    let params = PloomParams {
+        num_plooms: n,
+        ..PloomParams::default()
+    };
+    let mut rng = seeded_rng(4);
+    (Ploom::spawn_random_plooms(n, &params, &mut rng), params)
+}
+
+#[test]
+fn short_presses_click_long_ones_drag() {
+    let mut mouse = Mouse::default();
+    assert_eq!(mouse.release(Vec2::ZERO), None);
+    mouse.press(vec2(10.0, 10.0));
+    assert_eq!(
+        mouse.release(vec2(11.0, 12.0)),
+        Some(Stroke::Click(vec2(11.0, 12.0)))
+    );
+    mouse.press(vec2(10.0, 10.0));
+    assert_eq!(
+        mouse.release(vec2(60.0, 10.0)),
+        Some(Stroke::Drag(vec2(10.0, 10.0), vec2(60.0, 10.0)))
+    );
+    assert_eq!(mouse.release(vec2(60.0, 10.0)), None);
+}
+
+#[test]
+fn clicks_spawn_and_right_clicks_find_the_nearest() {
+    let (mut plooms, mut params) = plooms(5);
+    let mut rng = seeded_rng(5);
+    for ploom in plooms.iter_mut() {
+        ploom.path = FocusPath::random("walk", params.window_size, &mut rng);
+    }
+    let at = vec2(123.0, -45.0);
+    spawn_at(&mut plooms, &mut params, at, &mut rng);
+    assert_eq!((plooms.len(), params.num_plooms), (6, 6));
+    assert_eq!(plooms[5].focus, at);
+    // on the `--path` of the others
+    assert!(matches!(plooms[5].path, FocusPath::RandomWalk { .. }));
+    let spread = 10.0 * params.ploom;
+    assert!(plooms[5].circles.iter().all(|c| c.x.distance(at) < spread));
+
+    // the new ploom outlives the sketch's resize
+    Ploom::resize_plooms(&mut plooms, &params, &mut rng);
+    assert_eq!(plooms.len(), 6);
+
+    assert_eq!(nearest(&plooms, at + vec2(1.0, 1.0)), Some(5));
+    assert_eq!(nearest(&[], at), None);
+}
+
+#[test]
+fn removing_a_ploom_moves_what_follows_it_down() {
+    let foci = [vec2(-100.0, 0.0), vec2(0.0, 0.0), vec2(100.0, 0.0)];
+    let mut trails = Trails::new(1000);
+    let mut links = EdgeFade::default();
+    let mut events = Events::default();
+    for (i, &at) in foci.iter().enumerate() {
+        trails.trace(i, at, false);
+    }
+    links.update(&[(0, 1, 100.0), (1, 2, 100.0), (0, 2, 200.0)], 0.0, DT);
+    let (mut plooms, _) = plooms(3);
+    plooms[1].events.push((Lifecycle::PloomReset, foci[1]));
+    plooms[2].events.push((Lifecycle::FocusSpawn, foci[2]));
+    events.collect(&mut plooms);
+
+    // the middle one goes while all three trails are being drawn
+    trails.remove(1);
+    links.remove(1);
+    events.remove(1);
+
+    // the last ploom is now 1 and carries on its own trail
+    trails.trace(0, vec2(-100.0, 10.0), false);
+    trails.trace(1, vec2(100.0, 10.0), false);
+    assert_eq!(trails.polylines.len(), 3);
+    assert_eq!(trails.polylines[0], vec![foci[0], vec2(-100.0, 10.0)]);
+    assert_eq!(trails.polylines[1], vec![foci[1]]);
+    assert_eq!(trails.polylines[2], vec![foci[2], vec2(100.0, 10.0)]);
+    // a ploom spawned after takes a fresh one
+    trails.trace(2, vec2(0.0, 50.0), false);
+    assert_eq!(trails.polylines.len(), 4);
+
+    assert_eq!(links.iter().collect::<Vec<_>>(), vec![(0, 1, 1.0)]);
+    // every ploom spawned, the middle one's spawn and reset are gone
+    let left: Vec<_> = events.iter().map(|e| (e.ploom, e.kind)).collect();
+    let spawn = Lifecycle::FocusSpawn;
+    assert_eq!(left, vec![(0, spawn), (1, spawn), (1, spawn)]);
+}
+
+#[test]
+fn drags_set_the_nearest_velocity() {
+    let (mut plooms, params) = plooms(3);
+    let mut rng = seeded_rng(6);
+    let from = plooms[1].focus;
+    steer_nearest(&mut plooms, from, from + vec2(60.0, 0.0));
+    for _ in 0..30 {
+        plooms[1].update(&params, DT, &mut rng);
+    }
+    // half a second covers half the drag
+    assert!(plooms[1].focus.distance(from + vec2(30.0, 0.0)) < 1e-2);
+}
+
+#[test]
+fn cursor_pulls_and_pushes_nearby_circles() {
+    let (mut plooms, _) = plooms(10);
+    let at = plooms[0].circles[0].x + vec2(30.0, 0.0);
+    let distance = |plooms: &[Ploom]| plooms[0].circles[0].x.distance(at);
+    let start = distance(&plooms);
+
+    let mut mouse = Mouse::default();
+    mouse.at = Some(at);
+    mouse.pull_circles(&mut plooms, DT);
+    assert_eq!(distance(&plooms), start, "off by default");
+
+    mouse.pull = Pull::Repel;
+    for _ in 0..120 {
+        mouse.pull_circles(&mut plooms, DT);
+    }
+    // pushed out to where the pull fades away
+    assert!(distance(&plooms) > 0.9 * mouse.radius);
+
+    mouse.pull = Pull::Attract;
+    for _ in 0..600 {
+        mouse.pull_circles(&mut plooms, DT);
+    }
+    // lands on the cursor without overshooting
+    assert!(distance(&plooms) < 1e-3);
+
+    mouse.at = None;
+    plooms[0].circles[0].x += vec2(5.0, 0.0);
+    mouse.pull_circles(&mut plooms, DT);
+    assert!((distance(&plooms) - 5.0).abs() < 1e-3);
+
+    assert_eq!("repel".parse::<Pull>().unwrap(), Pull::Repel);
+    assert_eq!(Pull::Repel.next(), Pull::Off);
+}
@@ -0,0 +1,186 @@
+use nannou::prelude::*;
+use proto::clock::TICK_RATE;
+use proto::covariance::Covariance;
+use proto::noise::{
+    kick, ou_transition, Drift, Gaussian, Levy, NoiseProcess, OrnsteinUhlenbeck, Plume,
+};
+use proto::ploom::{Circle, PloomParams};
+use proto::rng::seeded_rng;
+
+const DT: f32 = 1.0 / TICK_RATE;
+
+fn plume(params: &PloomParams, sigma: f32) -> Plume<'_> {
+    Plume {
+        focus: vec2(10.0, -20.0),
+        covariance: Covariance::isotropic(sigma),
+        t: 0.0,
+        dt: DT,
+        params,
+    }
+}
+
+/// Per-axis variance around the focus after running `process` to equilibrium.
+fn stationary_variance<N: NoiseProcess>(process: &N, plume: &Plume, r: f32) -> f32 {
+    let mut rng = seeded_rng(11);
+    let mut circle = Circle {
+        x: plume.focus,
+        last: plume.focus,
+        r,
+    };
+    let (mut sum, mut n) = (0.0, 0);
+    for i in 0..400_000 {
+        circle.x = process.step(&circle, plume, &mut rng);
+        if i > 1000 {
+            sum += (circle.x - plume.focus).length_squared() / 2.0;
+            n += 1;
+        }
+    }
+    sum / n as f32
+}
+
+#[test]
+fn gaussian_matches_ar1_variance() {
+    let params = PloomParams {
+        dampening: 0.95,
+        ..PloomParams::default()
+    };
+    let plume = plume(&params, 4.0);
+    let a = 1.0 - params.pull(DT);
+    let expected = (4.0f32 / 2.0).powi(2) * DT / (1.0 - a * a);
+    let measured = stationary_variance(&Gaussian, &plume, 2.0);
+    assert!(
+        (measured / expected - 1.0).abs() < 0.05,
+        "{} vs {}",
+        measured,
+        expected
+    );
+}
+
+#[test]
+fn ornstein_uhlenbeck_matches_continuous_variance() {
+    for dampening in [0.4, 0.95, 0.998] {
+        let params = PloomParams {
+            dampening,
+            ..PloomParams::default()
+        };
+        let theta = params.pull_rate();
+        let plume = plume(&params, 4.0);
+        let expected = (4.0f32 / 2.0).powi(2) / (2.0 * theta);
+        let measured = stationary_variance(&OrnsteinUhlenbeck, &plume, 2.0);
+        assert!(
+            (measured / expected - 1.0).abs() < 0.05,
+            "theta {}: {} vs {}",
+            theta,
+            measured,
+            expected
+        );
+    }
+}
+
+#[test]
+fn levy_with_alpha_two_is_gaussian() {
+    let params = PloomParams::default();
+    let plume = plume(&params, 3.0);
+    let (mut a, mut b) = (seeded_rng(4), seeded_rng(4));
+    let mut circle = Circle {
+        x: vec2(1.0, 2.0),
+        last: vec2(1.0, 2.0),
+        r: 1.5,
+    };
+    for _ in 0..100 {
+        let x = Gaussian.step(&circle, &plume, &mut a);
+        assert_eq!(Levy { alpha: 2.0 }.step(&circle, &plume, &mut b), x);
+        circle.x = x;
+    }
+}
+
+#[test]
+fn cauchy_jumps_are_heavy_tailed() {
+    let params = PloomParams {
+        dampening: 0.0,
+        ..PloomParams::default()
+    };
+    // a one second step, where Gaussian and Cauchy jumps have the same scale
+    let plume = Plume {
+        dt: 1.0,
+        ..plume(&params, 1.0)
+    };
+    let circle = Circle {
+        x: plume.focus,
+        last: plume.focus,
+        r: 1.0,
+    };
+    let tail = |process: &dyn NoiseProcess| {
+        let mut rng = seeded_rng(2);
+        (0..100_000)
+            .filter(|_| {
+                process
+                    .step(&circle, &plume, &mut rng)
+                    .distance(plume.focus)
+                    > 10.0
+            })
+            .count()
+    };
+    assert_eq!(tail(&Gaussian), 0);
+    let cauchy = tail(&Levy { alpha: 1.0 });
+    assert!(cauchy > 1000, "only {} jumps beyond 10 sigma", cauchy);
+    assert_eq!(tail(&Levy { alpha: 1.0 }), cauchy);
+}
+
+#[test]
+fn drift_is_coherent_in_space() {
+    let params = PloomParams {
+        dampening: 0.0,
+        ..PloomParams::default()
+    };
+    let plume = plume(&params, 2.0);
+    let drift = Drift {
+        jitter: 0.0,
+        ..Drift::simplex(7)
+    };
+    let mut rng = seeded_rng(0);
+    let mut step = |x: Vec2| drift.step(&Circle { x, last: x, r: 1.0 }, &plume, &mut rng) - x;
+    let (a, b) = (step(vec2(100.0, 100.0)), step(vec2(101.0, 100.0)));
+    assert!((a.length() - 2.0 * drift.flow * DT).abs() < 1e-4);
+    assert!(a.angle_between(b).abs() < 0.2);
+}
+
+#[test]
+fn ou_transition_matches_the_continuous_process() {
+    let (decay, variance) = ou_transition(0.0, DT);
+    assert_eq!((decay, variance), (1.0, DT));
+    let theta = 0.05;
+    let (decay, variance) = ou_transition(theta, 1.0);
+    assert!((decay - (-theta).exp()).abs() < 1e-6);
+    // small θ is the Gaussian kick, stationary variance 1 / 2θ either way
+    assert!((variance - 1.0).abs() < theta);
+    assert!((variance / (1.0 - decay * decay) - 1.0 / (2.0 * theta)).abs() < 1e-3);
+    assert_eq!(
+        kick(vec2(2.0, 0.0), Vec2::ZERO, vec2(0.0, 1.0), 0.5),
+        vec2(1.0, 1.0)
+    );
+}
+
+#[test]
+fn spread_is_the_same_at_any_tick_rate() {
+    let params = PloomParams::default();
+    let at = |dt| {
+        let plume = Plume {
+            dt,
+            ..plume(&params, 4.0)
+        };
+        stationary_variance(&Gaussian, &plume, 2.0)
+    };
+    // σ² / 2θ in the limit, the tick only shows up as a small correction
+    let expected = 4.0 / (2.0 * params.pull_rate());
+    for dt in [DT, DT / 2.0, DT * 2.0] {
+        let measured = at(dt);
+        assert!(
+            (measured / expected - 1.0).abs() < 0.1,
+            "dt {}: {} vs {}",
+            dt,
+            measured,
+            expected
+        );
+    }
+}
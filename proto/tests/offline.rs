@@ -0,0 +1,127 @@
+use nannou::image;
+use nannou::prelude::*;
+use proto::accum::Trails;
+use proto::canvas::Canvas;
+use proto::clock::{Clock, TICK_RATE};
+use proto::offline::{check_fps, Offline};
+use proto::paint::Painter;
+use std::fs;
+use std::path::PathBuf;
+
+struct Model {
+    clock: Clock,
+    ticks: u32,
+}
+
+fn offline(name: &str, frames: u64, fps: f32) -> Offline {
+    let out_dir = std::env::temp_dir().join("nomos_offline_tests").join(name);
+    let _ = fs::remove_dir_all(&out_dir);
+    Offline {
+        frames,
+        fps,
+        out_dir,
+        trails: Trails::default(),
+    }
+}
+
+/// The frames `offline` wrote, in order.
+fn written(offline: &Offline) -> Vec<PathBuf> {
+    let dir = fs::read_dir(&offline.out_dir).unwrap().next().unwrap();
+    let mut frames: Vec<PathBuf> = fs::read_dir(dir.unwrap().path())
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .collect();
+    frames.sort();
+    frames
+}
+
+#[test]
+fn shapes_land_on_the_pixels_they_cover() {
+    // y points up and the origin is the center, like the window
+    let mut canvas = Canvas::new(20, 10);
+    canvas.background(BLACK);
+    canvas.rect(vec2(5.0, 2.5), vec2(10.0, 5.0), WHITE);
+    let image = canvas.to_image();
+    for (x, y, pixel) in image.enumerate_pixels() {
+        let inside = x >= 10 && y < 5;
+        assert_eq!(pixel.0, [255 * inside as u8; 3], "({}, {})", x, y);
+    }
+
+    // a disc covers pi r^2 pixels, its edge antialiased
+    let mut canvas = Canvas::new(64, 64);
+    canvas.ellipse(Vec2::ZERO, 20.0, WHITE);
+    let covered: f32 = canvas.pixels.iter().map(|p| p[0]).sum();
+    assert!((covered / (PI * 400.0) - 1.0).abs() < 0.01, "{}", covered);
+    assert_eq!(canvas.pixels[32 * 64 + 32], [1.0; 3]);
+    assert_eq!(canvas.pixels[0], [0.0; 3]);
+
+    // half transparent red over black, in linear light
+    let mut canvas = Canvas::new(4, 4);
+    canvas.rect(Vec2::ZERO, vec2(4.0, 4.0), Rgba::new(1.0, 0.0, 0.0, 0.5));
+    assert!(canvas.pixels.iter().all(|p| *p == [0.5, 0.0, 0.0]));
+}
+
+#[test]
+fn offline_frames_are_what_was_painted() {
+    let offline = offline("square", 2, 60.0);
+    let mut model = Model {
+        clock: Clock::default(),
+        ticks: 0,
+    };
+    offline.run(
+        1,
+        16,
+        &mut model,
+        |model, seconds| model.ticks += model.clock.advance(seconds),
+        |canvas, model, nth, _time| {
+            canvas.background(BLACK);
+            // one more column of white each tick
+            let width = model.ticks as f32;
+            canvas.rect(vec2(-8.0 + width / 2.0, 0.0), vec2(width, 16.0), WHITE);
+            assert_eq!(nth + 1, model.ticks as u64);
+        },
+    );
+    let frames = written(&offline);
+    assert_eq!(frames.len(), 2);
+    for (n, path) in frames.iter().enumerate() {
+        let image = image::open(path).unwrap().to_rgb8();
+        for (x, _, pixel) in image.enumerate_pixels() {
+            let white = x <= n as u32;
+            assert_eq!(pixel.0, [255 * white as u8; 3], "frame {} x {}", n, x);
+        }
+    }
+}
+
+#[test]
+fn slow_frame_rates_keep_every_tick() {
+    for fps in [1.0, 5.0, 24.0, 60.0, 144.0] {
+        let offline = offline(&format!("fps{}", fps), 6, fps);
+        let mut model = Model {
+            clock: Clock::default(),
+            ticks: 0,
+        };
+        offline.run(
+            1,
+            4,
+            &mut model,
+            |model, seconds| model.ticks += model.clock.advance(seconds),
+            |_, _, _, _| {},
+        );
+        let expected = 6.0 / fps * TICK_RATE;
+        assert!(
+            (model.ticks as f32 - expected).abs() <= 1.0,
+            "{} fps: {} ticks, not {}",
+            fps,
+            model.ticks,
+            expected
+        );
+    }
+}
+
+#[test]
+fn frame_rates_must_be_positive() {
+    assert_eq!(check_fps(24.0), Ok(24.0));
+    for fps in [0.0, -30.0, f32::NAN, f32::INFINITY] {
+        assert!(check_fps(fps).is_err(), "{}", fps);
+    }
+}
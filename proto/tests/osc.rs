@@ -0,0 +1,112 @@
+use proto::osc::{decode, set, Arg, Control, Message, OscListener, OscMap, OscSender, Target};
+use proto::ploom::PloomParams;
+use std::path::Path;
+use std::thread::sleep;
+use std::time::Duration;
+
+#[test]
+fn encodes_to_the_spec() {
+    let message = Message::new("/a", vec![Arg::Float(1.0), Arg::Int(-2)]);
+    let mut expected = b"/a\0\0,fi\0".to_vec();
+    expected.extend(1.0f32.to_be_bytes());
+    expected.extend((-2i32).to_be_bytes());
+    assert_eq!(message.encode(), expected);
+}
+
+#[test]
+fn round_trips_messages_and_bundles() {
+    let a = Message::new(
+        "/ploom/name",
+        vec![
+            Arg::Str(String::from("calm")),
+            Arg::Bool(true),
+            Arg::Float(0.25),
+        ],
+    );
+    let b = Message::new("/ploom/reset", vec![]);
+    assert_eq!(decode(&a.encode()).unwrap(), vec![a.clone()]);
+
+    let mut bundle = b"#bundle\0".to_vec();
+    bundle.extend([0, 0, 0, 0, 0, 0, 0, 1]); // immediately
+    for m in [&a, &b] {
+        let bytes = m.encode();
+        bundle.extend((bytes.len() as i32).to_be_bytes());
+        bundle.extend(bytes);
+    }
+    assert_eq!(decode(&bundle).unwrap(), vec![a, b]);
+
+    assert!(decode(b"nope\0\0\0\0").is_err());
+    assert!(decode(b"/a\0\0,f\0\0\0\0").is_err()); // float cut short
+}
+
+#[test]
+fn mapping_file_resolves_params_and_triggers() {
+    let map = OscMap::load(Path::new("osc/ploom.toml")).unwrap();
+    assert_eq!(map.port, Some(9000));
+    assert_eq!(
+        map.params["/ploom/decay"],
+        Target::Param(String::from("decay"))
+    );
+
+    let fader = Message::new("/ploom/spread", vec![Arg::Float(0.5)]);
+    assert_eq!(
+        map.control(&fader),
+        Some(Control::Set(String::from("ploom"), 250.0))
+    );
+    let reset = Message::new("/ploom/reset", vec![]);
+    assert_eq!(
+        map.control(&reset),
+        Some(Control::Trigger(String::from("reset")))
+    );
+    let decay = Message::new("/ploom/decay", vec![Arg::Int(1)]);
+    assert_eq!(
+        map.control(&decay),
+        Some(Control::Set(String::from("decay"), 1.0))
+    );
+    assert_eq!(map.control(&Message::new("/ploom/decay", vec![])), None);
+    assert_eq!(map.control(&Message::new("/elsewhere", vec![])), None);
+}
+
+#[test]
+fn sets_fields_by_name() {
+    let params = PloomParams::default();
+    let params = set(&params, "decay", 0.75).unwrap();
+    assert_eq!(params.decay, 0.75);
+    let params = set(&params, "num_plooms", 41.6).unwrap();
+    assert_eq!(params.num_plooms, 42);
+    assert!(set(&params, "nope", 1.0).is_err());
+    assert!(set(&params, "boundary", 1.0).is_err());
+}
+
+#[test]
+fn loopback_listener_applies_what_it_hears() {
+    let map = OscMap::load(Path::new("osc/ploom.toml")).unwrap();
+    let mut listener = OscListener::bind(map, 0).unwrap();
+    let sender = OscSender::new(listener.port()).unwrap();
+    sender
+        .send(&Message::new("/ploom/decay", vec![Arg::Float(0.5)]))
+        .unwrap();
+    sender
+        .send(&Message::new("/ploom/count", vec![Arg::Int(12)]))
+        .unwrap();
+    for addr in ["/ploom/unmapped", "/ploom/other", "/ploom/unmapped"] {
+        sender.send(&Message::new(addr, vec![])).unwrap();
+    }
+    sender.send(&Message::new("/ploom/reset", vec![])).unwrap();
+
+    let mut params = PloomParams::default();
+    let mut triggers = Vec::new();
+    for _ in 0..100 {
+        triggers.extend(listener.poll_params(&mut params));
+        if !triggers.is_empty() {
+            break;
+        }
+        sleep(Duration::from_millis(10));
+    }
+    assert_eq!(triggers, vec![String::from("reset")]);
+    assert_eq!(params.decay, 0.5);
+    assert_eq!(params.num_plooms, 12);
+    // each unmapped address is warned about once
+    let unmapped: Vec<&str> = listener.unmapped().iter().map(|a| a.as_str()).collect();
+    assert_eq!(unmapped, ["/ploom/other", "/ploom/unmapped"]);
+}
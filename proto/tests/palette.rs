@@ -0,0 +1,120 @@
+use proto::palette::{builtin, ColorBy, Palette, Stop};
+use proto::ploom::{Ploom, PloomParams};
+use proto::rng::seeded_rng;
+use std::fs;
+
+fn close(a: [f32; 3], b: [f32; 3]) -> bool {
+    a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-5)
+}
+
+#[test]
+fn cosine_matches_the_formula() {
+    let Palette::Cosine(cosine) = builtin("rainbow").unwrap() else {
+        panic!("rainbow is a cosine palette");
+    };
+    // a + b cos(2π d) at t = 0
+    let expected = [
+        1.0,
+        0.5 + 0.5 * (std::f32::consts::TAU * 0.33).cos(),
+        0.5 + 0.5 * (std::f32::consts::TAU * 0.67).cos(),
+    ];
+    assert!(close(cosine.at(0.0), expected));
+    // c = 1 makes it periodic over [0, 1]
+    assert!(close(cosine.at(0.0), cosine.at(1.0)));
+}
+
+#[test]
+fn gradient_interpolates_and_clamps() {
+    let palette = Palette::Gradient(vec![
+        Stop {
+            t: 0.2,
+            color: [0.0, 0.0, 0.0],
+        },
+        Stop {
+            t: 0.6,
+            color: [1.0, 0.5, 0.0],
+        },
+    ]);
+    assert!(close(palette.rgb(0.0), [0.0, 0.0, 0.0]));
+    assert!(close(palette.rgb(0.4), [0.5, 0.25, 0.0]));
+    assert!(close(palette.rgb(2.0), [1.0, 0.5, 0.0]));
+}
+
+#[test]
+fn swatch_bins_pick_each_color_once() {
+    let colors = vec![[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+    let palette = Palette::Swatch(colors.clone());
+    for (i, color) in colors.iter().enumerate() {
+        assert_eq!(palette.rgb((i as f32 + 0.5) / 3.0), *color);
+    }
+    assert_eq!(palette.rgb(1.0), colors[2]);
+}
+
+#[test]
+fn palette_files_load() {
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/palettes");
+    for name in ["nomos.toml", "ember.toml"] {
+        let palette = Palette::named(&format!("{}/{}", dir, name)).unwrap();
+        assert!(palette.rgb(0.5).iter().all(|x| (0.0..=1.0).contains(x)));
+    }
+    assert!(Palette::named("no_such_palette").is_err());
+}
+
+#[test]
+fn mappings_stay_in_range_and_track_sigma() {
+    let params = PloomParams::default();
+    let mut rng = seeded_rng(11);
+    let mut plooms = Ploom::spawn_random_plooms(5, &params, &mut rng);
+    let fresh = ColorBy::Sigma.t(&plooms, 0, 0, &params);
+    for _ in 0..120 {
+        for ploom in plooms.iter_mut() {
+            ploom.step(&params, 1.0 / 60.0, &mut rng);
+        }
+    }
+    for by in ["circle", "index", "sigma", "speed", "age"] {
+        let by: ColorBy = by.parse().unwrap();
+        for i in 0..plooms.len() {
+            for c in 0..plooms[i].circles.len() {
+                let t = by.t(&plooms, i, c, &params);
+                assert!((0.0..=1.0).contains(&t), "{:?} {}", by, t);
+            }
+        }
+    }
+    assert_eq!(fresh, 0.0);
+    assert!(ColorBy::Sigma.t(&plooms, 0, 0, &params) > fresh);
+    assert!("hue".parse::<ColorBy>().is_err());
+}
+
+#[test]
+fn bad_palettes_are_errors_not_panics() {
+    let dir = std::env::temp_dir().join("nomos_palette_tests");
+    fs::create_dir_all(&dir).unwrap();
+    let write = |name: &str, text: &str| {
+        let path = dir.join(name);
+        fs::write(&path, text).unwrap();
+        path.display().to_string()
+    };
+    assert!(Palette::named(&write("empty_swatch.toml", "swatch = []\n")).is_err());
+    assert!(Palette::named(&write("empty_gradient.toml", "gradient = []\n")).is_err());
+
+    // stops come sorted whatever order the file has them in
+    let unsorted = write(
+        "unsorted.toml",
+        "[[gradient]]\nt = 1.0\ncolor = [1.0, 1.0, 1.0]\n\n\
+         [[gradient]]\nt = 0.0\ncolor = [0.0, 0.0, 0.0]\n",
+    );
+    let palette = Palette::named(&unsorted).unwrap();
+    assert!(close(palette.rgb(0.25), [0.25, 0.25, 0.25]));
+
+    // built by hand they still don't panic
+    assert_eq!(Palette::Swatch(vec![]).rgb(0.5), [0.0; 3]);
+    assert_eq!(Palette::Gradient(vec![]).rgb(0.5), [0.0; 3]);
+
+    // nothing to decay through when the plooms start at the threshold
+    let params = PloomParams {
+        threshold: PloomParams::default().ploom,
+        ..PloomParams::default()
+    };
+    let plooms = Ploom::spawn_random_plooms(1, &params, &mut seeded_rng(12));
+    assert_eq!(ColorBy::Sigma.t(&plooms, 0, 0, &params), 0.0);
+}
@@ -0,0 +1,57 @@
+use proto::focus::FocusPath;
+use proto::panel::randomize;
+use proto::ploom::{Ploom, PloomParams};
+use proto::rng::seeded_rng;
+
+#[test]
+fn resize_grows_and_shrinks_in_place() {
+    let mut rng = seeded_rng(3);
+    let mut params = PloomParams {
+        num_plooms: 10,
+        num_circles: 2,
+        ..PloomParams::default()
+    };
+    let mut plooms = Ploom::spawn_random_plooms(params.num_plooms, &params, &mut rng);
+    for ploom in plooms.iter_mut() {
+        ploom.path = FocusPath::random("circle", params.window_size, &mut rng);
+    }
+    let first = plooms[0].focus;
+
+    params.num_plooms = 25;
+    params.num_circles = 5;
+    Ploom::resize_plooms(&mut plooms, &params, &mut rng);
+    assert_eq!(plooms.len(), 25);
+    assert!(plooms.iter().all(|p| p.circles.len() == 5));
+    assert_eq!(plooms[0].focus, first);
+    // new plooms keep to the `--path` kind instead of drifting
+    assert!(plooms[24].path.period().is_some());
+    assert!(!plooms[24].path.teleports());
+
+    params.num_plooms = 4;
+    params.num_circles = 1;
+    Ploom::resize_plooms(&mut plooms, &params, &mut rng);
+    assert_eq!(plooms.len(), 4);
+    assert!(plooms.iter().all(|p| p.circles.len() == 1));
+    assert_eq!(plooms[0].focus, first);
+}
+
+#[test]
+fn randomize_keeps_counts_and_threshold_below_scale() {
+    let mut rng = seeded_rng(5);
+    let mut params = PloomParams::default();
+    for _ in 0..1000 {
+        randomize(&mut params, &mut rng);
+        assert_eq!(params.num_plooms, PloomParams::default().num_plooms);
+        assert!(params.threshold < params.ploom);
+        assert!((0.0..1.0).contains(&params.decay));
+        // everything lands on the panel's sliders
+        assert!(
+            (0.1..=40.0).contains(&params.threshold),
+            "{}",
+            params.threshold
+        );
+        assert!((5.0..=800.0).contains(&params.ploom));
+        assert!((0.05..=1.0).contains(&params.dampening));
+        assert!((0.0..=400.0).contains(&params.max_distance));
+    }
+}
@@ -0,0 +1,180 @@
+use nannou::prelude::*;
+use proto::clock::{per_tick, TICK_RATE};
+use proto::ploom::PloomParams;
+use proto::ploom3::{Noise3, Ploom3};
+use proto::rng::seeded_rng;
+use rand::rngs::StdRng;
+
+const DT: f32 = 1.0 / TICK_RATE;
+
+fn spawn(params: &PloomParams, seed: u64) -> (Vec<Ploom3>, StdRng) {
+    let mut rng = seeded_rng(seed);
+    let plooms = Ploom3::spawn_random_plooms(params.num_plooms, params, &mut rng);
+    (plooms, rng)
+}
+
+#[test]
+fn plooms_reset_like_the_flat_ones() {
+    let params = PloomParams {
+        num_plooms: 20,
+        ..PloomParams::default()
+    };
+    let decays = (params.threshold / params.ploom).ln() / per_tick(params.decay, DT).ln();
+    let period = decays.ceil() as usize + 1;
+    let (mut plooms, mut rng) = spawn(&params, 1);
+    let bound = params.window_size as f32 / 2.0;
+    for tick in 1..=period * 3 {
+        for ploom in plooms.iter_mut() {
+            let drifted = ploom.focus + ploom.v * DT;
+            let reset = ploom.step(&params, DT, &mut rng);
+            assert_eq!(reset, tick % period == 0, "tick {}", tick);
+            if reset {
+                // teleported into the cube, nothing to interpolate from
+                assert_eq!(ploom.sigma, params.ploom);
+                assert_eq!(ploom.last_focus, ploom.focus);
+                assert!(ploom.focus.abs().max_element() <= bound);
+            } else {
+                assert_eq!(ploom.focus, drifted);
+            }
+        }
+    }
+}
+
+#[test]
+fn circles_disperse_in_all_three_axes() {
+    // y' = (1 - d)(y - v dt) + e with e ~ N(0, (sigma / r)^2 dt I) per axis,
+    // as in 2D
+    let dampening = 0.9;
+    let params = PloomParams {
+        num_plooms: 40,
+        num_circles: 4,
+        decay: 1.0, // sigma stays at `ploom`
+        dampening,
+        ..PloomParams::default()
+    };
+    let (mut plooms, mut rng) = spawn(&params, 3);
+    let keep = 1.0 - params.pull(DT);
+    let variance = params.ploom.powi(2) * DT / (1.0 - keep * keep);
+    let (mut scaled, mut n) = (Vec3::ZERO, 0);
+    for tick in 0..3000 {
+        for ploom in plooms.iter_mut() {
+            ploom.step(&params, DT, &mut rng);
+        }
+        if tick < 500 {
+            continue; // burn in
+        }
+        for ploom in &plooms {
+            let expected = -ploom.v * DT * keep / (1.0 - keep);
+            for circle in &ploom.circles {
+                let y = (circle.x - ploom.focus - expected) * circle.r;
+                scaled += y * y;
+                n += 1;
+            }
+        }
+    }
+    let measured = scaled / n as f32;
+    for axis in 0..3 {
+        let ratio = measured[axis] / variance;
+        assert!((ratio - 1.0).abs() < 0.05, "axis {}: {}", axis, ratio);
+    }
+}
+
+#[test]
+fn every_noise_stays_bounded() {
+    let params = PloomParams {
+        num_plooms: 20,
+        decay: 1.0,
+        ..PloomParams::default()
+    };
+    let keep = 1.0 - params.pull(DT);
+    let spread = params.ploom * (DT / (1.0 - keep * keep)).sqrt();
+    for noise in [
+        Noise3::Gaussian,
+        Noise3::OrnsteinUhlenbeck,
+        Noise3::Levy(1.5),
+    ] {
+        let (mut plooms, mut rng) = spawn(&params, 4);
+        for ploom in plooms.iter_mut() {
+            ploom.noise = noise;
+        }
+        for _ in 0..2000 {
+            for ploom in plooms.iter_mut() {
+                ploom.step(&params, DT, &mut rng);
+                let lag = ploom.v.length() * DT * keep / (1.0 - keep);
+                for circle in &ploom.circles {
+                    assert!(circle.x.is_finite(), "{}", noise);
+                    // jumps are capped at the window size
+                    let distance = circle.x.distance(ploom.focus) - lag;
+                    assert!(
+                        distance < 50.0 * spread + params.window_size as f32,
+                        "{}",
+                        noise
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn fixed_seeds_give_identical_runs() {
+    let params = PloomParams::default();
+    let run = |seed| {
+        let (mut plooms, mut rng) = spawn(&params, seed);
+        for _ in 0..300 {
+            for ploom in plooms.iter_mut() {
+                ploom.step(&params, DT, &mut rng);
+            }
+        }
+        plooms
+            .iter()
+            .flat_map(|p| p.circles.iter().map(|c| c.x))
+            .collect::<Vec<Vec3>>()
+    };
+    assert_eq!(run(5), run(5));
+    assert_ne!(run(5), run(6));
+}
+
+#[test]
+fn resizing_keeps_the_running_plooms() {
+    let params = PloomParams {
+        num_plooms: 10,
+        num_circles: 3,
+        ..PloomParams::default()
+    };
+    let (mut plooms, mut rng) = spawn(&params, 7);
+    plooms[0].noise = Noise3::Levy(1.5);
+    let first = plooms[0].focus;
+
+    let more = PloomParams {
+        num_plooms: 12,
+        num_circles: 5,
+        ..params
+    };
+    Ploom3::resize_plooms(&mut plooms, &more, &mut rng);
+    assert_eq!(plooms.len(), 12);
+    assert_eq!(plooms[0].focus, first);
+    // new plooms take the noise of the existing ones round robin
+    assert_eq!(plooms[10].noise, Noise3::Levy(1.5));
+    for ploom in &plooms {
+        assert_eq!(ploom.circles.len(), 5);
+    }
+
+    Ploom3::resize_plooms(&mut plooms, &params, &mut rng);
+    assert_eq!(plooms.len(), 10);
+    assert!(plooms.iter().all(|p| p.circles.len() == 3));
+}
+
+#[test]
+fn noise_names_round_trip() {
+    for noise in [
+        Noise3::Gaussian,
+        Noise3::OrnsteinUhlenbeck,
+        Noise3::Levy(1.5),
+    ] {
+        assert_eq!(noise.to_string().parse(), Ok(noise));
+    }
+    assert_eq!("cauchy".parse(), Ok(Noise3::Levy(1.0)));
+    assert!("perlin".parse::<Noise3>().is_err());
+    assert!("levy:x".parse::<Noise3>().is_err());
+}
@@ -0,0 +1,148 @@
+use nannou::prelude::*;
+use proto::ploom::rand_uniform_vec2;
+use proto::plotter::{order_paths, simplify, to_gcode, to_hpgl, Paper, Pen, Trails};
+use proto::rng::seeded_rng;
+
+/// Pen-up distance from `start` through `paths` in order.
+fn travel(paths: &[Vec<Vec2>], start: Vec2) -> f32 {
+    let mut pen = start;
+    let mut total = 0.0;
+    for path in paths {
+        total += pen.distance(path[0]);
+        pen = path[path.len() - 1];
+    }
+    total
+}
+
+fn segment_distance(p: Vec2, a: Vec2, b: Vec2) -> f32 {
+    let t = ((p - a).dot(b - a) / (b - a).length_squared()).clamp(0.0, 1.0);
+    p.distance(a + (b - a) * t)
+}
+
+#[test]
+fn simplify_keeps_corners_and_drops_wobble() {
+    // a wobbly straight line collapses to its ends
+    let wobbly: Vec<Vec2> = (0..=100)
+        .map(|i| vec2(i as f32, if i % 2 == 0 { 0.2 } else { -0.2 }))
+        .collect();
+    assert_eq!(
+        simplify(&wobbly, 0.5),
+        vec![vec2(0.0, 0.2), vec2(100.0, 0.2)]
+    );
+    // but not below the wobble
+    assert_eq!(simplify(&wobbly, 0.1), wobbly);
+
+    // an L keeps its corner, the points along the legs go
+    let mut l: Vec<Vec2> = (0..=10).map(|i| vec2(i as f32 * 10.0, 0.0)).collect();
+    l.extend((1..=10).map(|i| vec2(100.0, i as f32 * 10.0)));
+    assert_eq!(
+        simplify(&l, 1.0),
+        vec![vec2(0.0, 0.0), vec2(100.0, 0.0), vec2(100.0, 100.0)]
+    );
+
+    // every dropped point is within epsilon of the result
+    let mut rng = seeded_rng(1);
+    let walk: Vec<Vec2> = (0..500).map(|_| rand_uniform_vec2(&mut rng, 100)).collect();
+    let simple = simplify(&walk, 5.0);
+    assert!(simple.len() < walk.len());
+    for p in &walk {
+        let d = simple
+            .windows(2)
+            .map(|s| segment_distance(*p, s[0], s[1]))
+            .fold(f32::MAX, f32::min);
+        assert!(d <= 5.0, "{} off by {}", p, d);
+    }
+    assert_eq!((simple[0], simple[simple.len() - 1]), (walk[0], walk[499]));
+
+    let two = [vec2(0.0, 0.0), vec2(1.0, 1.0)];
+    assert_eq!(simplify(&two, 10.0), two.to_vec());
+}
+
+#[test]
+fn ordering_cuts_pen_up_travel() {
+    let mut rng = seeded_rng(2);
+    let paths: Vec<Vec<Vec2>> = (0..200)
+        .map(|_| {
+            let a = rand_uniform_vec2(&mut rng, 1000);
+            vec![a, a + rand_uniform_vec2(&mut rng, 40)]
+        })
+        .collect();
+    let start = vec2(-500.0, -500.0);
+    let ordered = order_paths(paths.clone(), start);
+    assert_eq!(ordered.len(), paths.len());
+    let (before, after) = (travel(&paths, start), travel(&ordered, start));
+    assert!(after < before / 4.0, "{} -> {}", before, after);
+
+    // a path whose far end is nearer is drawn backwards
+    let ordered = order_paths(vec![vec![vec2(10.0, 0.0), vec2(1.0, 0.0)]], Vec2::ZERO);
+    assert_eq!(ordered, vec![vec![vec2(1.0, 0.0), vec2(10.0, 0.0)]]);
+}
+
+#[test]
+fn hpgl_and_gcode_draw_the_paths_on_paper() {
+    // 100mm square paper, 10mm margins: the 100px window maps 0.8mm a px
+    let paper = Paper {
+        width: 100.0,
+        height: 100.0,
+        margin: 10.0,
+    };
+    let paths = vec![vec![
+        vec2(-50.0, -50.0),
+        vec2(50.0, -50.0),
+        vec2(50.0, 50.0),
+    ]];
+    assert_eq!(
+        to_hpgl(&paths, &paper, 100),
+        "IN;SP1;\nPU400,400;PD3600,400,3600,3600;\nPU0,0;SP0;\n"
+    );
+    let pen = Pen {
+        up: String::from("M5"),
+        down: String::from("M3"),
+        feed_rate: 1200.0,
+    };
+    assert_eq!(
+        to_gcode(&paths, &paper, 100, &pen),
+        "G21 ; millimetres\nG90 ; absolute\nM5\n\
+         G0 X10.000 Y10.000\nM3\n\
+         G1 X90.000 Y10.000 F1200\n\
+         G1 X90.000 Y90.000 F1200\n\
+         M5\nG0 X0 Y0\n"
+    );
+}
+
+#[test]
+fn papers_parse_or_explain() {
+    let paper: Paper = "300x200".parse().unwrap();
+    assert_eq!((paper.width, paper.height), (300.0, 200.0));
+    assert_eq!("a3".parse::<Paper>().unwrap().width, Paper::a3().width);
+    for bad in ["letter", "300x", "x200", "300xq", "-1x200"] {
+        let e = bad.parse::<Paper>().unwrap_err();
+        assert!(e.contains("<w>x<h>"), "{}", e);
+    }
+    // margins have to leave some paper to draw on
+    assert_eq!(Paper::a4().with_margin(0.0).unwrap().margin, 0.0);
+    assert_eq!(paper.clone().with_margin(99.0).unwrap().margin, 99.0);
+    for margin in [100.0, 150.0, -1.0, f32::NAN] {
+        assert!(paper.clone().with_margin(margin).is_err(), "{}", margin);
+    }
+    assert!("20x20".parse::<Paper>().is_err());
+}
+
+#[test]
+fn trails_split_on_jumps_and_at_the_window() {
+    let mut trails = Trails::new(100);
+    trails.trace(0, vec2(0.0, 0.0), false);
+    trails.trace(0, vec2(10.0, 0.0), false);
+    // off the window ends the trail, coming back starts another
+    trails.trace(0, vec2(80.0, 0.0), false);
+    trails.trace(0, vec2(40.0, 0.0), false);
+    trails.trace(0, vec2(30.0, 0.0), true);
+    assert_eq!(
+        trails.polylines,
+        vec![
+            vec![vec2(0.0, 0.0), vec2(10.0, 0.0)],
+            vec![vec2(40.0, 0.0)],
+            vec![vec2(30.0, 0.0)],
+        ]
+    );
+}
@@ -0,0 +1,94 @@
+use proto::covariance::Align;
+use proto::ploom::PloomParams;
+use proto::preset::{load, resolve, save, Preset};
+use std::fs;
+use std::path::PathBuf;
+use std::thread::sleep;
+use std::time::Duration;
+
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join("nomos_preset_tests");
+    fs::create_dir_all(&dir).unwrap();
+    dir.join(name)
+}
+
+fn tweaked() -> PloomParams {
+    PloomParams {
+        num_plooms: 42,
+        dampening: 0.005,
+        align: Align::Spin(0.25),
+        ..PloomParams::default()
+    }
+}
+
+#[test]
+fn toml_and_ron_round_trip() {
+    for name in ["round_trip.toml", "round_trip.ron"] {
+        let path = scratch(name);
+        save(&path, &tweaked()).unwrap();
+        let params: PloomParams = load(&path).unwrap();
+        assert_eq!(params.num_plooms, 42);
+        assert_eq!(params.dampening, 0.005);
+        assert_eq!(params.align, Align::Spin(0.25));
+    }
+}
+
+#[test]
+fn missing_fields_keep_defaults() {
+    let path = scratch("partial.toml");
+    fs::write(&path, "decay = 0.5\nalign = \"fixed\"\n").unwrap();
+    let params: PloomParams = load(&path).unwrap();
+    assert_eq!(params.decay, 0.5);
+    assert_eq!(params.align, Align::Fixed);
+    assert_eq!(params.num_plooms, PloomParams::default().num_plooms);
+}
+
+#[test]
+fn bad_align_is_an_error() {
+    let path = scratch("bad.toml");
+    fs::write(&path, "align = \"sideways\"\n").unwrap();
+    assert!(load::<PloomParams>(&path).is_err());
+}
+
+#[test]
+fn poll_picks_up_edits_but_not_own_saves() {
+    let path = scratch("watched.toml");
+    let _ = fs::remove_file(&path);
+    let mut preset = Preset::new(&path);
+    let params = preset.load_or(tweaked());
+    assert_eq!(params.num_plooms, 42);
+
+    preset.save(&params);
+    sleep(Duration::from_millis(300));
+    assert!(preset.poll::<PloomParams>().is_none());
+
+    // some filesystems only keep whole seconds
+    sleep(Duration::from_millis(1100));
+    fs::write(&path, "num_plooms = 7\n").unwrap();
+    let reloaded: PloomParams = preset.poll().unwrap();
+    assert_eq!(reloaded.num_plooms, 7);
+    assert!(preset.poll::<PloomParams>().is_none());
+}
+
+#[test]
+fn reload_keeps_running_values_on_a_bad_file() {
+    let path = scratch("reset.toml");
+    let _ = fs::remove_file(&path);
+    let mut preset = Preset::new(&path);
+    assert_eq!(preset.reload_or(tweaked()).unwrap().num_plooms, 42);
+
+    fs::write(&path, "num_plooms = 7\n").unwrap();
+    let params: PloomParams = preset.reload_or(tweaked()).unwrap();
+    assert_eq!(params.num_plooms, 7);
+
+    fs::write(&path, "align = \"sideways\"\n").unwrap();
+    assert!(preset.reload_or(tweaked()).is_none());
+}
+
+#[test]
+fn bare_names_live_under_presets() {
+    let path = resolve("calm");
+    assert!(path.starts_with("presets"));
+    assert_eq!(path.extension().unwrap(), "toml");
+    assert_eq!(resolve("my/calm.ron"), PathBuf::from("my/calm.ron"));
+}
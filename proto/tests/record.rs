@@ -0,0 +1,225 @@
+use nannou::prelude::*;
+use proto::clock::TICK_RATE;
+use proto::ploom::{Ploom, PloomParams};
+use proto::record::{
+    hold_plooms, load, restore_plooms, BallState, PloomState, Recorder, Replay, Snapshot,
+};
+use proto::rng::seeded_rng;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join("nomos_record_tests");
+    fs::create_dir_all(&dir).unwrap();
+    dir.join(name)
+}
+
+/// Plooms stepped `ticks` times, recorded into `path` every `every` ticks.
+fn record_plooms(path: &Path, ticks: usize, every: u64) -> Vec<Snapshot> {
+    let params = PloomParams {
+        num_plooms: 5,
+        ..PloomParams::default()
+    };
+    let mut rng = seeded_rng(3);
+    let mut plooms = Ploom::spawn_random_plooms(params.num_plooms, &params, &mut rng);
+    let mut recorder = Recorder::create(path, every).unwrap();
+    let mut expected = Vec::new();
+    let dt = 1.0 / TICK_RATE;
+    for tick in 0..ticks {
+        for ploom in plooms.iter_mut() {
+            ploom.step(&params, dt, &mut rng);
+        }
+        let snapshot = Snapshot {
+            plooms: plooms.iter().map(PloomState::of).collect(),
+            ..Snapshot::default()
+        };
+        if (tick as u64).is_multiple_of(every) {
+            expected.push(Snapshot {
+                frame: tick as u64,
+                time: (tick as f64 * dt as f64) as f32,
+                ..snapshot.clone()
+            });
+        }
+        recorder.record(dt, || snapshot).unwrap();
+    }
+    expected
+}
+
+#[test]
+fn csv_and_json_lines_round_trip() {
+    for name in ["plooms.csv", "plooms.jsonl"] {
+        let path = scratch(name);
+        let expected = record_plooms(&path, 20, 1);
+        assert_eq!(load(&path).unwrap(), expected, "{}", name);
+    }
+
+    let balls = Snapshot {
+        frame: 7,
+        time: 0.5,
+        balls: vec![
+            BallState {
+                cell: 0,
+                index: 3,
+                loc: [1.5, -2.25],
+                v: [0.1, 30.0],
+            },
+            BallState {
+                cell: 2,
+                index: 0,
+                loc: [-400.0, 12.0],
+                v: [-1e-3, 0.0],
+            },
+        ],
+        ..Snapshot::default()
+    };
+    for name in ["balls.csv", "balls.jsonl"] {
+        let path = scratch(name);
+        let mut recorder = Recorder::create(&path, 1).unwrap();
+        for _ in 0..8 {
+            recorder.record(1.0 / 14.0, || balls.clone()).unwrap();
+        }
+        let loaded = load(&path).unwrap();
+        assert_eq!(loaded.len(), 8);
+        assert_eq!(loaded[7].balls, balls.balls, "{}", name);
+        assert_eq!(loaded[7].frame, 7);
+    }
+}
+
+#[test]
+fn decimation_keeps_every_nth_tick() {
+    let path = scratch("decimated.jsonl");
+    let expected = record_plooms(&path, 10, 3);
+    let loaded = load(&path).unwrap();
+    assert_eq!(loaded, expected);
+    let frames: Vec<u64> = loaded.iter().map(|s| s.frame).collect();
+    assert_eq!(frames, vec![0, 3, 6, 9]);
+    assert!((loaded[2].time - 6.0 / TICK_RATE).abs() < 1e-6);
+
+    // the snapshot isn't even taken for the ticks that are skipped
+    let mut recorder = Recorder::create(&scratch("lazy.jsonl"), 4).unwrap();
+    let mut taken = 0;
+    for _ in 0..8 {
+        recorder
+            .record(0.1, || {
+                taken += 1;
+                Snapshot::default()
+            })
+            .unwrap();
+    }
+    assert_eq!(taken, 2);
+}
+
+#[test]
+fn replay_holds_snapshots_until_the_next_is_due() {
+    let snapshots: Vec<Snapshot> = [0, 3, 6]
+        .iter()
+        .map(|&frame| Snapshot {
+            frame,
+            time: frame as f32 * 0.1,
+            ..Snapshot::default()
+        })
+        .collect();
+    let mut replay = Replay::new(snapshots);
+    let shown: Vec<Option<u64>> = (0..9)
+        .map(|_| replay.advance(0.1).map(|s| s.frame))
+        .collect();
+    assert_eq!(
+        shown,
+        vec![
+            Some(0),
+            None,
+            None,
+            Some(3),
+            None,
+            None,
+            Some(6),
+            None,
+            None
+        ]
+    );
+    assert!(replay.is_done());
+
+    // a faster replay skips to the latest snapshot due
+    let mut replay = Replay::new(replay.snapshots.clone());
+    replay.advance(0.4);
+    assert_eq!(replay.advance(0.4).map(|s| s.frame), Some(3));
+    assert_eq!(Replay::new(Vec::new()).advance(0.1), None);
+}
+
+#[test]
+fn decimated_replays_never_jump_back() {
+    let path = scratch("decimated.jsonl");
+    record_plooms(&path, 20, 2);
+    let mut replay = Replay::load(&path).unwrap();
+    let mut params = PloomParams::default();
+    let mut rng = seeded_rng(4);
+    let mut plooms = Vec::new();
+    let mut shown: Option<Vec<Vec2>> = None;
+    for _ in 0..30 {
+        match replay.advance(1.0 / TICK_RATE) {
+            Some(snapshot) => restore_plooms(&mut plooms, &snapshot.plooms, &mut params, &mut rng),
+            None => hold_plooms(&mut plooms),
+        }
+        // each tick interpolates from where the last one ended
+        let from: Vec<Vec2> = plooms
+            .iter()
+            .flat_map(|p| p.circles.iter().map(|c| c.last))
+            .collect();
+        if let Some(shown) = &shown {
+            assert_eq!(&from, shown);
+        }
+        shown = Some(
+            plooms
+                .iter()
+                .flat_map(|p| p.circles.iter().map(|c| c.x))
+                .collect(),
+        );
+    }
+}
+
+#[test]
+fn restoring_plooms_matches_the_recording() {
+    let path = scratch("restore.csv");
+    record_plooms(&path, 2, 1);
+    let recording = load(&path).unwrap();
+
+    let mut params = PloomParams {
+        num_plooms: 12,
+        num_circles: 1,
+        ..PloomParams::default()
+    };
+    let mut rng = seeded_rng(99);
+    let mut plooms = Ploom::spawn_random_plooms(params.num_plooms, &params, &mut rng);
+    for snapshot in &recording {
+        restore_plooms(&mut plooms, &snapshot.plooms, &mut params, &mut rng);
+    }
+    let last = recording.last().unwrap();
+    let restored: Vec<PloomState> = plooms.iter().map(PloomState::of).collect();
+    assert_eq!(restored, last.plooms);
+    assert_eq!(params.num_plooms, 5);
+    assert_eq!(params.num_circles, last.plooms[0].circles.len());
+    // the snapshot before is where drawing interpolates from
+    let before = &recording[recording.len() - 2].plooms[0];
+    assert_eq!(plooms[0].last_focus, Vec2::from(before.focus));
+    assert_eq!(plooms[0].circles[1].last, Vec2::from(before.circles[1].x));
+}
+
+#[test]
+fn bad_recordings_are_errors() {
+    let path = scratch("bad.csv");
+    fs::write(
+        &path,
+        "frame,time,kind,id,index,x,y,vx,vy,r,sigma\n0,0,circle,0,0,1,2,,,3,\n",
+    )
+    .unwrap();
+    let e = load(&path).unwrap_err();
+    assert!(e.contains(":2:") && e.contains("before its focus"), "{}", e);
+
+    fs::write(&path, "frame,time,kind\n0,0,ball\n").unwrap();
+    assert!(load(&path).is_err());
+
+    let path = scratch("bad.jsonl");
+    fs::write(&path, "{\"frame\":0,\"time\":0.0}\nnot json\n").unwrap();
+    assert!(load(&path).unwrap_err().contains(":2:"));
+    assert!(load(&scratch("missing.jsonl")).is_err());
+}
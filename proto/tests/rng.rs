@@ -0,0 +1,57 @@
+use nannou::prelude::*;
+use proto::clock::TICK_RATE;
+use proto::focus::FocusPath;
+use proto::noise::from_name;
+use proto::ploom::{Ploom, PloomParams};
+use proto::rng::{capture_name, seeded_rng};
+use rand::Rng;
+
+/// Every circle after `ticks` ticks of a run drawing from `seed` alone:
+/// spawning, focus paths, noise and the steps themselves.
+fn run(seed: u64, ticks: usize) -> Vec<Vec2> {
+    let params = PloomParams {
+        num_plooms: 12,
+        ..PloomParams::default()
+    };
+    let mut rng = seeded_rng(seed);
+    let mut plooms = Ploom::spawn_random_plooms(params.num_plooms, &params, &mut rng);
+    let kinds = ["drift", "walk", "circle", "lissajous", "spline", "bezier"];
+    let noises = ["gaussian", "ou", "levy:1.5"];
+    for (i, ploom) in plooms.iter_mut().enumerate() {
+        ploom.path = FocusPath::random(kinds[i % kinds.len()], params.window_size, &mut rng);
+        ploom.noise = from_name(noises[i % noises.len()], seed as u32);
+    }
+    for _ in 0..ticks {
+        for ploom in plooms.iter_mut() {
+            ploom.step(&params, 1.0 / TICK_RATE, &mut rng);
+        }
+    }
+    plooms
+        .iter()
+        .flat_map(|p| p.circles.iter().map(|c| c.x))
+        .collect()
+}
+
+#[test]
+fn seeded_streams_repeat() {
+    let draw = |seed| {
+        let mut rng = seeded_rng(seed);
+        (0..100).map(|_| rng.gen::<u64>()).collect::<Vec<_>>()
+    };
+    assert_eq!(draw(42), draw(42));
+    assert_ne!(draw(42), draw(43));
+}
+
+#[test]
+fn a_seed_reproduces_the_run() {
+    assert_eq!(run(42, 600), run(42, 600));
+    assert_ne!(run(42, 600), run(43, 600));
+}
+
+#[test]
+fn captures_are_named_after_the_seed() {
+    assert_eq!(
+        capture_name("ploom_d", 42, 1200),
+        "ploom_d_seed42_frame1200.png"
+    );
+}
@@ -0,0 +1,67 @@
+use nannou::prelude::*;
+use proto::ploom::rand_uniform_vec2;
+use proto::rng::seeded_rng;
+use proto::spatial::{pairs_within, Grid};
+
+fn random_points(n: usize, seed: u64) -> Vec<Vec2> {
+    let mut rng = seeded_rng(seed);
+    let mut points: Vec<Vec2> = (0..n).map(|_| rand_uniform_vec2(&mut rng, 1000)).collect();
+    // duplicates and points sitting exactly on cell borders
+    points.push(points[0]);
+    points.push(vec2(0.0, 0.0));
+    points.push(vec2(100.0, -100.0));
+    points
+}
+
+fn brute_force_pairs(points: &[Vec2], radius: f32) -> Vec<(usize, usize)> {
+    let mut pairs = Vec::new();
+    for i in 0..points.len() {
+        for j in i + 1..points.len() {
+            if points[i].distance(points[j]) < radius {
+                pairs.push((i, j));
+            }
+        }
+    }
+    pairs
+}
+
+#[test]
+fn pairs_match_brute_force() {
+    for (seed, radius) in [(1, 100.0), (2, 37.5), (3, 250.0), (4, 1.0)] {
+        let points = random_points(400, seed);
+        let pairs: Vec<(usize, usize)> = pairs_within(&points, radius)
+            .iter()
+            .map(|&(i, j, _)| (i, j))
+            .collect();
+        assert_eq!(pairs, brute_force_pairs(&points, radius));
+    }
+}
+
+#[test]
+fn pairs_report_distance() {
+    let points = random_points(200, 5);
+    for (i, j, d) in pairs_within(&points, 150.0) {
+        assert_eq!(d, points[i].distance(points[j]));
+    }
+}
+
+#[test]
+fn within_matches_brute_force_for_any_cell_size() {
+    let points = random_points(300, 6);
+    let queries = random_points(20, 7);
+    for cell_size in [10.0, 80.0, 400.0] {
+        let grid = Grid::new(&points, cell_size);
+        for q in &queries {
+            let expected: Vec<usize> = (0..points.len())
+                .filter(|&i| points[i].distance(*q) < 120.0)
+                .collect();
+            assert_eq!(grid.within(*q, 120.0), expected);
+        }
+    }
+}
+
+#[test]
+fn empty_and_degenerate_inputs() {
+    assert!(pairs_within(&[], 100.0).is_empty());
+    assert!(pairs_within(&[vec2(0.0, 0.0), vec2(0.0, 0.0)], 0.0).is_empty());
+}
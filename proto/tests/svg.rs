@@ -0,0 +1,111 @@
+use nannou::prelude::*;
+use proto::paint::Painter;
+use proto::svg::{SvgExport, SvgRecorder};
+use std::fs;
+
+/// The lines of `svg` inside group `id`, without the group tags.
+fn layer<'a>(svg: &'a str, id: &str) -> Vec<&'a str> {
+    let open = format!(r#"<g id="{}""#, id);
+    svg.lines()
+        .skip_while(|l| !l.starts_with(&open))
+        .skip(1)
+        .take_while(|l| *l != "</g>")
+        .collect()
+}
+
+#[test]
+fn background_comes_from_the_first_frame_and_fades_are_dropped() {
+    let mut svg = SvgRecorder::new(200, 100);
+    svg.background(Rgba::new(1.0, 0.0, 0.0, 1.0));
+    // the next frame's fade, translucent and another color
+    svg.fade(vec2(200.0, 100.0), Rgba::new(0.0, 0.0, 1.0, 0.1));
+    // a rect smaller than the window isn't a fade
+    svg.rect(Vec2::ZERO, vec2(10.0, 10.0), WHITE);
+    let text = svg.to_svg();
+    let lines: Vec<&str> = text.lines().collect();
+    assert!(lines[0].contains(r#"viewBox="-100 -50 200 100""#));
+    assert_eq!(
+        lines[1],
+        r##"<rect id="background" x="-100" y="-50" width="200" height="100" fill="#ff0000" fill-opacity="1.000"/>"##
+    );
+    assert_eq!(lines[2], "</svg>");
+
+    // a fade first sets an opaque background
+    let mut svg = SvgRecorder::new(200, 100);
+    svg.fade(vec2(200.0, 100.0), Rgba::new(0.0, 0.0, 0.0, 0.02));
+    assert!(svg
+        .to_svg()
+        .contains(r##"fill="#000000" fill-opacity="1.000"/>"##));
+}
+
+#[test]
+fn circles_and_edges_go_into_their_own_layers() {
+    let mut svg = SvgRecorder::new(100, 100);
+    svg.background(BLACK);
+    svg.ellipse(vec2(10.0, 20.0), 3.0, Rgba::new(1.0, 1.0, 1.0, 0.4));
+    svg.line(
+        vec2(-5.0, 5.0),
+        vec2(5.0, -5.0),
+        2.0,
+        Rgba::new(0.0, 1.0, 0.0, 0.25),
+    );
+    svg.ellipse(vec2(-1.0, -2.0), 1.5, Rgba::new(0.0, 0.0, 1.0, 1.0));
+    let text = svg.to_svg();
+
+    // y flips, window y points up and SVG y down
+    assert_eq!(
+        layer(&text, "circles"),
+        vec![
+            r##"<circle cx="10.00" cy="-20.00" r="3.00" fill="#ffffff" fill-opacity="0.400"/>"##,
+            r##"<circle cx="-1.00" cy="2.00" r="1.50" fill="#0000ff" fill-opacity="1.000"/>"##,
+        ]
+    );
+    assert_eq!(
+        layer(&text, "edges"),
+        vec![
+            r##"<line x1="-5.00" y1="-5.00" x2="5.00" y2="5.00" stroke-width="2.00" stroke="#00ff00" stroke-opacity="0.250"/>"##
+        ]
+    );
+    // edges are drawn over the circles, and both are Inkscape layers
+    assert!(text.find(r#"<g id="circles""#) < text.find(r#"<g id="edges""#));
+    assert!(text.contains(r#"inkscape:groupmode="layer" inkscape:label="edges""#));
+    assert!(!text.contains(r#"<g id="quads""#));
+}
+
+#[test]
+fn colors_are_written_in_srgb() {
+    let mut svg = SvgRecorder::new(10, 10);
+    // shapes come in through nannou's sRGB colors and go out the same
+    svg.ellipse(Vec2::ZERO, 1.0, Rgba::new(0.5, 0.25, 0.75, 1.0));
+    // invisible shapes aren't written at all
+    svg.ellipse(Vec2::ZERO, 1.0, Rgba::new(1.0, 1.0, 1.0, 0.0));
+    svg.ellipse(Vec2::ZERO, 0.0, WHITE);
+    svg.line(Vec2::ZERO, Vec2::ONE, 0.0, WHITE);
+    let circles = layer(&svg.to_svg(), "circles").join("\n");
+    assert!(circles.contains(r##"fill="#8040bf""##), "{}", circles);
+    assert_eq!(circles.lines().count(), 1);
+    assert!(layer(&svg.to_svg(), "edges").is_empty());
+}
+
+#[test]
+fn export_records_only_its_frames() {
+    let dir = std::env::temp_dir().join("nomos_svg_tests");
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("window.svg");
+    let _ = fs::remove_file(&path);
+    let export = SvgExport::new(3, 2, path.clone(), 100);
+    for nth in 0..8 {
+        export.record(nth, |svg| {
+            svg.background(BLACK);
+            svg.ellipse(vec2(nth as f32, 0.0), 1.0, WHITE);
+        });
+        // written once the last frame of the span is in
+        assert_eq!(path.exists(), nth >= 4, "frame {}", nth);
+    }
+    let text = fs::read_to_string(&path).unwrap();
+    let xs: Vec<&str> = layer(&text, "circles")
+        .iter()
+        .map(|l| l.split('"').nth(1).unwrap())
+        .collect();
+    assert_eq!(xs, vec!["3.00", "4.00"]);
+}
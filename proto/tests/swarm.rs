@@ -0,0 +1,174 @@
+use nannou::prelude::*;
+use proto::boundary::Boundary;
+use proto::clock::{per_tick, TICK_RATE};
+use proto::event::{Events, Lifecycle};
+use proto::ploom::{Ploom, PloomParams};
+use proto::rng::seeded_rng;
+use proto::swarm::{check, Swarm, SwarmRun, CHUNK};
+use rayon::ThreadPoolBuilder;
+
+const DT: f32 = 1.0 / TICK_RATE;
+
+/// Circles after `ticks` ticks on a pool of `threads` threads.
+fn run(params: &PloomParams, seed: u64, ticks: usize, threads: usize) -> Vec<Vec2> {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .unwrap();
+    let mut swarm = Swarm::new(params, seed);
+    pool.install(|| {
+        for _ in 0..ticks {
+            swarm.step(params, DT);
+        }
+    });
+    swarm.x
+}
+
+#[test]
+fn results_depend_on_the_seed_not_the_threads() {
+    // several chunks, the last one partial
+    let params = PloomParams {
+        num_plooms: 3000,
+        num_circles: 3,
+        ..PloomParams::default()
+    };
+    assert!(params.num_plooms * params.num_circles > 2 * CHUNK);
+    let one = run(&params, 5, 200, 1);
+    assert_eq!(one, run(&params, 5, 200, 4));
+    assert_eq!(one, run(&params, 5, 200, 7));
+    assert_ne!(one, run(&params, 6, 200, 4));
+    assert!(one.iter().all(|x| x.is_finite()));
+}
+
+#[test]
+fn layout_matches_the_plooms() {
+    let params = PloomParams {
+        num_plooms: 4,
+        num_circles: 5,
+        ..PloomParams::default()
+    };
+    let mut rng = seeded_rng(2);
+    let mut plooms = Ploom::spawn_random_plooms(params.num_plooms, &params, &mut rng);
+    let swarm = Swarm::from_plooms(&plooms, 2).unwrap();
+    assert_eq!((swarm.num_plooms(), swarm.len()), (4, 20));
+    for (p, ploom) in plooms.iter().enumerate() {
+        assert_eq!(swarm.focus[p], ploom.focus);
+        let circles: Vec<Vec2> = ploom.circles.iter().map(|c| c.x).collect();
+        assert_eq!(swarm.circles(p), &circles[..]);
+    }
+    assert_eq!(swarm.lerp(7, 0.0), plooms[1].circles[2].last);
+
+    plooms[2].circles.pop();
+    let e = Swarm::from_plooms(&plooms, 2).err().unwrap();
+    assert!(e.contains("ploom 2"), "{}", e);
+    assert!(Swarm::from_plooms(&[], 2).unwrap().is_empty());
+}
+
+#[test]
+fn new_circles_start_around_their_focus() {
+    let params = PloomParams {
+        num_plooms: 200,
+        num_circles: 4,
+        ..PloomParams::default()
+    };
+    let swarm = Swarm::new(&params, 9);
+    for p in 0..swarm.num_plooms() {
+        for x in swarm.circles(p) {
+            assert!(x.distance(swarm.focus[p]) < 6.0 * params.ploom);
+        }
+    }
+    assert_eq!(swarm.x, Swarm::new(&params, 9).x);
+}
+
+#[test]
+fn runs_write_back_what_ploom_would() {
+    // the reset period and the spread are `dynamics`', only the write back
+    // and its events are the swarm's own
+    let params = PloomParams {
+        num_plooms: 30,
+        ..PloomParams::default()
+    };
+    let decays = (params.threshold / params.ploom).ln() / per_tick(params.decay, DT).ln();
+    let period = decays.ceil() as usize + 1;
+    let mut rng = seeded_rng(4);
+    let mut plooms = Ploom::spawn_random_plooms(params.num_plooms, &params, &mut rng);
+    let mut expected = Ploom::spawn_random_plooms(params.num_plooms, &params, &mut seeded_rng(4));
+    let mut run = SwarmRun::new(2, 4);
+    let mut events = Events::default();
+    events.collect(&mut plooms); // new plooms spawn their foci
+    events.drain();
+    for tick in 1..=period * 2 {
+        let resets = run.step(&mut plooms, &params, DT).unwrap();
+        events.collect(&mut plooms);
+        let spawns = events.iter().filter(|e| e.kind == Lifecycle::FocusSpawn);
+        assert_eq!(spawns.count(), resets, "tick {}", tick);
+        if tick < period {
+            // no teleport yet, the foci and sigmas are deterministic
+            for (ploom, expected) in plooms.iter().zip(expected.iter_mut()) {
+                expected.update(&params, DT, &mut rng);
+                assert_eq!(ploom.focus, expected.focus);
+                assert_eq!(ploom.sigma, expected.sigma);
+            }
+        }
+        events.drain();
+    }
+}
+
+#[test]
+fn runs_pick_up_outside_changes_whatever_the_threads() {
+    let params = PloomParams {
+        num_plooms: 40,
+        num_circles: 3,
+        ..PloomParams::default()
+    };
+    let run = |threads| {
+        let mut plooms = Ploom::spawn_random_plooms(params.num_plooms, &params, &mut seeded_rng(8));
+        let mut run = SwarmRun::new(threads, 8);
+        for tick in 0..100 {
+            if tick == 50 {
+                plooms.remove(3);
+                plooms[0].focus = Vec2::ZERO;
+            }
+            run.step(&mut plooms, &params, DT).unwrap();
+            if tick == 50 && plooms[0].sigma != params.ploom {
+                assert_eq!(plooms[0].focus, plooms[0].v * DT);
+            }
+        }
+        plooms
+            .iter()
+            .flat_map(|p| p.circles.iter().map(|c| c.x))
+            .collect::<Vec<Vec2>>()
+    };
+    let one = run(1);
+    assert_eq!(one.len(), 39 * 3);
+    assert_eq!(one, run(3));
+}
+
+#[test]
+fn runs_honour_outside_resets_and_refuse_boundaries() {
+    let params = PloomParams {
+        num_plooms: 10,
+        ..PloomParams::default()
+    };
+    let mut plooms = Ploom::spawn_random_plooms(params.num_plooms, &params, &mut seeded_rng(2));
+    let mut run = SwarmRun::new(2, 2);
+    run.step(&mut plooms, &params, DT).unwrap();
+    let mut events = Events::default();
+    events.collect(&mut plooms);
+    events.drain();
+
+    // what OSC `/ploom/reset` does
+    plooms[4].sigma = 0.0;
+    assert_eq!(run.step(&mut plooms, &params, DT), Ok(1));
+    events.collect(&mut plooms);
+    assert!(events.has(4, Lifecycle::PloomReset));
+    assert!(events.has(4, Lifecycle::FocusSpawn));
+    assert_eq!(plooms[4].sigma, params.ploom);
+
+    let wrap = PloomParams {
+        boundary: Boundary::Wrap,
+        ..params
+    };
+    assert!(check(&wrap).is_err());
+    assert!(run.step(&mut plooms, &wrap, DT).is_err());
+}
@@ -1,25 +1,54 @@
 use nannou::prelude::*;
+use proto::accum::AccumWindow;
+use proto::clock::Clock;
+use proto::offline::Offline;
+use proto::paint::Painter;
+use proto::plotter::PlotExport;
+use proto::preset::Preset;
+use proto::record::{BallState, Recorder, Replay, Snapshot};
+use proto::rng::{capture_name, seed_from_args, seeded_rng};
 use rand::distributions::{Distribution, Normal};
+use rand::rngs::StdRng;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
 
 const WINDOW_SIZE: u32 = 1000;
-const NUM_CELLS: usize = 2;
 const BALL_COUNT: usize = 10;
 const BALL_SIZE: f32 = 4.0;
-
-const BALL_V: f32 = 1.0;
 const ALPHA: f32 = 0.25;
-const DT: f32 = 0.3;
 
-fn rand_normal_vec2(mu: f32, var: f32) -> Vec2 {
+/// What a preset file can set, `NUM_CELLS`, `BALL_V` and `DT` used to be consts.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+struct Params {
+    num_cells: usize,
+    ball_v: f32,
+    dt: f32, // simulated time per second
+}
+
+impl Default for Params {
+    fn default() -> Params {
+        Params {
+            num_cells: 2,
+            ball_v: 1.0,
+            dt: 18.0,
+        }
+    }
+}
+
+fn rand_normal_vec2(rng: &mut StdRng, mu: f32, var: f32) -> Vec2 {
     let normal = Normal::new(mu as f64, var as f64);
-    vec2(
-        normal.sample(&mut rand::thread_rng()) as f32,
-        normal.sample(&mut rand::thread_rng()) as f32,
-    )
+    vec2(normal.sample(rng) as f32, normal.sample(rng) as f32)
 }
 
 fn main() {
-    nannou::app(model).update(update).run();
+    match Offline::from_args() {
+        Some(offline) => {
+            let mut model = Model::new(seed_from_args());
+            offline.run(model.seed, WINDOW_SIZE, &mut model, advance, paint);
+        }
+        None => nannou::app(model).update(update).run(),
+    }
 }
 
 struct Poly {
@@ -32,6 +61,7 @@ struct Poly {
 
 struct Ball {
     loc: Vec2,
+    last: Vec2,
     v: Vec2,
 }
 
@@ -41,25 +71,28 @@ struct Cell {
 }
 
 impl Ball {
-    fn new(loc: Vec2) -> Ball {
+    fn new(loc: Vec2, ball_v: f32, rng: &mut StdRng) -> Ball {
         Ball {
-            loc: loc,
-            v: rand_normal_vec2(0.0, BALL_V),
+            loc,
+            last: loc,
+            v: rand_normal_vec2(rng, 0.0, ball_v),
         }
     }
 
-    fn spawn_balls(loc: Vec2) -> Vec<Ball> {
-        (0..BALL_COUNT).map(|_| Ball::new(loc)).collect()
+    fn spawn_balls(loc: Vec2, ball_v: f32, rng: &mut StdRng) -> Vec<Ball> {
+        (0..BALL_COUNT)
+            .map(|_| Ball::new(loc, ball_v, rng))
+            .collect()
     }
 }
 
 impl Cell {
-    fn new(i: usize) -> Cell {
-        let row: usize = i % NUM_CELLS;
-        let column: usize = i / NUM_CELLS;
+    fn new(i: usize, params: &Params, rng: &mut StdRng) -> Cell {
+        let row: usize = i % params.num_cells;
+        let column: usize = i / params.num_cells;
 
-        let delta: f32 = WINDOW_SIZE as f32 / NUM_CELLS as f32;
-        let min_coord: f32 = -1.0 * WINDOW_SIZE as f32 / 2.0 + delta / 4.0;
+        let delta: f32 = WINDOW_SIZE as f32 / params.num_cells as f32;
+        let min_coord: f32 = -(WINDOW_SIZE as f32) / 2.0 + delta / 4.0;
         let coord: Vec2 = vec2(
             min_coord + row as f32 * delta,
             min_coord + column as f32 * delta,
@@ -72,7 +105,8 @@ impl Cell {
             color: Rgba::new(0.05490196, 0.61176471, 0.36078431, 0.65),
         };
 
-        let random_balls: Vec<Ball> = Ball::spawn_balls(coord + vec2(delta / 4.0, delta / 4.0));
+        let random_balls: Vec<Ball> =
+            Ball::spawn_balls(coord + vec2(delta / 4.0, delta / 4.0), params.ball_v, rng);
         Cell {
             poly: polygon,
             balls: random_balls,
@@ -82,26 +116,60 @@ impl Cell {
     fn wall_check(&mut self) {
         for ball in &mut self.balls {
             if ball.loc.x < self.poly.p2.x || ball.loc.x > self.poly.p4.x {
-                ball.v.x *= -1.0;
+                ball.v.x = -ball.v.x;
             }
             if ball.loc.y < self.poly.p2.y || ball.loc.y > self.poly.p4.y {
-                ball.v.y *= -1.0;
+                ball.v.y = -ball.v.y;
             }
         }
     }
 }
 
 struct Model {
+    params: Params,
     cells: Vec<Cell>,
+    seed: u64,
+    plot: Option<PlotExport>,
+    record: Option<Recorder>,
+    replay: Option<Replay>, // drives the balls instead of the simulation
+    clock: Clock,
+    preset: Preset,
+    hdr: Option<RefCell<AccumWindow>>, // `--trails` and friends in the window
 }
 
 impl Model {
-    fn new() -> Model {
-        let cells: Vec<Cell> = (0..NUM_CELLS * NUM_CELLS).map(|i| Cell::new(i)).collect();
-        Model { cells }
+    fn new(seed: u64) -> Model {
+        let mut preset = Preset::from_args(seed);
+        let params = preset.load_or(Params::default());
+        Model {
+            cells: spawn_cells(&params, seed),
+            params,
+            seed,
+            plot: PlotExport::from_args(seed, WINDOW_SIZE),
+            record: Recorder::from_args(),
+            replay: Replay::from_args(),
+            clock: Clock::default(),
+            hdr: None,
+            preset,
+        }
+    }
+
+    /// `dt` applies as is, a new layout or ball speed respawns the cells.
+    fn set_params(&mut self, params: Params) {
+        if params.num_cells != self.params.num_cells || params.ball_v != self.params.ball_v {
+            self.cells = spawn_cells(&params, self.seed);
+        }
+        self.params = params;
     }
 }
 
+fn spawn_cells(params: &Params, seed: u64) -> Vec<Cell> {
+    let mut rng = seeded_rng(seed);
+    (0..params.num_cells * params.num_cells)
+        .map(|i| Cell::new(i, params, &mut rng))
+        .collect()
+}
+
 fn model(app: &App) -> Model {
     app.new_window()
         .size(WINDOW_SIZE, WINDOW_SIZE)
@@ -110,51 +178,148 @@ fn model(app: &App) -> Model {
         .build()
         .unwrap();
 
-    Model::new()
+    let mut model = Model::new(seed_from_args());
+    model.hdr = AccumWindow::from_args(app, WINDOW_SIZE).map(RefCell::new);
+    model
 }
 
-fn update(_app: &App, model: &mut Model, _update: Update) {
-    for cell in &mut model.cells {
-        cell.wall_check();
-        for ball in &mut cell.balls {
-            ball.loc += ball.v * DT
+fn update(_app: &App, model: &mut Model, update: Update) {
+    if let Some(params) = model.preset.poll() {
+        model.set_params(params);
+    }
+    advance(model, update.since_last.as_secs_f64());
+}
+
+fn advance(model: &mut Model, seconds: f64) {
+    for _ in 0..model.clock.advance(seconds) {
+        step(model);
+    }
+}
+
+fn step(model: &mut Model) {
+    let dt = model.clock.dt();
+    match &mut model.replay {
+        Some(replay) => match replay.advance(dt) {
+            Some(snapshot) => restore_balls(&mut model.cells, &snapshot.balls),
+            None => hold_balls(&mut model.cells),
+        },
+        None => {
+            for cell in model.cells.iter_mut() {
+                cell.wall_check();
+                for ball in cell.balls.iter_mut() {
+                    ball.last = ball.loc;
+                    ball.loc += ball.v * model.params.dt * dt;
+                }
+            }
+        }
+    }
+    if let Some(plot) = &mut model.plot {
+        for (c, cell) in model.cells.iter().enumerate() {
+            for (i, ball) in cell.balls.iter().enumerate() {
+                plot.trace(c * BALL_COUNT + i, ball.loc, false);
+            }
+        }
+        plot.end_tick();
+    }
+    if let Some(record) = &mut model.record {
+        let cells = &model.cells;
+        let snapshot = || Snapshot {
+            balls: cells
+                .iter()
+                .enumerate()
+                .flat_map(|(c, cell)| {
+                    cell.balls
+                        .iter()
+                        .enumerate()
+                        .map(move |(i, ball)| ball_state(c, i, ball))
+                })
+                .collect(),
+            ..Snapshot::default()
+        };
+        if let Err(e) = record.record(dt, snapshot) {
+            eprintln!("record: {}", e);
+        }
+    }
+}
+
+fn ball_state(cell: usize, index: usize, ball: &Ball) -> BallState {
+    BallState {
+        cell,
+        index,
+        loc: ball.loc.to_array(),
+        v: ball.v.to_array(),
+    }
+}
+
+/// Moves every recorded ball to where it was, the rest stay put.
+fn restore_balls(cells: &mut [Cell], balls: &[BallState]) {
+    for state in balls {
+        if let Some(ball) = cells
+            .get_mut(state.cell)
+            .and_then(|cell| cell.balls.get_mut(state.index))
+        {
+            ball.last = ball.loc;
+            ball.loc = Vec2::from(state.loc);
+            ball.v = Vec2::from(state.v);
+        }
+    }
+}
+
+/// Leaves every ball where it is on a tick without a new snapshot, like
+/// `hold_plooms`.
+fn hold_balls(cells: &mut [Cell]) {
+    for cell in cells.iter_mut() {
+        for ball in cell.balls.iter_mut() {
+            ball.last = ball.loc;
         }
     }
 }
 
 fn view(app: &App, model: &Model, frame: Frame) {
-    let draw = app.draw();
+    match &model.hdr {
+        Some(hdr) => hdr.borrow_mut().view(app, &frame, |accum| {
+            paint(accum, model, frame.nth(), app.time)
+        }),
+        None => {
+            let mut draw = app.draw();
+            paint(&mut draw, model, frame.nth(), app.time);
+            draw.to_frame(app, &frame).unwrap();
+        }
+    }
+}
 
-    if frame.nth() == 0 {
-        draw.rect()
-            .wh(app.window_rect().wh())
-            .rgba(0.18431373, 0.19215686, 0.29019608, ALPHA);
+fn paint<P: Painter>(p: &mut P, model: &Model, nth: u64, time: f32) {
+    if nth == 0 {
+        p.rect(
+            Vec2::ZERO,
+            vec2(WINDOW_SIZE as f32, WINDOW_SIZE as f32),
+            Rgba::new(0.18431373, 0.19215686, 0.29019608, ALPHA),
+        );
     }
 
-    let time = app.time;
-    draw_cells(&draw, &model, time);
-    draw.to_frame(app, &frame).unwrap();
+    draw_cells(p, model, time);
 }
 
-fn draw_cells(draw: &Draw, model: &Model, _time: f32) {
+fn draw_cells<P: Painter>(p: &mut P, model: &Model, _time: f32) {
+    let alpha = model.clock.alpha();
     for cell in &model.cells {
-        draw.quad().color(cell.poly.color).points(
-            cell.poly.p1,
-            cell.poly.p2,
-            cell.poly.p3,
-            cell.poly.p4,
+        p.quad(
+            [cell.poly.p1, cell.poly.p2, cell.poly.p3, cell.poly.p4],
+            cell.poly.color,
         );
         for ball in &cell.balls {
-            draw.ellipse().xy(ball.loc).radius(BALL_SIZE).color(WHITE);
+            p.ellipse(ball.last.lerp(ball.loc, alpha), BALL_SIZE, WHITE);
         }
     }
 }
-fn key_released(app: &App, _model: &mut Model, key: Key) {
+fn key_released(app: &App, model: &mut Model, key: Key) {
     match key {
         Key::S => {
-            app.main_window()
-                .capture_frame(app.exe_name().unwrap() + ".png");
+            let name = capture_name(&app.exe_name().unwrap(), model.seed, app.elapsed_frames());
+            app.main_window().capture_frame(name);
         }
+        // write the running parameters to the preset file
+        Key::W => model.preset.save(&model.params),
         _other_key => {}
     }
 }
@@ -1,32 +1,79 @@
 use nannou::prelude::*;
+use proto::accum::AccumWindow;
+use proto::clock::{per_tick, Clock};
+use proto::offline::Offline;
+use proto::paint::Painter;
+use proto::palette::Palette;
+use proto::plotter::PlotExport;
+use proto::preset::Preset;
+use proto::record::{BallState, Recorder, Replay, Snapshot};
+use proto::rng::{capture_name, seed_from_args, seeded_rng};
 use rand::distributions::{Distribution, Normal};
+use rand::rngs::StdRng;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
 use std::collections::HashMap;
 
 const WINDOW_SIZE: u32 = 1000;
-const NUM_CELLS: usize = 3;
 const BALL_COUNT: usize = 9;
 const BALL_SIZE: f32 = 4.0;
 const POCKET_SIZE: f32 = 10.0;
-const NUM_PARTICLES: usize = 10;
 
-const BALL_V: f32 = 20.0;
+const FRICTION: f32 = 0.74; // fraction of the velocity left after a second
+const RING_SPEED: f32 = 60.0; // pocket ring growth, px per second
 const ALPHA: f32 = 0.1;
-const DT: f32 = 0.3;
+
+/// What a preset file can set, `NUM_CELLS`, `BALL_V` and `DT` used to be consts.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+struct Params {
+    num_cells: usize,
+    ball_v: f32,
+    dt: f32, // simulated time per second
+}
+
+impl Default for Params {
+    fn default() -> Params {
+        Params {
+            num_cells: 3,
+            ball_v: 20.0,
+            dt: 18.0,
+        }
+    }
+}
 
 // Opening scene there is a pool ball that flashes behind the squares with a flash
 // Balls ordered in a triangle (maybe)
 // If a ball goes in a pocket, we want to visualize it bellow the pool table as a colored ellipse
 
-fn rand_normal_vec2(mu: f32, var: f32) -> Vec2 {
+/// Ball `i` takes the `i`th of nine colors.
+fn pool_balls() -> Palette {
+    Palette::swatch(&[
+        Rgba::new(0.835, 0.125, 0.125, 1.0),
+        Rgba::new(0.125, 0.235, 0.835, 1.0),
+        Rgba::new(0.921, 0.839, 0.078, 1.0),
+        Rgba::new(0.666, 0.078, 0.921, 1.0),
+        Rgba::new(0.584, 0.352, 0.156, 1.0),
+        Rgba::new(0.109, 0.588, 0.090, 1.0),
+        Rgba::new(0.960, 0.505, 0.039, 1.0),
+        Rgba::new(0.0, 0.0, 0.0, 1.0),
+        Rgba::new(1.0, 1.0, 1.0, 1.0),
+    ])
+}
+
+fn rand_normal_vec2(rng: &mut StdRng, mu: f32, var: f32) -> Vec2 {
     let normal = Normal::new(mu as f64, var as f64);
-    vec2(
-        normal.sample(&mut rand::thread_rng()) as f32,
-        normal.sample(&mut rand::thread_rng()) as f32,
-    )
+    vec2(normal.sample(rng) as f32, normal.sample(rng) as f32)
 }
 
 fn main() {
-    nannou::app(model).update(update).run();
+    match Offline::from_args() {
+        Some(offline) => {
+            let mut model = Model::new(seed_from_args());
+            offline.run(model.seed, WINDOW_SIZE, &mut model, advance, paint);
+        }
+        None => nannou::app(model).update(update).run(),
+    }
 }
 
 struct Poly {
@@ -37,6 +84,7 @@ struct Poly {
 #[derive(Copy, Clone)]
 struct Ball {
     loc: Vec2,
+    last: Vec2,
     v: Vec2,
     color: Rgba,
 }
@@ -49,11 +97,7 @@ struct Circle {
 
 impl Circle {
     fn new(loc: Vec2, color: Rgba) -> Circle {
-        Circle {
-            loc: loc,
-            color: color,
-            r: 1.0,
-        }
+        Circle { loc, color, r: 1.0 }
     }
 }
 struct Cell {
@@ -64,39 +108,46 @@ struct Cell {
 }
 
 impl Ball {
-    fn new(loc: Vec2, color: Rgba) -> Ball {
-        let mut v: Vec2 = rand_normal_vec2(0.0, 1.0);
+    fn new(loc: Vec2, color: Rgba, ball_v: f32, rng: &mut StdRng) -> Ball {
+        let mut v: Vec2 = rand_normal_vec2(rng, 0.0, 1.0);
         let mag: f32 = (v.x * v.x + v.y * v.y).sqrt();
         v /= mag;
-        v *= BALL_V;
+        v *= ball_v;
 
         Ball {
-            loc: loc,
-            v: v,
-            color: color,
+            loc,
+            last: loc,
+            v,
+            color,
         }
     }
 
-    fn spawn_balls(loc: Vec2, colors: Vec<Rgba>) -> HashMap<usize, Ball> {
+    fn spawn_balls(
+        loc: Vec2,
+        palette: &Palette,
+        ball_v: f32,
+        rng: &mut StdRng,
+    ) -> HashMap<usize, Ball> {
         let mut balls = HashMap::new();
 
         for i in 0..BALL_COUNT {
-            balls.insert(i, Ball::new(loc, colors[i % colors.len()]));
+            let color = palette.rgba((i as f32 + 0.5) / BALL_COUNT as f32, 1.0);
+            balls.insert(i, Ball::new(loc, color, ball_v, rng));
         }
         balls
     }
 }
 
 impl Cell {
-    fn new(i: usize) -> Cell {
-        let row: usize = i % NUM_CELLS;
-        let column: usize = i / NUM_CELLS;
+    fn new(i: usize, params: &Params, palette: &Palette, rng: &mut StdRng) -> Cell {
+        let row: usize = i % params.num_cells;
+        let column: usize = i / params.num_cells;
 
-        let delta_x: f32 = WINDOW_SIZE as f32 / NUM_CELLS as f32;
-        let delta_y: f32 = WINDOW_SIZE as f32 / NUM_CELLS as f32;
+        let delta_x: f32 = WINDOW_SIZE as f32 / params.num_cells as f32;
+        let delta_y: f32 = WINDOW_SIZE as f32 / params.num_cells as f32;
 
-        let min_x: f32 = -1.0 * WINDOW_SIZE as f32 / 2.0 + delta_x / 4.0;
-        let min_y: f32 = -1.0 * WINDOW_SIZE as f32 / 2.0 + delta_y / 4.0;
+        let min_x: f32 = -(WINDOW_SIZE as f32) / 2.0 + delta_x / 4.0;
+        let min_y: f32 = -(WINDOW_SIZE as f32) / 2.0 + delta_y / 4.0;
         let coord: Vec2 = vec2(
             min_x + row as f32 * delta_x,
             min_y + column as f32 * delta_y,
@@ -112,20 +163,12 @@ impl Cell {
             color: Rgba::new(0.05490196, 0.61176471, 0.36078431, 0.65),
         };
 
-        let ball_colors: Vec<Rgba> = vec![
-            Rgba::new(0.835, 0.125, 0.125, 1.0),
-            Rgba::new(0.125, 0.235, 0.835, 1.0),
-            Rgba::new(0.921, 0.839, 0.078, 1.0),
-            Rgba::new(0.666, 0.078, 0.921, 1.0),
-            Rgba::new(0.584, 0.352, 0.156, 1.0),
-            Rgba::new(0.109, 0.588, 0.090, 1.0),
-            Rgba::new(0.960, 0.505, 0.039, 1.0),
-            Rgba::new(0.0, 0.0, 0.0, 1.0),
-            Rgba::new(1.0, 1.0, 1.0, 1.0),
-        ];
-
-        let random_balls: HashMap<usize, Ball> =
-            Ball::spawn_balls(coord + vec2(delta_x / 4.0, delta_y / 4.0), ball_colors);
+        let random_balls: HashMap<usize, Ball> = Ball::spawn_balls(
+            coord + vec2(delta_x / 4.0, delta_y / 4.0),
+            palette,
+            params.ball_v,
+            rng,
+        );
 
         Cell {
             poly: polygon,
@@ -136,17 +179,17 @@ impl Cell {
     }
 
     fn wall_check(&mut self) {
-        for (_, ball) in &mut self.balls {
+        for ball in self.balls.values_mut() {
             if ball.loc.x < self.poly.points[1].x || ball.loc.x > self.poly.points[3].x {
-                ball.v.x *= -1.0;
+                ball.v.x = -ball.v.x;
             }
             if ball.loc.y < self.poly.points[1].y || ball.loc.y > self.poly.points[3].y {
-                ball.v.y *= -1.0;
+                ball.v.y = -ball.v.y;
             }
         }
     }
 
-    fn pocket_check(&mut self) {
+    fn pocket_check(&mut self, num_cells: usize) {
         for pocket in &self.poly.points {
             let mut delete_log: Vec<usize> = Vec::new();
             for (i, ball) in &self.balls {
@@ -156,9 +199,10 @@ impl Cell {
                 if distance < POCKET_SIZE / 2.0 {
                     let num_pocketed: usize = self.pocketed.len();
                     let mut b: Ball = *ball;
-                    let pdelta: f32 = WINDOW_SIZE as f32 / NUM_CELLS as f32 / 20.0;
+                    let pdelta: f32 = WINDOW_SIZE as f32 / num_cells as f32 / 20.0;
                     b.loc = self.poly.points[1]
                         + vec2(pdelta * num_pocketed as f32 + 17.0, -pdelta * 1.5);
+                    b.last = b.loc;
                     self.pocketed.insert(*i, b);
                     delete_log.push(*i);
                     let c: Circle = Circle::new(*pocket, b.color);
@@ -172,13 +216,13 @@ impl Cell {
         }
     }
 
-    fn pocket_event_update(&mut self) {
+    fn pocket_event_update(&mut self, dt: f32) {
         let mut keys_to_delete: Vec<usize> = Vec::new();
         for (i, circle) in &mut self.pocket_events {
             if circle.r >= 25.0 {
                 keys_to_delete.push(*i);
             } else {
-                circle.r += 1.0;
+                circle.r += RING_SPEED * dt;
             }
         }
 
@@ -189,16 +233,53 @@ impl Cell {
 }
 
 struct Model {
+    params: Params,
     cells: Vec<Cell>,
+    seed: u64,
+    plot: Option<PlotExport>,
+    record: Option<Recorder>,
+    replay: Option<Replay>, // drives the balls instead of the simulation
+    clock: Clock,
+    preset: Preset,
+    palette: Palette,
+    hdr: Option<RefCell<AccumWindow>>, // `--trails` and friends in the window
 }
 
 impl Model {
-    fn new() -> Model {
-        let cells: Vec<Cell> = (0..NUM_CELLS * NUM_CELLS).map(|i| Cell::new(i)).collect();
-        Model { cells }
+    fn new(seed: u64) -> Model {
+        let mut preset = Preset::from_args(seed);
+        let params = preset.load_or(Params::default());
+        let palette = Palette::from_args(pool_balls());
+        Model {
+            cells: spawn_cells(&params, &palette, seed),
+            params,
+            seed,
+            plot: PlotExport::from_args(seed, WINDOW_SIZE),
+            record: Recorder::from_args(),
+            replay: Replay::from_args(),
+            clock: Clock::default(),
+            hdr: None,
+            preset,
+            palette,
+        }
+    }
+
+    /// `dt` applies as is, a new layout or ball speed respawns the cells.
+    fn set_params(&mut self, params: Params) {
+        if params.num_cells != self.params.num_cells || params.ball_v != self.params.ball_v {
+            self.cells = spawn_cells(&params, &self.palette, self.seed);
+        }
+        self.params = params;
     }
 }
 
+fn spawn_cells(params: &Params, palette: &Palette, seed: u64) -> Vec<Cell> {
+    let mut rng = seeded_rng(seed);
+    (0..params.num_cells * params.num_cells)
+        .map(|i| Cell::new(i, params, palette, &mut rng))
+        .collect()
+}
+
 fn model(app: &App) -> Model {
     app.new_window()
         .size(WINDOW_SIZE, WINDOW_SIZE)
@@ -207,85 +288,184 @@ fn model(app: &App) -> Model {
         .build()
         .unwrap();
 
-    Model::new()
+    let mut model = Model::new(seed_from_args());
+    model.hdr = AccumWindow::from_args(app, WINDOW_SIZE).map(RefCell::new);
+    model
+}
+
+fn update(_app: &App, model: &mut Model, update: Update) {
+    if let Some(params) = model.preset.poll() {
+        model.set_params(params);
+    }
+    advance(model, update.since_last.as_secs_f64());
+}
+
+fn advance(model: &mut Model, seconds: f64) {
+    for _ in 0..model.clock.advance(seconds) {
+        step(model);
+    }
+}
+
+fn step(model: &mut Model) {
+    let dt = model.clock.dt();
+    match &mut model.replay {
+        Some(replay) => {
+            // pockets only look at where the balls are, before they move
+            for cell in model.cells.iter_mut() {
+                cell.pocket_check(model.params.num_cells);
+                cell.pocket_event_update(dt);
+            }
+            match replay.advance(dt) {
+                Some(snapshot) => restore_balls(&mut model.cells, &snapshot.balls),
+                None => hold_balls(&mut model.cells),
+            }
+        }
+        None => {
+            for cell in model.cells.iter_mut() {
+                cell.wall_check();
+                cell.pocket_check(model.params.num_cells);
+                cell.pocket_event_update(dt);
+                for ball in cell.balls.values_mut() {
+                    ball.last = ball.loc;
+                    ball.loc += ball.v * model.params.dt * dt;
+                    ball.v *= per_tick(FRICTION, dt);
+                }
+            }
+        }
+    }
+    if let Some(plot) = &mut model.plot {
+        for (c, cell) in model.cells.iter().enumerate() {
+            for (i, ball) in cell.balls.iter() {
+                plot.trace(c * BALL_COUNT + i, ball.loc, false);
+            }
+        }
+        plot.end_tick();
+    }
+    if let Some(record) = &mut model.record {
+        let cells = &model.cells;
+        let snapshot = || {
+            let mut balls: Vec<BallState> = cells
+                .iter()
+                .enumerate()
+                .flat_map(|(c, cell)| {
+                    cell.balls
+                        .iter()
+                        .map(move |(&i, ball)| ball_state(c, i, ball))
+                })
+                .collect();
+            balls.sort_by_key(|b| (b.cell, b.index));
+            Snapshot {
+                balls,
+                ..Snapshot::default()
+            }
+        };
+        if let Err(e) = record.record(dt, snapshot) {
+            eprintln!("record: {}", e);
+        }
+    }
+}
+
+fn ball_state(cell: usize, index: usize, ball: &Ball) -> BallState {
+    BallState {
+        cell,
+        index,
+        loc: ball.loc.to_array(),
+        v: ball.v.to_array(),
+    }
+}
+
+/// Moves every recorded ball still on the table to where it was.
+fn restore_balls(cells: &mut [Cell], balls: &[BallState]) {
+    for state in balls {
+        if let Some(ball) = cells
+            .get_mut(state.cell)
+            .and_then(|cell| cell.balls.get_mut(&state.index))
+        {
+            ball.last = ball.loc;
+            ball.loc = Vec2::from(state.loc);
+            ball.v = Vec2::from(state.v);
+        }
+    }
 }
 
-fn update(_app: &App, model: &mut Model, _update: Update) {
-    for cell in &mut model.cells {
-        cell.wall_check();
-        cell.pocket_check();
-        cell.pocket_event_update();
-        for (_, ball) in &mut cell.balls {
-            ball.loc += ball.v * DT;
-            ball.v *= 0.995;
+/// Leaves every ball where it is on a tick without a new snapshot, like
+/// `hold_plooms`.
+fn hold_balls(cells: &mut [Cell]) {
+    for cell in cells.iter_mut() {
+        for ball in cell.balls.values_mut() {
+            ball.last = ball.loc;
         }
     }
 }
 
 fn view(app: &App, model: &Model, frame: Frame) {
-    let draw = app.draw();
+    match &model.hdr {
+        Some(hdr) => hdr.borrow_mut().view(app, &frame, |accum| {
+            paint(accum, model, frame.nth(), app.time)
+        }),
+        None => {
+            let mut draw = app.draw();
+            paint(&mut draw, model, frame.nth(), app.time);
+            draw.to_frame(app, &frame).unwrap();
+        }
+    }
+}
 
-    //if frame.nth() == 0 {
-    draw.rect()
-        .wh(app.window_rect().wh())
-        .rgba(0.18431373, 0.19215686, 0.29019608, ALPHA);
+fn paint<P: Painter>(p: &mut P, model: &Model, _nth: u64, time: f32) {
+    //if nth == 0 {
+    p.fade(
+        vec2(WINDOW_SIZE as f32, WINDOW_SIZE as f32),
+        Rgba::new(0.18431373, 0.19215686, 0.29019608, ALPHA),
+    );
     //}
 
-    let time = app.time;
-    draw_cells(&draw, &model, time);
-    draw.to_frame(app, &frame).unwrap();
+    draw_cells(p, model, time);
 }
 
-fn draw_cells(draw: &Draw, model: &Model, _time: f32) {
+fn draw_cells<P: Painter>(p: &mut P, model: &Model, _time: f32) {
+    let alpha = model.clock.alpha();
     for cell in &model.cells {
-        draw.quad().color(cell.poly.color).points(
-            cell.poly.points[0],
-            cell.poly.points[1],
-            cell.poly.points[2],
-            cell.poly.points[3],
+        let points = &cell.poly.points;
+        p.quad(
+            [points[0], points[1], points[2], points[3]],
+            cell.poly.color,
         );
 
         for i in 0..9 {
-            let pdelta: f32 = WINDOW_SIZE as f32 / NUM_CELLS as f32 / 20.0;
-            draw.ellipse()
-                .xy(cell.poly.points[1] + vec2(pdelta * i as f32 + 17.0, -pdelta * 1.5))
-                .radius(BALL_SIZE * 1.5)
-                .color(WHITE);
+            let pdelta: f32 = WINDOW_SIZE as f32 / model.params.num_cells as f32 / 20.0;
+            p.ellipse(
+                points[1] + vec2(pdelta * i as f32 + 17.0, -pdelta * 1.5),
+                BALL_SIZE * 1.5,
+                WHITE,
+            );
         }
 
-        for (_, circle) in &cell.pocket_events {
-            draw.ellipse()
-                .xy(circle.loc)
-                .radius(circle.r)
-                .color(circle.color);
+        for circle in cell.pocket_events.values() {
+            p.ellipse(circle.loc, circle.r, circle.color);
         }
 
-        for p in &cell.poly.points {
-            draw.ellipse().xy(*p).radius(POCKET_SIZE).color(BLACK);
+        for pocket in points {
+            p.ellipse(*pocket, POCKET_SIZE, BLACK);
         }
 
-        for (_, ball) in &cell.balls {
-            draw.ellipse()
-                .xy(ball.loc)
-                .radius(BALL_SIZE)
-                .color(ball.color);
+        for ball in cell.balls.values() {
+            p.ellipse(ball.last.lerp(ball.loc, alpha), BALL_SIZE, ball.color);
         }
 
-        for (_, ball) in &cell.pocketed {
-            draw.ellipse()
-                .xy(ball.loc)
-                .radius(BALL_SIZE)
-                .color(ball.color);
+        for ball in cell.pocketed.values() {
+            p.ellipse(ball.loc, BALL_SIZE, ball.color);
         }
     }
 }
 
-fn key_released(app: &App, _model: &mut Model, key: Key) {
+fn key_released(app: &App, model: &mut Model, key: Key) {
     match key {
         Key::S => {
-            app.main_window()
-                .capture_frame(app.exe_name().unwrap() + ".png");
+            let name = capture_name(&app.exe_name().unwrap(), model.seed, app.elapsed_frames());
+            app.main_window().capture_frame(name);
         }
+        // write the running parameters to the preset file
+        Key::W => model.preset.save(&model.params),
         _other_key => {}
     }
 }